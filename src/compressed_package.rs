@@ -0,0 +1,282 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::errors::{ParseError, Result};
+use crate::uasset_parser::UassetParser;
+use crate::uasset_summary::{CompressedChunk, UassetSummary};
+
+/// Tag at the start of every compressed chunk, the package file tag.
+pub const COMPRESSED_CHUNK_TAG: i64 = 0x9E2A83C1;
+/// Tag of chunks that also store the compression format.
+pub const COMPRESSED_CHUNK_V2_TAG: i64 = 0x22222222_9E2A83C1;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ECompressionFlags {
+    None = 0x00,
+    Zlib = 0x01,
+    Gzip = 0x02,
+    Custom = 0x04,
+    BiasMemory = 0x10,
+    BiasSpeed = 0x20,
+    SourceIsPadded = 0x80,
+}
+
+const COMPRESSION_FORMAT_MASK: u32 = 0x0F;
+
+/// Decompresses the blocks of compressed chunks, implement it to read packages
+/// compressed with a format the crate doesn't handle.
+pub trait Decompressor: Send + Sync {
+    fn decompress(&self, compressed: &[u8], uncompressed_size: usize) -> Result<Vec<u8>>;
+}
+
+pub struct ZlibDecompressor;
+
+impl Decompressor for ZlibDecompressor {
+    fn decompress(&self, compressed: &[u8], uncompressed_size: usize) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(uncompressed_size);
+        flate2::read::ZlibDecoder::new(compressed)
+            .read_to_end(&mut data)
+            .map_err(|e| ParseError::DecompressionFailed(e.to_string()))?;
+        Ok(data)
+    }
+}
+
+pub struct GzipDecompressor;
+
+impl Decompressor for GzipDecompressor {
+    fn decompress(&self, compressed: &[u8], uncompressed_size: usize) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(uncompressed_size);
+        flate2::read::GzDecoder::new(compressed)
+            .read_to_end(&mut data)
+            .map_err(|e| ParseError::DecompressionFailed(e.to_string()))?;
+        Ok(data)
+    }
+}
+
+/// Decompressor for the format in the `compression_flags` of a summary.
+pub fn decompressor_for_flags(flags: u32) -> Result<Arc<dyn Decompressor>> {
+    match flags & COMPRESSION_FORMAT_MASK {
+        f if f == ECompressionFlags::Zlib as u32 => Ok(Arc::new(ZlibDecompressor)),
+        f if f == ECompressionFlags::Gzip as u32 => Ok(Arc::new(GzipDecompressor)),
+        f => Err(ParseError::UnsupportedCompressionMethod(format!(
+            "compression flags {:#x}",
+            f
+        ))),
+    }
+}
+
+/// Reader over the uncompressed data of a package saved with compressed
+/// chunks, parse the package from it like from any other reader.
+pub struct CompressedPackageReader<R> {
+    reader: R,
+    chunks: Vec<CompressedChunk>,
+    decompressor: Arc<dyn Decompressor>,
+    position: u64,
+    /// Index and data of the last decompressed chunk
+    cached: Option<(usize, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> CompressedPackageReader<R> {
+    /// Reads the chunks from the summary of the package, the decompressor is
+    /// picked from its compression flags.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let summary = Self::read_summary(&mut reader)?;
+        let decompressor = decompressor_for_flags(summary.compression_flags)?;
+        Ok(Self::from_chunks(
+            reader,
+            summary.compressed_chunks,
+            decompressor,
+        ))
+    }
+
+    /// Reads the chunks from the summary of the package and decompresses them
+    /// with `decompressor`, whatever the compression flags.
+    pub fn with_decompressor(mut reader: R, decompressor: Arc<dyn Decompressor>) -> Result<Self> {
+        let summary = Self::read_summary(&mut reader)?;
+        Ok(Self::from_chunks(
+            reader,
+            summary.compressed_chunks,
+            decompressor,
+        ))
+    }
+
+    pub fn from_chunks(
+        reader: R,
+        chunks: Vec<CompressedChunk>,
+        decompressor: Arc<dyn Decompressor>,
+    ) -> Self {
+        Self {
+            reader,
+            chunks,
+            decompressor,
+            position: 0,
+            cached: None,
+        }
+    }
+
+    pub fn chunks(&self) -> &[CompressedChunk] {
+        &self.chunks
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_summary(reader: &mut R) -> Result<UassetSummary> {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut parser = UassetParser::new(reader);
+        let summary: UassetSummary = parser.read()?;
+        Ok(summary)
+    }
+
+    /// Size of the uncompressed package.
+    fn len(&self) -> u64 {
+        self.chunks
+            .iter()
+            .map(|chunk| (chunk.uncompressed_offset as i64 + chunk.uncompressed_size as i64) as u64)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Data before the first chunk is stored uncompressed, all of it when
+    /// there are no chunks.
+    fn uncompressed_end(&self) -> u64 {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.uncompressed_offset as u64)
+            .min()
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Reads a chunk: the tag and block size, the total sizes, the sizes of
+    /// every block and then the compressed blocks.
+    fn decompress_chunk(&mut self, index: usize) -> Result<Vec<u8>> {
+        let chunk = self.chunks[index];
+        let file_size = self.reader.seek(SeekFrom::End(0))?;
+        self.reader
+            .seek(SeekFrom::Start(chunk.compressed_offset as u64))?;
+
+        let tag = self.reader.read_i64::<LittleEndian>()?;
+        let block_size = self.reader.read_i64::<LittleEndian>()?;
+        if tag == COMPRESSED_CHUNK_V2_TAG {
+            // Index of the compression format, the decompressor decides it
+            self.reader.read_u8()?;
+        } else if tag != COMPRESSED_CHUNK_TAG {
+            return Err(ParseError::DecompressionFailed(format!(
+                "invalid tag {:#x} for the chunk at {}",
+                tag, chunk.compressed_offset
+            )));
+        }
+        let _total_compressed_size = self.reader.read_i64::<LittleEndian>()?;
+        let total_size = self.reader.read_i64::<LittleEndian>()?;
+        if block_size <= 0 || total_size != chunk.uncompressed_size as i64 {
+            return Err(ParseError::DecompressionFailed(format!(
+                "invalid sizes for the chunk at {}",
+                chunk.compressed_offset
+            )));
+        }
+
+        // Every block has its sizes in the table, 16 bytes each
+        let block_count = (total_size + block_size - 1) / block_size;
+        let mut remaining = file_size.saturating_sub(self.reader.stream_position()?);
+        if block_count as u64 > remaining / 16 {
+            return Err(ParseError::DecompressionFailed(format!(
+                "{} blocks don't fit in the chunk at {}",
+                block_count, chunk.compressed_offset
+            )));
+        }
+        remaining -= block_count as u64 * 16;
+        let mut blocks = Vec::with_capacity(block_count as usize);
+        for _ in 0..block_count {
+            let compressed_size = self.reader.read_i64::<LittleEndian>()?;
+            let size = self.reader.read_i64::<LittleEndian>()?;
+            if !(0..=remaining as i64).contains(&compressed_size)
+                || !(0..=block_size).contains(&size)
+            {
+                return Err(ParseError::DecompressionFailed(format!(
+                    "invalid block in the chunk at {}",
+                    chunk.compressed_offset
+                )));
+            }
+            remaining -= compressed_size as u64;
+            blocks.push((compressed_size as usize, size as usize));
+        }
+
+        let mut data = Vec::with_capacity(total_size as usize);
+        for (compressed_size, size) in blocks {
+            let mut compressed = vec![0u8; compressed_size];
+            self.reader.read_exact(&mut compressed)?;
+            let block = self.decompressor.decompress(&compressed, size)?;
+            if block.len() != size {
+                return Err(ParseError::DecompressionFailed(format!(
+                    "block of {} bytes instead of {}",
+                    block.len(),
+                    size
+                )));
+            }
+            data.extend(block);
+        }
+        if data.len() != total_size as usize {
+            return Err(ParseError::DecompressionFailed(format!(
+                "chunk of {} bytes instead of {}",
+                data.len(),
+                total_size
+            )));
+        }
+        Ok(data)
+    }
+}
+
+impl<R: Read + Seek> Read for CompressedPackageReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let uncompressed_end = self.uncompressed_end();
+        if self.position < uncompressed_end {
+            let size = buf.len().min((uncompressed_end - self.position) as usize);
+            self.reader.seek(SeekFrom::Start(self.position))?;
+            let read = self.reader.read(&mut buf[..size])?;
+            self.position += read as u64;
+            return Ok(read);
+        }
+
+        let position = self.position;
+        let Some(index) = self.chunks.iter().position(|chunk| {
+            let start = chunk.uncompressed_offset as u64;
+            position >= start && position < start + chunk.uncompressed_size as u64
+        }) else {
+            return Ok(0);
+        };
+
+        if self
+            .cached
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != index)
+        {
+            let data = self
+                .decompress_chunk(index)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            self.cached = Some((index, data));
+        }
+        let (_, data) = self.cached.as_ref().unwrap();
+        let start = (position - self.chunks[index].uncompressed_offset as u64) as usize;
+        let size = buf.len().min(data.len() - start);
+        buf[..size].copy_from_slice(&data[start..start + size]);
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+impl<R: Read + Seek> Seek for CompressedPackageReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))?;
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,564 @@
+use crate::errors::Result;
+use crate::guid::Guid;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
+
+/// Version of a system that serializes its own data, stored in the package
+/// summary next to the object versions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CustomVersion {
+    pub key: Guid,
+    pub version: i32,
+}
+
+impl CustomVersion {
+    /// Name of the version if it is one of the engine's.
+    pub fn name(&self) -> Option<&'static str> {
+        custom_version_name(&self.key)
+    }
+}
+
+/// Custom version with a known key, implemented by the enums listing its
+/// versions so they can be looked up with `custom_version::<T>()`.
+pub trait CustomVersionKey {
+    const KEY: Guid;
+    const NAME: &'static str;
+}
+
+/// Custom versions of the engine, by key.
+pub const KNOWN_CUSTOM_VERSIONS: &[(Guid, &str)] = &[
+    (Guid::new(0, 0, 0, 0xF99D40C1), "UnusedCustomVersionKey"),
+    (
+        Guid::new(0xB0D832E4, 0x1F894F0D, 0xACCF7EB7, 0x36FD4AA2),
+        "FBlueprintsObjectVersion",
+    ),
+    (
+        Guid::new(0xE1C64328, 0xA22C4D53, 0xA36C8E86, 0x6417BD8C),
+        "FBuildObjectVersion",
+    ),
+    (
+        Guid::new(0x375EC13C, 0x06E448FB, 0xB50084F0, 0x262A717E),
+        "FCoreObjectVersion",
+    ),
+    (
+        Guid::new(0xE4B068ED, 0xF49442E9, 0xA231DA0B, 0x2E46BB41),
+        "FEditorObjectVersion",
+    ),
+    (
+        Guid::new(0xCFFC743F, 0x43B04480, 0x939114DF, 0x171D2073),
+        "FFrameworkObjectVersion",
+    ),
+    (
+        Guid::new(0xB02B49B5, 0xBB2044E9, 0xA30432B7, 0x52E40360),
+        "FMobileObjectVersion",
+    ),
+    (
+        Guid::new(0xA4E4105C, 0x59A149B5, 0xA7C540C4, 0x547EDFEE),
+        "FNetworkingObjectVersion",
+    ),
+    (
+        Guid::new(0x39C831C9, 0x5AE647DC, 0x9A449C17, 0x3E1C8E7C),
+        "FOnlineObjectVersion",
+    ),
+    (
+        Guid::new(0x78F01B33, 0xEBEA4F98, 0xB9B484EA, 0xCCB95AA2),
+        "FPhysicsObjectVersion",
+    ),
+    (
+        Guid::new(0x6631380F, 0x2D4D43E0, 0x8009CF27, 0x6956A95A),
+        "FPlatformObjectVersion",
+    ),
+    (
+        Guid::new(0x12F88B9F, 0x88754AFC, 0xA67CD90C, 0x383ABD29),
+        "FRenderingObjectVersion",
+    ),
+    (
+        Guid::new(0xD7296918, 0x1DD64BDD, 0x9DE264A8, 0x3CC13884),
+        "FVRObjectVersion",
+    ),
+    (
+        Guid::new(0xC2A15278, 0xBFE74AFE, 0x6C1790FF, 0x531DF755),
+        "FLoadTimesObjectVersion",
+    ),
+    (
+        Guid::new(0x6EACA3D4, 0x40EC4CC1, 0xB7868BED, 0x09428FC5),
+        "FGeometryObjectVersion",
+    ),
+    (
+        Guid::new(0x29E575DD, 0xE0A34627, 0x9D10D276, 0x232CDCEA),
+        "FAnimPhysObjectVersion",
+    ),
+    (
+        Guid::new(0xAF43A65D, 0x7FD34947, 0x98733E8E, 0xD9C1BB05),
+        "FAnimObjectVersion",
+    ),
+    (
+        Guid::new(0x6B266CEC, 0x1EC74B8F, 0xA30BE4D9, 0x0942FC07),
+        "FReflectionCaptureObjectVersion",
+    ),
+    (
+        Guid::new(0x0DF73D61, 0xA23F47EA, 0xB72789E9, 0x0C41499A),
+        "FAutomationObjectVersion",
+    ),
+    (
+        Guid::new(0x9DFFBCD6, 0x494F0158, 0xE2211282, 0x3C92A888),
+        "FEnterpriseObjectVersion",
+    ),
+    (
+        Guid::new(0xF2AED0AC, 0x9AFE416F, 0x8664AA7F, 0xFA26D6FC),
+        "FNiagaraObjectVersion",
+    ),
+    (
+        Guid::new(0x174F1F0B, 0xB4C645A5, 0xB13F2EE8, 0xD0FB917D),
+        "FDestructionObjectVersion",
+    ),
+    (
+        Guid::new(0x35F94A83, 0xE258406C, 0xA31809F5, 0x9610247C),
+        "FExternalPhysicsCustomObjectVersion",
+    ),
+    (
+        Guid::new(0xB68FC16E, 0x8B1B42E2, 0xB453215C, 0x058844FE),
+        "FExternalPhysicsMaterialCustomObjectVersion",
+    ),
+    (
+        Guid::new(0xB2E18506, 0x4273CFC2, 0xA54EF4BB, 0x758BBA07),
+        "FCineCameraObjectVersion",
+    ),
+    (
+        Guid::new(0x64F58936, 0xFD1B42BA, 0xBA967289, 0xD5D0FA4E),
+        "FVirtualProductionObjectVersion",
+    ),
+    (
+        Guid::new(0x6F0ED827, 0xA6094895, 0x9C91998D, 0x90180EA4),
+        "FMediaFrameworkObjectVersion",
+    ),
+    (
+        Guid::new(0xAFE08691, 0x3A0D4952, 0xB673673B, 0x7CF22D1E),
+        "FPoseDriverCustomVersion",
+    ),
+    (
+        Guid::new(0xCB8AB0CD, 0xE78C4BDE, 0xA8621393, 0x14E9EF62),
+        "FTempCustomVersion",
+    ),
+    (
+        Guid::new(0x2EB5FDBD, 0x01AC4D10, 0x8136F38F, 0x3393A5DA),
+        "FAnimationCustomVersion",
+    ),
+    (
+        Guid::new(0x717F9EE7, 0xE9B0493A, 0x88B39132, 0x1B388107),
+        "FAssetRegistryVersion",
+    ),
+    (
+        Guid::new(0xFB680AF2, 0x59EF4BA3, 0xBAA819B5, 0x73C8443D),
+        "FClothingAssetCustomVersion",
+    ),
+    (
+        Guid::new(0x4A56EB40, 0x10F511DC, 0x92D3347E, 0xB2C96AE7),
+        "FParticleSystemCustomVersion",
+    ),
+    (
+        Guid::new(0xD78A4A00, 0xE8584697, 0xBAA819B5, 0x487D46B4),
+        "FSkeletalMeshCustomVersion",
+    ),
+    (
+        Guid::new(0x5579F886, 0x933A4C1F, 0x83BA087B, 0x6361B92F),
+        "FRecomputeTangentCustomVersion",
+    ),
+    (
+        Guid::new(0x612FBE52, 0xDA53400B, 0x910D4F91, 0x9FB1857C),
+        "FOverlappingVerticesCustomVersion",
+    ),
+    (
+        Guid::new(0x430C4D19, 0x71544970, 0x87699B69, 0xDF90B0E5),
+        "FFoliageCustomVersion",
+    ),
+    (
+        Guid::new(0xAAFE32BD, 0x53954C14, 0xB66A5E25, 0x1032D1DD),
+        "FProceduralFoliageCustomVersion",
+    ),
+    (
+        Guid::new(0xAB965196, 0x45D808FC, 0xB7D7228D, 0x78AD569E),
+        "FLiveLinkCustomVersion",
+    ),
+    (
+        Guid::new(0x7B5AE74C, 0xD2704C10, 0xA9585798, 0x0B212A5A),
+        "FSequencerObjectVersion",
+    ),
+    (
+        Guid::new(0x9C54D522, 0xA8264FBE, 0x94210746, 0x61B482D0),
+        "FReleaseObjectVersion",
+    ),
+    (
+        Guid::new(0x601D1886, 0xAC644F84, 0xAA16D3DE, 0x0DEAC7D6),
+        "FFortniteMainBranchObjectVersion",
+    ),
+    (
+        Guid::new(0xE7086368, 0x6B234C58, 0x84391B70, 0x16265E91),
+        "FFortniteReleaseBranchCustomObjectVersion",
+    ),
+    (
+        Guid::new(0x5B4C06B7, 0x24634AF8, 0x805BBF70, 0xCDF5D0A2),
+        "FFortniteSeasonBranchObjectVersion",
+    ),
+    (
+        Guid::new(0x697DD581, 0xE64F41AB, 0xAA4A51EC, 0xBEB7B628),
+        "FUE5MainStreamObjectVersion",
+    ),
+    (
+        Guid::new(0xD89B5E42, 0x24BD4D46, 0x8412ACA8, 0xDF641779),
+        "FUE5ReleaseStreamObjectVersion",
+    ),
+    (
+        Guid::new(0x59DA5D52, 0x12324948, 0xB8785978, 0x70B8E98B),
+        "FUE5PrivateFrostyStreamObjectVersion",
+    ),
+];
+
+pub fn custom_version_name(key: &Guid) -> Option<&'static str> {
+    KNOWN_CUSTOM_VERSIONS
+        .iter()
+        .find(|(known, _)| known == key)
+        .map(|(_, name)| *name)
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FCoreObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+    MaterialInputNativeSerialize,
+    EnumProperties,
+    SkeletalMaterialEditorDataStripping,
+    FProperties,
+}
+
+impl CustomVersionKey for FCoreObjectVersion {
+    const KEY: Guid = Guid::new(0x375EC13C, 0x06E448FB, 0xB50084F0, 0x262A717E);
+    const NAME: &'static str = "FCoreObjectVersion";
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FEditorObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+    GatheredTextProcessVersionFlagging,
+    GatheredTextPackageCacheFixesV1,
+    RootMetaDataSupport,
+    GatheredTextPackageCacheFixesV2,
+    TextFormatArgumentDataIsVariant,
+    SplineComponentCurvesInStruct,
+    ComboBoxControllerSupportUpdate,
+    RefactorMeshEditorMaterials,
+    AddedFontFaceAssets,
+    UPropertryForMeshSection,
+    WidgetGraphSchema,
+    AddedBackgroundBlurContentSlot,
+    StableUserDefinedEnumDisplayNames,
+    AddedInlineFontFaceAssets,
+    UPropertryForMeshSectionSerialize,
+    FastWidgetTemplates,
+    MaterialThumbnailRenderingChanges,
+    NewSlateClippingSystem,
+    MovieSceneMetaDataSerialization,
+    GatheredTextEditorOnlyPackageLocId,
+    AddedAlwaysSignNumberFormattingOption,
+    AddedMaterialSharedInputs,
+    AddedMorphTargetSectionIndices,
+    SerializeInstancedStaticMeshRenderData,
+    MeshDescriptionNewSerializationMovedToRelease,
+    MeshDescriptionNewAttributeFormat,
+    ChangeSceneCaptureRootComponent,
+    StaticMeshDeprecatedRawMesh,
+    MeshDescriptionBulkDataGuid,
+    MeshDescriptionRemovedHoles,
+    ChangedWidgetComponentWindowVisibilityDefault,
+    CultureInvariantTextSerializationKeyStability,
+    ScrollBarThicknessChange,
+    RemoveLandscapeHoleMaterial,
+    MeshDescriptionTriangles,
+    ComputeWeightedNormals,
+    SkeletalMeshBuildRefactor,
+    SkeletalMeshMoveEditorSourceDataToPrivateAsset,
+    NumberParsingOptionsNumberLimitsAndClamping,
+    SkeletalMeshSourceDataSupport16bitOfMaterialNumber,
+}
+
+impl CustomVersionKey for FEditorObjectVersion {
+    const KEY: Guid = Guid::new(0xE4B068ED, 0xF49442E9, 0xA231DA0B, 0x2E46BB41);
+    const NAME: &'static str = "FEditorObjectVersion";
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FFrameworkObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+    UseBodySetupCollisionProfile,
+    AnimBlueprintSubgraphFix,
+    MeshSocketScaleUtilization,
+    ExplicitAttachmentRules,
+    MoveCompressedAnimDataToTheDDC,
+    FixNonTransactionalPins,
+    SmartNameRefactor,
+    AddSourceReferenceSkeletonToRig,
+    ConstraintInstanceBehaviorParameters,
+    PoseAssetSupportPerBoneMask,
+    PhysAssetUseSkeletalBodySetup,
+    RemoveSoundWaveCompressionName,
+    AddInternalClothingGraphicalSkinning,
+    WheelOffsetIsFromWheel,
+    MoveCurveTypesToSkeleton,
+    CacheDestructibleOverlaps,
+    GeometryCacheMissingMaterials,
+    LODsUseResolutionIndependentScreenSize,
+    BlendSpacePostLoadSnapToGrid,
+    SupportBlendSpaceRateScale,
+    LODHysteresisUseResolutionIndependentScreenSize,
+    ChangeAudioComponentOverrideSubtitlePriorityDefault,
+    HardSoundReferences,
+    EnforceConstInAnimBlueprintFunctionGraphs,
+    InputKeySelectorTextStyle,
+    EdGraphPinContainerType,
+    ChangeAssetPinsToString,
+    LocalVariablesBlueprintVisible,
+    RemoveUfieldNext,
+    UserDefinedStructsBlueprintVisible,
+    PinsStoreFName,
+    UserDefinedStructsStoreDefaultInstance,
+    FunctionTerminatorNodesUseMemberReference,
+    EditableEventsUseConstRefParameters,
+    BlueprintGeneratedClassIsAlwaysAuthoritative,
+    EnforceBlueprintFunctionVisibility,
+    StoringUCSSerializationIndex,
+}
+
+impl CustomVersionKey for FFrameworkObjectVersion {
+    const KEY: Guid = Guid::new(0xCFFC743F, 0x43B04480, 0x939114DF, 0x171D2073);
+    const NAME: &'static str = "FFrameworkObjectVersion";
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FReleaseObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+    StaticMeshExtendedBoundsFix,
+    NoSyncAsyncPhysAsset,
+    LevelTransArrayConvertedToTArray,
+    AddComponentNodeTemplateUniqueNames,
+    UPropertryForMeshSectionSerialize,
+    ConvertHLODScreenSize,
+    SpeedTreeBillboardSectionInfoFixup,
+    EventSectionParameterStringAssetRef,
+    SkyLightRemoveMobileIrradianceMap,
+    RenameNoTwistToAllowTwistInTwoBoneIK,
+    MaterialLayersParameterSerializationRefactor,
+    AddSkeletalMeshSectionDisable,
+    RemovedMaterialSharedInputCollection,
+    HISMCClusterTreeMigration,
+    PinDefaultValuesVerified,
+    FixBrokenStateMachineReferencesInTransitionGetters,
+    MeshDescriptionNewSerialization,
+    UnclampRGBColorCurves,
+    LinkTimeAnimBlueprintRootDiscoveryBugFix,
+    TrailNodeBlendVariableNameChange,
+    PropertiesSerializeRepCondition,
+    FocalDistanceDisablesDOF,
+    UnusedSoundClass2dreverbSend,
+    GroomAssetVersion1,
+    GroomAssetVersion2,
+    SerializeAnimModifierState,
+    GroomAssetVersion3,
+    DeprecateFilmbackSettings,
+    CustomImplicitCollisionType,
+    FFieldPathOwnerSerialization,
+    ReleaseObjectVersionFixup,
+    PinTypeIncludesUObjectWrapperFlag,
+    WeightFMeshToMeshVertData,
+    AnimationGraphNodeBindingsDisplayedAsPins,
+    SerializeRigVMOffsetSegmentPaths,
+    AbcVelocitiesSupport,
+    MarginAddedToConvexAndBox,
+    StructureDataAddedToConvex,
+    AddedFrontRightUpAxesToLiveLinkPreProcessor,
+    FixupCopiedEventSections,
+    RemoteControlSerializeFunctionArgumentsSize,
+    AddedSubSequenceEntryWarpCounter,
+    LonglatTextureCubeDefaultMaxResolution,
+    GeometryCollectionCacheRemovesMassToLocal,
+}
+
+impl CustomVersionKey for FReleaseObjectVersion {
+    const KEY: Guid = Guid::new(0x9C54D522, 0xA8264FBE, 0x94210746, 0x61B482D0);
+    const NAME: &'static str = "FReleaseObjectVersion";
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FFortniteMainBranchObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+    WorldCompositionTile3DOffset,
+    MaterialInstanceSerializeOptimizationShaderFname,
+    CullDistanceRefactorRemovedDefaultDistance,
+    CullDistanceRefactorNeverCullHlodsByDefault,
+    CullDistanceRefactorNeverCullAlodactorsByDefault,
+    SaveGeneratedMorphTargetByEngine,
+    ConvertReductionSettingOptions,
+    StaticParameterTerrainLayerWeightBlendType,
+    FixUpNoneNameAnimationCurves,
+    EnsureActiveBoneIndicesToContainParents,
+    SerializeInstancedStaticMeshRenderData,
+    CachedMaterialQualityNodeUsage,
+    FontOutlineDropShadowFixup,
+    NewSkeletalMeshImporterWorkflow,
+    NewLandscapeMaterialPerLOD,
+    RemoveUnnecessaryTracksFromPose,
+    FoliageLazyObjPtrToSoftObjPtr,
+    RevertedStoreTimelineNamesInTemplate,
+    AddBakePoseOverrideForSkeletalMeshReductionSetting,
+    StoreTimelineNamesInTemplate,
+    WidgetStopDuplicatingAnimations,
+    AllowSkeletalMeshToReduceTheBaseLOD,
+    ShrinkCurveTableSize,
+    WidgetAnimationDefaultToSelfFail,
+    FortHUDElementNowRequiresTag,
+    FortMappedCookedAnimation,
+    SupportVirtualBoneInRetargeting,
+    FixUpWaterMetadata,
+    MoveWaterMetadataToActor,
+    ReplaceLakeCollision,
+    AnimLayerGuidConformation,
+    MakeOceanCollisionTransient,
+    FFieldPathOwnerSerialization,
+    FixUpUnderwaterPostProcessMaterial,
+    SupportMultipleWaterBodiesPerExclusionVolume,
+    RigVMByteCodeDeterminism,
+    LandscapePhysicalMaterialRenderData,
+    FixupRuntimeVirtualTextureVolume,
+    FixUpRiverCollisionComponents,
+    FixDuplicateRiverSplineMeshCollisionComponents,
+    ContainsStableActorGUIDs,
+    LevelsetSerializationSupportForBodySetup,
+    ChaosSolverPropertiesMoved,
+    GameFeatureDataMovedComponentListAndCheats,
+    ChaosClothAddfictitiousforces,
+    ChaosConvexVariableStructureDataAndVerticesArray,
+    RemoveLandscapeWaterInfo,
+    ChaosClothAddWeightedValue,
+    ChaosClothAddTetherStiffnessWeightMap,
+    ChaosClothFixLODTransitionMaps,
+    ChaosClothAddTetherScaleAndDragLiftWeightMaps,
+    ChaosClothAddMaterialWeightMaps,
+    SerializeFloatChannelShowCurve,
+    LandscapeGrassSingleArray,
+    AddedSubSequenceEntryWarpCounter,
+    WaterBodyComponentRefactor,
+    BPGCCookedEditorTags,
+    TerrainLayerWeightsAreNotParameters,
+    GravityOverrideDefinedInWorldSpace,
+}
+
+impl CustomVersionKey for FFortniteMainBranchObjectVersion {
+    const KEY: Guid = Guid::new(0x601D1886, 0xAC644F84, 0xAA16D3DE, 0x0DEAC7D6);
+    const NAME: &'static str = "FFortniteMainBranchObjectVersion";
+}
+
+/// Versions of the UE5 main branch, only the first ones are listed.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FUE5MainStreamObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+    GeometryCollectionNaniteData,
+    GeometryCollectionNaniteDDC,
+    RemovingSourceAnimationData,
+    MeshDescriptionNewFormat,
+    PartitionActorDescSerializeGridGuid,
+    ExternalActorsMapDataPackageFlag,
+    AnimationAddedBlendProfileModes,
+    WorldPartitionActorDescSerializeDataLayers,
+    RenamingAnimationNumFrames,
+    WorldPartitionHLODActorDescSerializeHLODLayer,
+    GeometryCollectionNaniteCooked,
+    AddedCookedBoolFontFaceAssets,
+    WorldPartitionHLODActorDescSerializeCellHash,
+    GeometryCollectionNaniteTransient,
+    AddedLandscapeSplineActorDesc,
+    AddCollisionConstraintFlag,
+}
+
+impl CustomVersionKey for FUE5MainStreamObjectVersion {
+    const KEY: Guid = Guid::new(0x697DD581, 0xE64F41AB, 0xAA4A51EC, 0xBEB7B628);
+    const NAME: &'static str = "FUE5MainStreamObjectVersion";
+}
+
+/// Versions of the UE5 release branch, only the first ones are listed.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub enum FUE5ReleaseStreamObjectVersion {
+    BeforeCustomVersionWasAdded = 0,
+
+    // Added Lumen reflections to the reflection method enum
+    ReflectionMethodEnum,
+
+    // Serialize HLOD info in WorldPartitionActorDesc
+    WorldPartitionActorDescSerializeHLODInfo,
+
+    // Removing Tessellation from materials and meshes
+    RemovingTessellation,
+
+    // LevelInstance serialize runtime behavior
+    LevelInstanceSerializeRuntimeBehavior,
+
+    // Refactoring Pose Asset runtime data structures
+    PoseAssetRuntimeRefactor,
+
+    // Serialize the folder path of actor descs
+    WorldPartitionActorDescSerializeActorFolderPath,
+
+    // Change hair strands vertex format
+    HairStrandsVertexFormatChange,
+
+    // Added max linear and angular speed to Chaos bodies
+    AddChaosMaxLinearAngularSpeed,
+
+    // PackedLevelInstance version
+    PackedLevelInstanceVersion,
+
+    // PackedLevelInstance bounds fix
+    PackedLevelInstanceBoundsFix,
+
+    // Custom property anim graph nodes now use the optional pin manager
+    CustomPropertyAnimGraphNodesUseOptionalPinManager,
+
+    // Add native double and int64 support to FFormatArgumentData
+    TextFormatArgumentData64bitSupport,
+}
+
+impl CustomVersionKey for FUE5ReleaseStreamObjectVersion {
+    const KEY: Guid = Guid::new(0xD89B5E42, 0x24BD4D46, 0x8412ACA8, 0xDF641779);
+    const NAME: &'static str = "FUE5ReleaseStreamObjectVersion";
+}
+
+impl<R: Read + Seek> Parsable<CustomVersion> for UassetParser<R> {
+    fn parse(&mut self) -> Result<CustomVersion> {
+        Ok(CustomVersion {
+            key: self.read()?,
+            version: self.reader.read_i32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl<W: Write + Seek> Writable<CustomVersion> for UassetWriter<W> {
+    fn serialize(&mut self, value: &CustomVersion) -> Result<()> {
+        self.write(&value.key)?;
+        self.writer.write_i32::<LittleEndian>(value.version)?;
+        Ok(())
+    }
+}
@@ -1,53 +1,464 @@
 use std::collections::HashMap;
-use std::io::{Read, Seek};
+use std::io::{self, Read, Seek, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "serde")]
+use serde::Deserialize;
 
 use crate::errors::Result;
 use crate::fname::FName;
+use crate::ftext::FText;
+use crate::package_index::PackageIndex;
 use crate::property_data::{PropertyData, PropertyValue};
+#[cfg(feature = "serde")]
+use crate::property_deserializer;
+use crate::property_type_name::PropertyTypeName;
+use crate::struct_value::{SoftObjectPath, StructValue};
 use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
 
 use super::uclass::UClassData;
 
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DataTable {
     pub class_data: UClassData,
-    pub rows: Vec<HashMap<String, String>>,
+    /// Path of the struct the rows are made of, e.g. `/Script/MyGame.ItemRow`
+    /// or `/Game/Data/S_Item.S_Item` for a user defined struct
+    pub row_struct: Option<String>,
+    pub rows: Vec<DataTableRow>,
+    /// Property names in the order of the row struct. The mappings schema of
+    /// the struct lists all of them, otherwise they come from the properties
+    /// the rows serialize
+    pub columns: Vec<String>,
+    /// Paths of the imports and exports of the package, which object
+    /// properties refer to by index
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub objects: HashMap<PackageIndex, String>,
+}
+
+/// Column of the rows as found in the serialized properties.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DataTableField {
+    pub name: String,
+    pub type_name: PropertyTypeName,
+}
+
+/// Row of a data table, its properties in serialization order. Properties left
+/// at their default value aren't serialized and are missing from the row.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DataTableRow {
+    pub name: FName,
+    pub properties: Vec<PropertyData>,
 }
 
 impl<R: Read + Seek> Parsable<DataTable> for UassetParser<R> {
     fn parse(&mut self) -> Result<DataTable> {
-        let flags = self.reader.read_u8()?;
         let class_data: UClassData = self.read()?;
-        let mut rows_count: i32 = self.reader.read_i32::<LittleEndian>()?;
-        let mut rows: Vec<HashMap<String, String>> = vec![];
-
-        while rows_count > 0 {
-            let row_name: FName = self.read()?;
-
-            let mut values: HashMap<String, String> = HashMap::new();
-            values.insert("Name".into(), row_name.as_string());
-
-            loop {
-                let property_data: PropertyData = self.read()?;
-                if property_data.tag.name.is_none() {
-                    rows.push(values);
-                    break;
+        let row_struct_index = class_data
+            .properties
+            .iter()
+            .find(|property| property.tag.name.as_string() == "RowStruct")
+            .and_then(|property| match property.value {
+                PropertyValue::ObjectProperty(index) => Some(index),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let row_struct = self.object_path(row_struct_index);
+        let row_struct_name = self
+            .resolve(row_struct_index)
+            .map(|row_struct| row_struct.object_name().as_string())
+            .unwrap_or_default();
+        let objects = (0..self.imports.len())
+            .map(PackageIndex::from_import)
+            .chain((0..self.entries.len()).map(PackageIndex::from_export))
+            .filter_map(|index| Some((index, self.object_path(index)?)))
+            .collect();
+        let mut columns: Vec<String> = vec![];
+        if let Some(schema) = self
+            .mappings
+            .as_ref()
+            .and_then(|mappings| mappings.flattened_properties(&row_struct_name).ok())
+        {
+            for (property, _) in schema.into_iter().flatten() {
+                if !columns.contains(&property.name) {
+                    columns.push(property.name.clone());
                 }
-                let tag = &property_data.tag;
-                let value: String = match property_data.value {
-                    PropertyValue::StrProperty(value) => value,
-                    _ => String::new(),
-                };
-                values.insert(property_data.tag.name.as_string(), value);
             }
+        }
+
+        let mut rows_count: i32 = self.reader.read_i32::<LittleEndian>()?;
+        let mut rows = vec![];
+        while rows_count > 0 {
+            let name: FName = self.read()?;
+            let properties = self.read_properties(&row_struct_name)?;
+            merge_columns(&mut columns, &properties);
+            rows.push(DataTableRow { name, properties });
 
             rows_count -= 1;
         }
 
         Ok(DataTable {
             class_data,
+            row_struct,
             rows,
+            columns,
+            objects,
         })
     }
 }
+
+impl<W: Write + Seek> Writable<DataTable> for UassetWriter<W> {
+    fn serialize(&mut self, value: &DataTable) -> Result<()> {
+        self.write(&value.class_data)?;
+        self.writer
+            .write_i32::<LittleEndian>(value.rows.len() as i32)?;
+        for row in &value.rows {
+            self.write(&row.name)?;
+            self.write_tagged_properties(&row.properties)?;
+        }
+        Ok(())
+    }
+}
+
+impl DataTableRow {
+    pub fn get(&self, column: &str) -> Option<&PropertyData> {
+        self.properties
+            .iter()
+            .find(|property| property.tag.name.as_string() == column)
+    }
+
+    pub fn get_value(&self, column: &str) -> Option<&PropertyValue> {
+        self.get(column).map(|property| &property.value)
+    }
+
+    pub fn get_bool(&self, column: &str) -> Option<bool> {
+        match self.get_value(column)? {
+            PropertyValue::BoolProperty(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of any integer property that fits in an i32.
+    pub fn get_i32(&self, column: &str) -> Option<i32> {
+        self.get_i64(column)
+            .and_then(|value| i32::try_from(value).ok())
+    }
+
+    /// Value of any integer property that fits in an i64.
+    pub fn get_i64(&self, column: &str) -> Option<i64> {
+        match self.get_value(column)? {
+            PropertyValue::Int8Property(value) => Some(*value as i64),
+            PropertyValue::Int16Property(value) => Some(*value as i64),
+            PropertyValue::IntProperty(value) => Some(*value as i64),
+            PropertyValue::Int64Property(value) => Some(*value),
+            PropertyValue::ByteProperty(value) => Some(*value as i64),
+            PropertyValue::UInt16Property(value) => Some(*value as i64),
+            PropertyValue::UInt32Property(value) => Some(*value as i64),
+            PropertyValue::UInt64Property(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn get_f32(&self, column: &str) -> Option<f32> {
+        match self.get_value(column)? {
+            PropertyValue::FloatProperty(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of a float or double property.
+    pub fn get_f64(&self, column: &str) -> Option<f64> {
+        match self.get_value(column)? {
+            PropertyValue::FloatProperty(value) => Some(*value as f64),
+            PropertyValue::DoubleProperty(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_str(&self, column: &str) -> Option<&str> {
+        match self.get_value(column)? {
+            PropertyValue::StrProperty(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Value of a name or enum property.
+    pub fn get_name(&self, column: &str) -> Option<&FName> {
+        match self.get_value(column)? {
+            PropertyValue::NameProperty(value) | PropertyValue::EnumProperty(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_text(&self, column: &str) -> Option<&FText> {
+        match self.get_value(column)? {
+            PropertyValue::TextProperty(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_object(&self, column: &str) -> Option<PackageIndex> {
+        match self.get_value(column)? {
+            PropertyValue::ObjectProperty(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_soft_object_path(&self, column: &str) -> Option<&SoftObjectPath> {
+        match self.get_value(column)? {
+            PropertyValue::SoftObjectProperty(value)
+            | PropertyValue::StructProperty(StructValue::SoftObjectPath(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_struct(&self, column: &str) -> Option<&StructValue> {
+        match self.get_value(column)? {
+            PropertyValue::StructProperty(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Elements of an array or set property.
+    pub fn get_array(&self, column: &str) -> Option<&[PropertyValue]> {
+        match self.get_value(column)? {
+            PropertyValue::ArrayProperty(elements)
+            | PropertyValue::SetProperty { elements, .. } => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Deserializes the properties of the row into `T`, see
+    /// [`from_properties`](crate::property_deserializer::from_properties).
+    /// Objects come as package indices, [`DataTable::deserialize_rows`] gives
+    /// their paths.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_as<'de, T: Deserialize<'de>>(&'de self) -> Result<T> {
+        property_deserializer::from_properties(&self.properties)
+    }
+
+    /// Values of the row as strings, in serialization order.
+    pub fn string_values(&self) -> Vec<(String, String)> {
+        self.properties
+            .iter()
+            .map(|property| (property.tag.name.as_string(), property.value.as_string()))
+            .collect()
+    }
+}
+
+impl DataTable {
+    pub fn row(&self, name: &str) -> Option<&DataTableRow> {
+        self.rows.iter().find(|row| row.name.as_string() == name)
+    }
+
+    /// Deserializes every row into `T`, in row order. Objects come as their
+    /// paths.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_rows<'de, T: Deserialize<'de>>(&'de self) -> Result<Vec<T>> {
+        let object_text = |index| self.object_path(index);
+        self.rows
+            .iter()
+            .map(|row| property_deserializer::from_properties_with(&row.properties, &object_text))
+            .collect()
+    }
+
+    /// Name and type of every column, in column order. Types come from the
+    /// first row holding the column.
+    pub fn schema(&self) -> Vec<DataTableField> {
+        self.columns
+            .iter()
+            .filter_map(|column| {
+                let property = self.rows.iter().find_map(|row| row.get(column))?;
+                Some(DataTableField {
+                    name: column.clone(),
+                    type_name: property.tag.type_name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Path of an object of the package, `None` for null or unknown objects
+    /// like the editor writes null references.
+    pub fn object_path(&self, index: PackageIndex) -> String {
+        self.objects
+            .get(&index)
+            .cloned()
+            .unwrap_or_else(|| "None".to_string())
+    }
+
+    /// Text of a value the way the exports write it, objects by path.
+    pub fn export_text(&self, value: &PropertyValue) -> String {
+        value.export_text(&|index| self.object_path(index))
+    }
+
+    /// Rows as maps of column to string value, the row name under `Name`.
+    /// A property called `Name` overwrites the row name.
+    pub fn string_rows(&self) -> Vec<HashMap<String, String>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let mut values: HashMap<String, String> = HashMap::new();
+                values.insert("Name".into(), row.name.as_string());
+                values.extend(row.properties.iter().map(|property| {
+                    (
+                        property.tag.name.as_string(),
+                        self.export_text(&property.value),
+                    )
+                }));
+                values
+            })
+            .collect()
+    }
+
+    /// Writes the rows as CSV the way the editor exports them: a `---` header
+    /// for the row names followed by the columns, every value quoted.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "---")?;
+        for column in &self.columns {
+            write!(writer, ",{}", column)?;
+        }
+        writeln!(writer)?;
+
+        for row in &self.rows {
+            write!(writer, "{}", row.name.as_string())?;
+            for column in &self.columns {
+                let value = row
+                    .get_value(column)
+                    .map(|value| self.export_text(value))
+                    .unwrap_or_default();
+                write!(writer, ",\"{}\"", value.replace('"', "\"\""))?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the rows in the shape of the editor's JSON export, an array of
+    /// objects holding the row name under `Name` and then the columns. Values
+    /// keep their JSON types, structs and maps become objects.
+    #[cfg(feature = "serde")]
+    pub fn write_json<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let rows: Vec<serde_json::Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut object = serde_json::Map::new();
+                object.insert(
+                    "Name".into(),
+                    serde_json::Value::String(row.name.as_string()),
+                );
+                for column in &self.columns {
+                    if let Some(value) = row.get_value(column) {
+                        object.insert(column.clone(), self.json_value(value));
+                    }
+                }
+                serde_json::Value::Object(object)
+            })
+            .collect();
+
+        // The editor indents with tabs
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut writer, formatter);
+        serde::Serialize::serialize(&rows, &mut serializer).map_err(io::Error::other)?;
+        writeln!(writer)
+    }
+
+    #[cfg(feature = "serde")]
+    fn json_value(&self, value: &PropertyValue) -> serde_json::Value {
+        use serde_json::Value;
+
+        match value {
+            PropertyValue::BoolProperty(value) => Value::Bool(*value),
+            PropertyValue::Int8Property(value) => Value::from(*value),
+            PropertyValue::Int16Property(value) => Value::from(*value),
+            PropertyValue::IntProperty(value) => Value::from(*value),
+            PropertyValue::Int64Property(value) => Value::from(*value),
+            PropertyValue::UInt16Property(value) => Value::from(*value),
+            PropertyValue::UInt32Property(value) => Value::from(*value),
+            PropertyValue::UInt64Property(value) => Value::from(*value),
+            PropertyValue::FloatProperty(value) => Value::from(*value),
+            PropertyValue::DoubleProperty(value) => Value::from(*value),
+            PropertyValue::ByteProperty(value) => Value::from(*value),
+            PropertyValue::StructProperty(value) => self.json_struct(value),
+            PropertyValue::ArrayProperty(elements)
+            | PropertyValue::SetProperty { elements, .. } => Value::Array(
+                elements
+                    .iter()
+                    .map(|element| self.json_value(element))
+                    .collect(),
+            ),
+            PropertyValue::MapProperty { entries, .. } => Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (self.export_text(key), self.json_value(value)))
+                    .collect(),
+            ),
+            PropertyValue::Undefined => Value::Null,
+            _ => Value::String(self.export_text(value)),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn json_struct(&self, value: &StructValue) -> serde_json::Value {
+        let fields: Vec<(String, serde_json::Value)> = match value {
+            StructValue::Vector(v) => fields([("X", v.x), ("Y", v.y), ("Z", v.z)]),
+            StructValue::Vector2D(v) => fields([("X", v.x), ("Y", v.y)]),
+            StructValue::Vector4(v) => fields([("X", v.x), ("Y", v.y), ("Z", v.z), ("W", v.w)]),
+            StructValue::Rotator(r) => {
+                fields([("Pitch", r.pitch), ("Yaw", r.yaw), ("Roll", r.roll)])
+            }
+            StructValue::Quat(q) => fields([("X", q.x), ("Y", q.y), ("Z", q.z), ("W", q.w)]),
+            StructValue::Color(c) => fields([("B", c.b), ("G", c.g), ("R", c.r), ("A", c.a)]),
+            StructValue::LinearColor(c) => fields([("R", c.r), ("G", c.g), ("B", c.b), ("A", c.a)]),
+            StructValue::IntPoint(p) => fields([("X", p.x), ("Y", p.y)]),
+            StructValue::GameplayTagContainer(tags) => {
+                let tags = tags.iter().map(|tag| tag.as_string().into()).collect();
+                return serde_json::Value::Array(tags);
+            }
+            StructValue::Properties(properties) => properties
+                .iter()
+                .map(|p| (p.tag.name.as_string(), self.json_value(&p.value)))
+                .collect(),
+            StructValue::Raw(_) => return serde_json::Value::Null,
+            StructValue::Custom(custom) => return custom.value.as_string().into(),
+            _ => return value.export_text(&|index| self.object_path(index)).into(),
+        };
+        serde_json::Value::Object(fields.into_iter().collect())
+    }
+}
+
+/// Adds the properties of a row missing from `columns`. Rows skip properties
+/// left at their defaults but keep the struct order, so new columns go right
+/// before the next column the row serializes, or last when there is none.
+fn merge_columns(columns: &mut Vec<String>, properties: &[PropertyData]) {
+    let mut position = 0;
+    let mut pending: Vec<String> = vec![];
+    for property in properties {
+        let column = property.tag.name.as_string();
+        match columns.iter().position(|existing| *existing == column) {
+            Some(index) if index >= position => {
+                position = index + pending.len() + 1;
+                columns.splice(index..index, pending.drain(..));
+            }
+            Some(_) => {}
+            None if !pending.contains(&column) => pending.push(column),
+            None => {}
+        }
+    }
+    columns.append(&mut pending);
+}
+
+/// Fields of a native struct for the JSON export.
+#[cfg(feature = "serde")]
+fn fields<T: Into<serde_json::Value>, const N: usize>(
+    values: [(&str, T); N],
+) -> Vec<(String, serde_json::Value)> {
+    values
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.into()))
+        .collect()
+}
@@ -0,0 +1,792 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+use crate::errors::{ParseError, Result};
+use crate::fname::FName;
+use crate::ftext::{FText, TextHistory};
+use crate::guid::Guid;
+use crate::package_index::PackageIndex;
+use crate::property_data::{PropertyData, PropertyValue};
+use crate::property_tag::PropertyTag;
+use crate::property_type_name::PropertyTypeName;
+use crate::struct_value::{
+    Color, IntPoint, LinearColor, Quat, Rotator, SoftObjectPath, StructValue, Vector, Vector2D,
+    Vector4,
+};
+
+use super::data_table::{DataTable, DataTableRow};
+
+/// Row name and the text of its values by column.
+type ImportRow = (String, Vec<(String, String)>);
+
+impl DataTable {
+    /// Replaces the rows with the ones of a CSV file laid out like the
+    /// editor's export: a header naming the columns after the row name
+    /// column, then one line per row.
+    pub fn import_csv<R: Read>(&mut self, mut reader: R) -> Result<()> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+
+        let mut records = parse_csv(&text)?.into_iter();
+        let header = records
+            .next()
+            .ok_or_else(|| ParseError::InvalidCsv("missing header".into()))?;
+
+        let rows = records
+            .map(|record| {
+                let mut cells = record.into_iter();
+                let name = cells.next().unwrap_or_default();
+                (name, header.iter().skip(1).cloned().zip(cells).collect())
+            })
+            .collect();
+        self.import_rows(rows)
+    }
+
+    /// Replaces the rows with the ones of a JSON file laid out like the
+    /// editor's export, an array of objects with the row name under `Name`.
+    #[cfg(feature = "serde")]
+    pub fn import_json<R: Read>(&mut self, reader: R) -> Result<()> {
+        let serde_json::Value::Array(objects) = serde_json::from_reader(reader)? else {
+            return Err(ParseError::UnexpectedJsonLayout(
+                "expected an array of rows".into(),
+            ));
+        };
+
+        let mut rows = vec![];
+        for object in objects {
+            let serde_json::Value::Object(fields) = object else {
+                return Err(ParseError::UnexpectedJsonLayout(
+                    "expected rows to be objects".into(),
+                ));
+            };
+
+            let mut name = None;
+            let mut values = vec![];
+            for (column, value) in fields {
+                if column == "Name" {
+                    name = Some(json_text(&value));
+                } else {
+                    values.push((column, json_text(&value)));
+                }
+            }
+            let name =
+                name.ok_or_else(|| ParseError::UnexpectedJsonLayout("row without a Name".into()))?;
+            rows.push((name, values));
+        }
+        self.import_rows(rows)
+    }
+
+    /// Finds the object of the package at `text`, a path as the exports write
+    /// it or the editor's `Class'Path'` form. `None` is the null object.
+    fn find_object(&self, text: &str) -> Option<PackageIndex> {
+        let path = match text.split_once('\'') {
+            Some((_, quoted)) => quoted.strip_suffix('\'')?,
+            None => text,
+        };
+        if path.is_empty() || path == "None" {
+            return Some(PackageIndex::default());
+        }
+        self.objects
+            .iter()
+            .find(|(_, object_path)| object_path.as_str() == path)
+            .map(|(index, _)| *index)
+    }
+
+    /// Rows left out of the import are removed, columns left out of a row keep
+    /// their values. Values are converted to the types of the existing properties.
+    fn import_rows(&mut self, rows: Vec<ImportRow>) -> Result<()> {
+        // The first property of each column gives the tag and the shape of the
+        // value for rows that don't have the property yet
+        let mut templates: HashMap<String, &PropertyData> = HashMap::new();
+        for row in &self.rows {
+            for property in &row.properties {
+                templates
+                    .entry(property.tag.name.as_string())
+                    .or_insert(property);
+            }
+        }
+
+        let mut imported = vec![];
+        for (name, values) in rows {
+            if let Some((column, _)) = values.iter().find(|(c, _)| !self.columns.contains(c)) {
+                return Err(ParseError::UnknownImportColumn(column.clone()));
+            }
+
+            let existing = self
+                .row(&name)
+                .map(|row| row.properties.as_slice())
+                .unwrap_or_default();
+
+            let mut properties = vec![];
+            for column in &self.columns {
+                let current = existing.iter().find(|p| &p.tag.name.as_string() == column);
+                let text = values
+                    .iter()
+                    .find(|(c, _)| c == column)
+                    .map(|(_, text)| text.as_str());
+
+                match (text, current) {
+                    (None, Some(current)) => properties.push(current.clone()),
+                    (None, None) | (Some(""), None) => {}
+                    (Some(text), _) => {
+                        let template = current
+                            .or(templates.get(column).copied())
+                            .ok_or_else(|| ParseError::UnknownImportColumn(column.clone()))?;
+                        let value = import_value(
+                            &template.tag.type_name,
+                            text,
+                            current.map(|p| &p.value),
+                            Some(&template.value),
+                            self,
+                        )
+                        .ok_or_else(|| ParseError::InvalidImportValue {
+                            column: column.clone(),
+                            value: text.to_string(),
+                        })?;
+                        properties.push(PropertyData {
+                            tag: PropertyTag {
+                                size: 0,
+                                ..template.tag.clone()
+                            },
+                            value,
+                        });
+                    }
+                }
+            }
+
+            imported.push(DataTableRow {
+                name: FName::new(name),
+                properties,
+            });
+        }
+
+        self.rows = imported;
+        Ok(())
+    }
+}
+
+/// Converts the text of a value back to a value of `type_name`. Texts that
+/// match the existing value keep it as is, `template` is a value of the same
+/// property elsewhere and gives the layout of structs. Objects are given by
+/// path and must be among the imports and exports of the package of `table`.
+fn import_value(
+    type_name: &PropertyTypeName,
+    text: &str,
+    existing: Option<&PropertyValue>,
+    template: Option<&PropertyValue>,
+    table: &DataTable,
+) -> Option<PropertyValue> {
+    if let Some(existing) = existing
+        && table.export_text(existing) == text
+    {
+        return Some(existing.clone());
+    }
+    let reference = existing.or(template);
+
+    let value = match type_name.name.as_str() {
+        "BoolProperty" => PropertyValue::BoolProperty(match text.to_ascii_lowercase().as_str() {
+            "true" | "1" => true,
+            "false" | "0" => false,
+            _ => return None,
+        }),
+        "Int8Property" => PropertyValue::Int8Property(text.parse().ok()?),
+        "Int16Property" => PropertyValue::Int16Property(text.parse().ok()?),
+        "IntProperty" => PropertyValue::IntProperty(text.parse().ok()?),
+        "Int64Property" => PropertyValue::Int64Property(text.parse().ok()?),
+        "UInt16Property" => PropertyValue::UInt16Property(text.parse().ok()?),
+        "UInt32Property" => PropertyValue::UInt32Property(text.parse().ok()?),
+        "UInt64Property" => PropertyValue::UInt64Property(text.parse().ok()?),
+        "FloatProperty" => PropertyValue::FloatProperty(text.parse().ok()?),
+        "DoubleProperty" => PropertyValue::DoubleProperty(text.parse().ok()?),
+        "ByteProperty"
+            if matches!(reference, Some(PropertyValue::ByteProperty(_)))
+                || (reference.is_none() && type_name.enum_name().is_none()) =>
+        {
+            PropertyValue::ByteProperty(text.parse().ok()?)
+        }
+        "ByteProperty" | "EnumProperty" => {
+            PropertyValue::EnumProperty(FName::new(import_enum(type_name, text, reference)))
+        }
+        "NameProperty" => PropertyValue::NameProperty(FName::new(text)),
+        "StrProperty" => PropertyValue::StrProperty(text.to_string()),
+        "Utf8StrProperty" => PropertyValue::Utf8StrProperty(text.to_string()),
+        "AnsiStrProperty" => PropertyValue::AnsiStrProperty(text.to_string()),
+        "TextProperty" => PropertyValue::TextProperty(import_text(text, existing)),
+        "ObjectProperty" | "ClassProperty" | "WeakObjectProperty" | "InterfaceProperty" => {
+            PropertyValue::ObjectProperty(table.find_object(text)?)
+        }
+        "LazyObjectProperty" => PropertyValue::LazyObjectProperty(parse_guid(text)?),
+        "SoftObjectProperty" | "SoftClassProperty" => {
+            let legacy = matches!(
+                reference,
+                Some(PropertyValue::SoftObjectProperty(SoftObjectPath {
+                    asset_name: None,
+                    ..
+                }))
+            );
+            PropertyValue::SoftObjectProperty(parse_soft_object_path(text, legacy))
+        }
+        "StructProperty" => PropertyValue::StructProperty(import_struct(
+            type_name,
+            text,
+            as_struct(existing),
+            as_struct(template),
+            table,
+        )?),
+        "ArrayProperty" | "SetProperty" => {
+            let inner = type_name.inner()?;
+            let (existing, template) = match (existing, reference) {
+                (
+                    Some(PropertyValue::ArrayProperty(existing)),
+                    Some(PropertyValue::ArrayProperty(template)),
+                )
+                | (
+                    Some(PropertyValue::SetProperty {
+                        elements: existing, ..
+                    }),
+                    Some(PropertyValue::SetProperty {
+                        elements: template, ..
+                    }),
+                ) => (existing.as_slice(), template.first()),
+                (
+                    _,
+                    Some(
+                        PropertyValue::ArrayProperty(template)
+                        | PropertyValue::SetProperty {
+                            elements: template, ..
+                        },
+                    ),
+                ) => (&[][..], template.first()),
+                _ => (&[][..], None),
+            };
+
+            let elements = split_list(text)?
+                .iter()
+                .enumerate()
+                .map(|(i, element)| {
+                    import_value(inner, &unquote(element), existing.get(i), template, table)
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            if type_name.name == "SetProperty" {
+                PropertyValue::SetProperty {
+                    elements,
+                    removed: vec![],
+                }
+            } else {
+                PropertyValue::ArrayProperty(elements)
+            }
+        }
+        "MapProperty" => {
+            let (key_type, value_type) = type_name.key_value()?;
+            let (existing, template) = match (existing, reference) {
+                (
+                    Some(PropertyValue::MapProperty {
+                        entries: existing, ..
+                    }),
+                    Some(PropertyValue::MapProperty {
+                        entries: template, ..
+                    }),
+                ) => (existing.as_slice(), template.first()),
+                (
+                    _,
+                    Some(PropertyValue::MapProperty {
+                        entries: template, ..
+                    }),
+                ) => (&[][..], template.first()),
+                _ => (&[][..], None),
+            };
+
+            let mut entries = vec![];
+            for (i, entry) in split_list(text)?.iter().enumerate() {
+                let [key, value] = match split_list(entry) {
+                    Some(pair) => <[String; 2]>::try_from(pair).ok()?,
+                    // Maps of the JSON export are objects, their entries come as Key=Value
+                    None => {
+                        let (key, value) = entry.split_once('=')?;
+                        [key.trim().to_string(), value.trim().to_string()]
+                    }
+                };
+                let current = existing.get(i);
+                entries.push((
+                    import_value(
+                        key_type,
+                        &unquote(&key),
+                        current.map(|(k, _)| k),
+                        template.map(|(k, _)| k),
+                        table,
+                    )?,
+                    import_value(
+                        value_type,
+                        &unquote(&value),
+                        current.map(|(_, v)| v),
+                        template.map(|(_, v)| v),
+                        table,
+                    )?,
+                ));
+            }
+            PropertyValue::MapProperty {
+                entries,
+                removed: vec![],
+            }
+        }
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Exports write enum values without the `EEnum::` prefix most enums store
+/// them with, it comes back from another value of the property or, without
+/// one, from the name of the enum.
+fn import_enum(
+    type_name: &PropertyTypeName,
+    text: &str,
+    reference: Option<&PropertyValue>,
+) -> String {
+    if text.contains("::") {
+        return text.to_string();
+    }
+    let prefix = match reference {
+        Some(PropertyValue::EnumProperty(value)) => value
+            .as_string()
+            .split_once("::")
+            .map(|(prefix, _)| prefix.to_string()),
+        _ => type_name.enum_name().map(str::to_string),
+    };
+    match prefix {
+        Some(prefix) => format!("{}::{}", prefix, text),
+        None => text.to_string(),
+    }
+}
+
+/// Keeps the localization key of an existing text, new texts get a key
+/// derived from their source string.
+fn import_text(text: &str, existing: Option<&PropertyValue>) -> FText {
+    match existing {
+        Some(PropertyValue::TextProperty(FText {
+            flags,
+            history: TextHistory::Base { namespace, key, .. },
+        })) => FText {
+            flags: *flags,
+            history: TextHistory::Base {
+                namespace: namespace.clone(),
+                key: key.clone(),
+                source_string: text.to_string(),
+            },
+        },
+        _ if text.is_empty() => FText {
+            flags: 0,
+            history: TextHistory::None {
+                culture_invariant_string: None,
+            },
+        },
+        _ => FText {
+            flags: 0,
+            history: TextHistory::Base {
+                namespace: String::new(),
+                key: text_key(text),
+                source_string: text.to_string(),
+            },
+        },
+    }
+}
+
+/// 32 hex digit key in the shape of the editor's generated ones, two FNV-1a
+/// hashes of the source string.
+fn text_key(text: &str) -> String {
+    let fnv = |basis: u64| {
+        text.bytes().fold(basis, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    };
+    format!(
+        "{:016X}{:016X}",
+        fnv(0xcbf29ce484222325),
+        fnv(0x84222325cbf29ce4)
+    )
+}
+
+fn import_struct(
+    type_name: &PropertyTypeName,
+    text: &str,
+    existing: Option<&StructValue>,
+    template: Option<&StructValue>,
+    table: &DataTable,
+) -> Option<StructValue> {
+    let reference = existing.or(template);
+    let kind = match reference {
+        Some(value) => struct_kind(value),
+        None => type_name.struct_name()?,
+    };
+
+    let value = match kind {
+        "Vector" => {
+            let f = parse_fields(text)?;
+            StructValue::Vector(Vector {
+                x: field(&f, "X")?,
+                y: field(&f, "Y")?,
+                z: field(&f, "Z")?,
+            })
+        }
+        "Vector2D" => {
+            let f = parse_fields(text)?;
+            StructValue::Vector2D(Vector2D {
+                x: field(&f, "X")?,
+                y: field(&f, "Y")?,
+            })
+        }
+        "Vector4" => {
+            let f = parse_fields(text)?;
+            StructValue::Vector4(Vector4 {
+                x: field(&f, "X")?,
+                y: field(&f, "Y")?,
+                z: field(&f, "Z")?,
+                w: field(&f, "W")?,
+            })
+        }
+        "Rotator" => {
+            let f = parse_fields(text)?;
+            StructValue::Rotator(Rotator {
+                pitch: field(&f, "Pitch")?,
+                yaw: field(&f, "Yaw")?,
+                roll: field(&f, "Roll")?,
+            })
+        }
+        "Quat" => {
+            let f = parse_fields(text)?;
+            StructValue::Quat(Quat {
+                x: field(&f, "X")?,
+                y: field(&f, "Y")?,
+                z: field(&f, "Z")?,
+                w: field(&f, "W")?,
+            })
+        }
+        "Color" => {
+            let f = parse_fields(text)?;
+            StructValue::Color(Color {
+                b: field(&f, "B")?,
+                g: field(&f, "G")?,
+                r: field(&f, "R")?,
+                a: field(&f, "A")?,
+            })
+        }
+        "LinearColor" => {
+            let f = parse_fields(text)?;
+            StructValue::LinearColor(LinearColor {
+                r: field(&f, "R")?,
+                g: field(&f, "G")?,
+                b: field(&f, "B")?,
+                a: field(&f, "A")?,
+            })
+        }
+        "IntPoint" => {
+            let f = parse_fields(text)?;
+            StructValue::IntPoint(IntPoint {
+                x: field(&f, "X")?,
+                y: field(&f, "Y")?,
+            })
+        }
+        "Guid" => StructValue::Guid(parse_guid(text)?),
+        "DateTime" => StructValue::DateTime(text.parse().ok()?),
+        "Timespan" => StructValue::Timespan(text.parse().ok()?),
+        "SoftObjectPath" | "SoftClassPath" => {
+            let legacy = matches!(
+                reference,
+                Some(StructValue::SoftObjectPath(SoftObjectPath {
+                    asset_name: None,
+                    ..
+                }))
+            );
+            StructValue::SoftObjectPath(parse_soft_object_path(text, legacy))
+        }
+        // The editor exports tags as `(TagName="A.B")`
+        "GameplayTag" => {
+            let tag_name = parse_fields(text)
+                .and_then(|f| f.into_iter().find(|(name, _)| name == "TagName"))
+                .map(|(_, value)| value);
+            StructValue::GameplayTag(FName::new(tag_name.unwrap_or_else(|| text.to_string())))
+        }
+        "GameplayTagContainer" => StructValue::GameplayTagContainer(
+            split_list(text)?
+                .iter()
+                .map(|tag| FName::new(unquote(tag)))
+                .collect(),
+        ),
+        "Properties" => StructValue::Properties(import_struct_fields(
+            text,
+            struct_fields(existing),
+            struct_fields(template),
+            table,
+        )?),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Struct given as `(Field=Value,...)`, fields it leaves out keep their values.
+fn import_struct_fields(
+    text: &str,
+    existing: &[PropertyData],
+    template: &[PropertyData],
+    table: &DataTable,
+) -> Option<Vec<PropertyData>> {
+    let mut properties = existing.to_vec();
+    for (name, field_text) in parse_fields(text)? {
+        let current = existing.iter().find(|p| p.tag.name.as_string() == name);
+        let field_template =
+            current.or(template.iter().find(|p| p.tag.name.as_string() == name))?;
+        let value = import_value(
+            &field_template.tag.type_name,
+            &field_text,
+            current.map(|p| &p.value),
+            Some(&field_template.value),
+            table,
+        )?;
+
+        match properties
+            .iter_mut()
+            .find(|p| p.tag.name.as_string() == name)
+        {
+            Some(property) => property.value = value,
+            None => properties.push(PropertyData {
+                tag: PropertyTag {
+                    size: 0,
+                    ..field_template.tag.clone()
+                },
+                value,
+            }),
+        }
+    }
+    Some(properties)
+}
+
+fn as_struct(value: Option<&PropertyValue>) -> Option<&StructValue> {
+    match value {
+        Some(PropertyValue::StructProperty(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn struct_fields(value: Option<&StructValue>) -> &[PropertyData] {
+    match value {
+        Some(StructValue::Properties(fields)) => fields,
+        _ => &[],
+    }
+}
+
+fn struct_kind(value: &StructValue) -> &'static str {
+    match value {
+        StructValue::Vector(_) => "Vector",
+        StructValue::Vector2D(_) => "Vector2D",
+        StructValue::Vector4(_) => "Vector4",
+        StructValue::Rotator(_) => "Rotator",
+        StructValue::Quat(_) => "Quat",
+        StructValue::Guid(_) => "Guid",
+        StructValue::Color(_) => "Color",
+        StructValue::LinearColor(_) => "LinearColor",
+        StructValue::IntPoint(_) => "IntPoint",
+        StructValue::DateTime(_) => "DateTime",
+        StructValue::Timespan(_) => "Timespan",
+        StructValue::SoftObjectPath(_) => "SoftObjectPath",
+        StructValue::GameplayTag(_) => "GameplayTag",
+        StructValue::GameplayTagContainer(_) => "GameplayTagContainer",
+        StructValue::Properties(_) => "Properties",
+        StructValue::Raw(_) => "Raw",
+        StructValue::Custom(_) => "Custom",
+    }
+}
+
+fn field<T: FromStr>(fields: &[(String, String)], name: &str) -> Option<T> {
+    fields
+        .iter()
+        .find(|(field_name, _)| field_name.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+fn parse_guid(text: &str) -> Option<Guid> {
+    let text = text.trim().replace('-', "");
+    if text.len() != 32 || !text.is_ascii() {
+        return None;
+    }
+    let part = |i: usize| u32::from_str_radix(&text[i * 8..(i + 1) * 8], 16).ok();
+    Some(Guid {
+        a: part(0)?,
+        b: part(1)?,
+        c: part(2)?,
+        d: part(3)?,
+    })
+}
+
+/// Parses `/Game/Package.Asset:SubPath`. Legacy paths keep the whole asset
+/// path in the package name.
+fn parse_soft_object_path(text: &str, legacy: bool) -> SoftObjectPath {
+    let text = if text.is_empty() { "None" } else { text };
+    let (path, sub_path) = text.split_once(':').unwrap_or((text, ""));
+    let (package_name, asset_name) = match path.rsplit_once('.') {
+        Some((package_name, asset_name)) if !legacy => (package_name, Some(asset_name)),
+        _ if legacy => (path, None),
+        _ => (path, Some("None")),
+    };
+
+    SoftObjectPath {
+        package_name: FName::new(package_name),
+        asset_name: asset_name.map(FName::new),
+        sub_path: sub_path.to_string(),
+    }
+}
+
+/// Splits `(a,b,(c,d))` into its top level items, quoted items may hold
+/// separators.
+fn split_list(text: &str) -> Option<Vec<String>> {
+    let inner = text.trim().strip_prefix('(')?.strip_suffix(')')?;
+    if inner.trim().is_empty() {
+        return Some(vec![]);
+    }
+
+    let mut items = vec![];
+    let mut item = String::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if quoted => {
+                item.push(c);
+                item.extend(chars.next());
+                continue;
+            }
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                items.push(std::mem::take(&mut item).trim().to_string());
+                continue;
+            }
+            _ => {}
+        }
+        item.push(c);
+    }
+    if quoted || depth != 0 {
+        return None;
+    }
+    items.push(item.trim().to_string());
+
+    Some(items)
+}
+
+/// Splits `(Name=Value,...)` into its fields.
+fn parse_fields(text: &str) -> Option<Vec<(String, String)>> {
+    split_list(text)?
+        .iter()
+        .map(|item| {
+            let (name, value) = item.split_once('=')?;
+            Some((name.trim().to_string(), unquote(value.trim())))
+        })
+        .collect()
+}
+
+fn unquote(text: &str) -> String {
+    match text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    {
+        Some(text) => {
+            let mut unquoted = String::with_capacity(text.len());
+            let mut chars = text.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => unquoted.extend(chars.next()),
+                    _ => unquoted.push(c),
+                }
+            }
+            unquoted
+        }
+        None => text.to_string(),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn quote(text: &str) -> String {
+    if text.contains([',', '(', ')', '"', '=']) {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Text of a JSON value in the form used for CSV cells.
+#[cfg(feature = "serde")]
+fn json_text(value: &serde_json::Value) -> String {
+    let item = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text) => quote(text),
+        _ => json_text(value),
+    };
+
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Bool(value) => value.to_string(),
+        serde_json::Value::Number(value) => value.to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(values) => {
+            let items: Vec<String> = values.iter().map(item).collect();
+            format!("({})", items.join(","))
+        }
+        serde_json::Value::Object(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, item(value)))
+                .collect();
+            format!("({})", fields.join(","))
+        }
+    }
+}
+
+/// Parses RFC 4180 CSV, quoted fields may hold separators, line breaks and
+/// doubled quotes.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(ParseError::InvalidCsv("unterminated quoted field".into()));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // Blank lines
+    records.retain(|record| !(record.len() == 1 && record[0].is_empty()));
+    Ok(records)
+}
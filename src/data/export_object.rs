@@ -0,0 +1,111 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::errors::{ParseError, Result};
+use crate::parser_registry::PluginValue;
+use crate::uasset_parser::UassetParser;
+
+use super::data_table::DataTable;
+use super::uclass::UClassData;
+use super::uobject::UObjectExport;
+
+/// Decoded data of an export, picked by the class of the export.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ExportObject {
+    DataTable(DataTable),
+    /// Object of a class without a dedicated parser
+    Object(UObjectExport),
+    /// Data that can't be decoded, e.g. unversioned properties without mappings
+    Raw(Vec<u8>),
+    /// Export decoded by a parser from the registry
+    Custom(PluginValue),
+}
+
+impl ExportObject {
+    pub fn as_data_table(&self) -> Option<&DataTable> {
+        match self {
+            ExportObject::DataTable(data_table) => Some(data_table),
+            _ => None,
+        }
+    }
+
+    pub fn into_data_table(self) -> Option<DataTable> {
+        match self {
+            ExportObject::DataTable(data_table) => Some(data_table),
+            _ => None,
+        }
+    }
+
+    /// Properties of the object, every parsed kind starts with them.
+    pub fn class_data(&self) -> Option<&UClassData> {
+        match self {
+            ExportObject::DataTable(data_table) => Some(&data_table.class_data),
+            ExportObject::Object(object) => Some(&object.class_data),
+            ExportObject::Raw(_) | ExportObject::Custom(_) => None,
+        }
+    }
+}
+
+/// Classes whose exports serialize the rows of a data table.
+const DATA_TABLE_CLASSES: &[&str] = &[
+    "/Script/Engine.DataTable",
+    "/Script/Engine.CompositeDataTable",
+];
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Name of the class of the export, e.g. `DataTable`.
+    pub fn export_class_name(&self, export_index: usize) -> Option<String> {
+        let export = self.entries.get(export_index)?;
+        self.resolve(export.class_index)
+            .map(|class| class.object_name().as_string())
+    }
+
+    /// Index of the first export of class `class_name`.
+    pub fn find_export(&self, class_name: &str) -> Option<usize> {
+        (0..self.entries.len())
+            .find(|&index| self.export_class_name(index).as_deref() == Some(class_name))
+    }
+
+    /// Index of the first export `read_export` decodes as a data table, of any
+    /// of the data table classes.
+    pub fn find_data_table(&self) -> Option<usize> {
+        (0..self.entries.len()).find(|&index| {
+            self.object_path(self.entries[index].class_index)
+                .is_some_and(|class_path| DATA_TABLE_CLASSES.contains(&class_path.as_str()))
+        })
+    }
+
+    /// Reads the export at `export_index` with the parser for its class.
+    pub fn read_export(&mut self, export_index: usize) -> Result<ExportObject> {
+        let export = self
+            .entries
+            .get(export_index)
+            .ok_or(ParseError::InvalidExportIndex(export_index))?;
+        self.reader
+            .seek(SeekFrom::Start(export.serial_offset as u64))?;
+
+        let class_path = self.object_path(export.class_index).unwrap_or_default();
+        if let Some(parser) = self
+            .registry
+            .as_ref()
+            .and_then(|registry| registry.export_parser(&class_path))
+        {
+            return self.read_plugin_value(parser).map(ExportObject::Custom);
+        }
+
+        let object = if DATA_TABLE_CLASSES.contains(&class_path.as_str()) {
+            self.read().map(ExportObject::DataTable)
+        } else {
+            self.read().map(ExportObject::Object)
+        };
+
+        match object {
+            Err(ParseError::MissingMappings)
+            | Err(ParseError::MissingSchema(_))
+            | Err(ParseError::UnknownExportClass(_)) => {
+                self.read_export_data(export_index).map(ExportObject::Raw)
+            }
+            object => object,
+        }
+    }
+}
@@ -1,2 +1,5 @@
 pub mod data_table;
+pub mod data_table_import;
+pub mod export_object;
 pub mod uclass;
+pub mod uobject;
@@ -1,30 +1,105 @@
-use byteorder::ReadBytesExt;
-use std::io::{Read, Seek};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
 
-use crate::errors::Result;
+use crate::errors::{ParseError, Result};
+use crate::guid::Guid;
 use crate::{
     property_data::PropertyData,
+    property_tag::EOverriddenPropertyOperation,
     uasset_parser::{Parsable, UassetParser},
+    uasset_writer::{UassetWriter, Writable},
+    versions::EUnrealEngineObjectUE5Version,
 };
 
+#[repr(u8)]
+pub enum EClassSerializationControlExtension {
+    NoExtension = 0x00,
+    ReserveForFutureUse = 0x01,
+    OverridableSerializationInformation = 0x02,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UClassData {
+    pub serialization_control: u8,
+    pub overridable_operation: u8,
     pub properties: Vec<PropertyData>,
+    /// Class default objects don't serialize the object GUID
+    pub class_default_object: bool,
+    /// GUID lazy object pointers refer to the object by
+    pub object_guid: Option<Guid>,
 }
 
 impl<R: Read + Seek> Parsable<UClassData> for UassetParser<R> {
     fn parse(&mut self) -> Result<UClassData> {
-        let mut properties = Vec::new();
+        let start = self.reader.stream_position()?;
+        let mut serialization_control = EClassSerializationControlExtension::NoExtension as u8;
+        let mut overridable_operation = EOverriddenPropertyOperation::None as u8;
 
-        loop {
-            let property: PropertyData = self.read()?;
-            if property.tag.name.is_none() {
-                break;
+        if self.summary()?.file_version_ue5
+            >= EUnrealEngineObjectUE5Version::PropertyTagExtensionAndOverridableSerialization as i32
+        {
+            serialization_control = self.reader.read_u8()?;
+            if serialization_control
+                & (EClassSerializationControlExtension::OverridableSerializationInformation as u8)
+                != 0
+            {
+                overridable_operation = self.reader.read_u8()?;
             }
-            properties.push(property);
         }
 
-        self.skip_bytes(4)?; // todo: figure out why it's needed
+        let class_default_object = self
+            .export_at(start)
+            .is_some_and(|export| export.is_class_default_object());
+        let properties = if self.has_unversioned_properties() {
+            // Unversioned properties follow the schema of the class of the export
+            let class_name = self
+                .export_at(start)
+                .and_then(|export| self.resolve(export.class_index))
+                .map(|class| class.object_name().as_string())
+                .ok_or(ParseError::UnknownExportClass(start))?;
+            self.read_unversioned_properties(&class_name)?
+        } else {
+            self.read_tagged_properties()?
+        };
+
+        let mut object_guid = None;
+        if !class_default_object && self.reader.read_u32::<LittleEndian>()? != 0 {
+            object_guid = Some(self.read()?);
+        }
+
+        Ok(UClassData {
+            serialization_control,
+            overridable_operation,
+            properties,
+            class_default_object,
+            object_guid,
+        })
+    }
+}
+
+impl<W: Write + Seek> Writable<UClassData> for UassetWriter<W> {
+    fn serialize(&mut self, value: &UClassData) -> Result<()> {
+        if self.get_summary().file_version_ue5
+            >= EUnrealEngineObjectUE5Version::PropertyTagExtensionAndOverridableSerialization as i32
+        {
+            self.writer.write_u8(value.serialization_control)?;
+            if value.serialization_control
+                & (EClassSerializationControlExtension::OverridableSerializationInformation as u8)
+                != 0
+            {
+                self.writer.write_u8(value.overridable_operation)?;
+            }
+        }
 
-        Ok(UClassData { properties, })
+        self.write_tagged_properties(&value.properties)?;
+        if !value.class_default_object {
+            self.writer
+                .write_u32::<LittleEndian>(value.object_guid.is_some() as u32)?;
+            if let Some(guid) = &value.object_guid {
+                self.write(guid)?;
+            }
+        }
+        Ok(())
     }
 }
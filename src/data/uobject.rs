@@ -0,0 +1,43 @@
+use std::io::{Read, Seek, Write};
+
+use crate::errors::{ParseError, Result};
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
+
+use super::uclass::UClassData;
+
+/// Export of any class: the object properties followed by whatever the class
+/// serializes natively after them, kept as raw bytes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UObjectExport {
+    pub class_data: UClassData,
+    /// Data after the properties up to the end of the export
+    pub tail: Vec<u8>,
+}
+
+impl<R: Read + Seek> Parsable<UObjectExport> for UassetParser<R> {
+    fn parse(&mut self) -> Result<UObjectExport> {
+        let start = self.reader.stream_position()?;
+        let end = self
+            .export_at(start)
+            .map(|export| (export.serial_offset + export.serial_size) as u64)
+            .ok_or(ParseError::NoExportAtOffset(start))?;
+
+        let class_data: UClassData = self.read()?;
+
+        let position = self.reader.stream_position()?;
+        let mut tail = vec![0u8; end.saturating_sub(position) as usize];
+        self.reader.read_exact(&mut tail)?;
+
+        Ok(UObjectExport { class_data, tail })
+    }
+}
+
+impl<W: Write + Seek> Writable<UObjectExport> for UassetWriter<W> {
+    fn serialize(&mut self, value: &UObjectExport) -> Result<()> {
+        self.write(&value.class_data)?;
+        self.writer.write_all(&value.tail)?;
+        Ok(())
+    }
+}
@@ -21,9 +21,15 @@ pub enum ParseError {
     #[error("Invalid compression flags")]
     InvalidCompressionFlags,
 
-    #[error("Compressed chunks not supported")]
+    #[error("Writing packages with compressed chunks is not supported")]
     CompressedChunksNotSupported,
 
+    #[error("Package data is compressed, read it through a CompressedPackageReader")]
+    CompressedPackage,
+
+    #[error("No package summary, parse the asset or set a summary first")]
+    MissingSummary,
+
     #[error("Unversioned asset parsing not allowed")]
     UnversionedAssetNotAllowed,
 
@@ -35,6 +41,123 @@ pub enum ParseError {
 
     #[error("Invalid UTF-16 string")]
     InvalidUtf16,
+
+    #[error("Unsupported property type: {0}")]
+    UnsupportedPropertyType(String),
+
+    #[error("Property type nested deeper than {0} levels")]
+    PropertyTypeTooDeep(usize),
+
+    #[error("Invalid size {size} for property {name}")]
+    InvalidPropertySize { name: String, size: i32 },
+
+    #[error("Invalid soft object path index: {0}")]
+    InvalidSoftObjectPathIndex(i32),
+
+    #[error("Unsupported text history type: {0}")]
+    UnsupportedTextHistory(i8),
+
+    #[error("Unsupported text format argument type: {0}")]
+    UnsupportedFormatArgumentType(i8),
+
+    #[error("Invalid usmap magic: {0:#06x}")]
+    InvalidUsmapMagic(u16),
+
+    #[error("Unsupported usmap version: {0}")]
+    UnsupportedUsmapVersion(u8),
+
+    #[error("Unsupported usmap compression method: {0}")]
+    UnsupportedUsmapCompression(u8),
+
+    #[error("Invalid usmap name index: {0}")]
+    InvalidUsmapNameIndex(i32),
+
+    #[error("Decompression failed: {0}")]
+    DecompressionFailed(String),
+
+    #[error("Unversioned package without file versions, provide them or mappings that have them")]
+    MissingUnversionedFileVersion,
+
+    #[error("Unversioned properties require mappings")]
+    MissingMappings,
+
+    #[error("No mappings schema for struct: {0}")]
+    MissingSchema(String),
+
+    #[error("Super struct chain of {0} loops in the mappings")]
+    CyclicUsmapSchema(String),
+
+    #[error("Unversioned property index {index} not in the schema of {struct_name}")]
+    InvalidUnversionedPropertyIndex { struct_name: String, index: usize },
+
+    #[error("Invalid export index: {0}")]
+    InvalidExportIndex(usize),
+
+    #[error("Expected data for {expected} exports, got {actual}")]
+    ExportCountMismatch { expected: usize, actual: usize },
+
+    #[error("No export starts at offset {0}")]
+    NoExportAtOffset(u64),
+
+    #[error("No export class found for the data at offset {0}")]
+    UnknownExportClass(u64),
+
+    #[error("Writing unversioned properties is not supported")]
+    UnversionedWriteNotSupported,
+
+    #[error("Invalid CSV: {0}")]
+    InvalidCsv(String),
+
+    #[cfg(feature = "serde")]
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("Unexpected JSON layout: {0}")]
+    UnexpectedJsonLayout(String),
+
+    #[error("Column not in the data table: {0}")]
+    UnknownImportColumn(String),
+
+    #[error("Invalid value for column {column}: {value}")]
+    InvalidImportValue { column: String, value: String },
+
+    #[error("Invalid pak footer")]
+    InvalidPakFooter,
+
+    #[error("Unsupported pak version: {0}")]
+    UnsupportedPakVersion(i32),
+
+    #[error("Data is encrypted and no AES key was given")]
+    MissingAesKey,
+
+    #[error("Invalid AES key: {0}")]
+    InvalidAesKey(String),
+
+    #[error("File not found in the pak: {0}")]
+    PakEntryNotFound(String),
+
+    #[error("Unsupported compression method: {0}")]
+    UnsupportedCompressionMethod(String),
+
+    #[error("Invalid IoStore table of contents")]
+    InvalidIoStoreToc,
+
+    #[error("Unsupported IoStore table of contents version: {0}")]
+    UnsupportedIoStoreVersion(u8),
+
+    #[error("Chunk not found in the container: {0}")]
+    IoChunkNotFound(String),
+
+    #[cfg(feature = "serde")]
+    #[error("Deserialization failed: {0}")]
+    Deserialize(String),
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for ParseError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ParseError::Deserialize(msg.to_string())
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ParseError>;
@@ -1,16 +1,28 @@
 use crate::errors::Result;
 use crate::fname::FName;
+use crate::package_index::PackageIndex;
 use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
 use crate::versions::EUnrealEngineObjectUE5Version;
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Read, Seek};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
 
-#[derive(Debug)]
+#[repr(u32)]
+pub enum EObjectFlags {
+    Public = 0x00000001,
+    Standalone = 0x00000002,
+    Transactional = 0x00000008,
+    ClassDefaultObject = 0x00000010,
+    ArchetypeObject = 0x00000020,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ExportEntry {
-    pub class_index: i32,
-    pub super_index: i32,
-    pub template_index: i32,
-    pub outer_index: i32,
+    pub class_index: PackageIndex,
+    pub super_index: PackageIndex,
+    pub template_index: PackageIndex,
+    pub outer_index: PackageIndex,
     pub object_name: FName,
     pub object_flags: i32,
     pub serial_size: i64,
@@ -32,12 +44,18 @@ pub struct ExportEntry {
     pub script_serialization_end_offset: i64,
 }
 
+impl ExportEntry {
+    pub fn is_class_default_object(&self) -> bool {
+        self.object_flags as u32 & EObjectFlags::ClassDefaultObject as u32 != 0
+    }
+}
+
 impl<R: Read + Seek> Parsable<ExportEntry> for UassetParser<R> {
     fn parse(&mut self) -> Result<ExportEntry> {
-        let class_index = self.reader.read_i32::<LittleEndian>()?;
-        let super_index = self.reader.read_i32::<LittleEndian>()?;
-        let template_index = self.reader.read_i32::<LittleEndian>()?;
-        let outer_index = self.reader.read_i32::<LittleEndian>()?;
+        let class_index: PackageIndex = self.read()?;
+        let super_index: PackageIndex = self.read()?;
+        let template_index: PackageIndex = self.read()?;
+        let outer_index: PackageIndex = self.read()?;
         let object_name: FName = self.read()?;
         let object_flags: i32 = self.reader.read_i32::<LittleEndian>()?;
         let serial_size: i64 = self.reader.read_i64::<LittleEndian>()?;
@@ -112,3 +130,49 @@ impl<R: Read + Seek> Parsable<ExportEntry> for UassetParser<R> {
         Ok(export)
     }
 }
+
+impl<W: Write + Seek> Writable<ExportEntry> for UassetWriter<W> {
+    fn serialize(&mut self, value: &ExportEntry) -> Result<()> {
+        self.write(&value.class_index)?;
+        self.write(&value.super_index)?;
+        self.write(&value.template_index)?;
+        self.write(&value.outer_index)?;
+        self.write(&value.object_name)?;
+
+        let file_version_ue5 = self.get_summary().file_version_ue5;
+        let w = &mut self.writer;
+        w.write_i32::<LittleEndian>(value.object_flags)?;
+        w.write_i64::<LittleEndian>(value.serial_size)?;
+        w.write_i64::<LittleEndian>(value.serial_offset)?;
+        w.write_u32::<LittleEndian>(value.force_export as u32)?;
+        w.write_u32::<LittleEndian>(value.not_for_client as u32)?;
+        w.write_u32::<LittleEndian>(value.not_for_server as u32)?;
+
+        if file_version_ue5 < EUnrealEngineObjectUE5Version::RemoveObjectExportPackageGuid as i32 {
+            w.write_i128::<LittleEndian>(0)?;
+        }
+
+        if file_version_ue5 > EUnrealEngineObjectUE5Version::TrackObjectExportIsInherited as i32 {
+            w.write_u32::<LittleEndian>(value.is_inherited_instance as u32)?;
+        }
+
+        w.write_u32::<LittleEndian>(value.package_flags)?;
+        w.write_u32::<LittleEndian>(value.not_always_loaded_for_editor_game as u32)?;
+        w.write_u32::<LittleEndian>(value.is_asset as u32)?;
+
+        if file_version_ue5 >= EUnrealEngineObjectUE5Version::OptionalResources as i32 {
+            w.write_u32::<LittleEndian>(value.generate_public_hash as u32)?;
+        }
+
+        w.write_i32::<LittleEndian>(value.first_export_dependency)?;
+        w.write_i32::<LittleEndian>(value.serialization_before_serialization_dependencies)?;
+        w.write_i32::<LittleEndian>(value.create_before_serialization_dependencies)?;
+        w.write_i32::<LittleEndian>(value.serialization_before_create_dependencies)?;
+        w.write_i32::<LittleEndian>(value.create_before_create_dependencies)?;
+
+        w.write_i64::<LittleEndian>(value.script_serialization_start_offset)?;
+        w.write_i64::<LittleEndian>(value.script_serialization_end_offset)?;
+
+        Ok(())
+    }
+}
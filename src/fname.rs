@@ -1,11 +1,12 @@
 use crate::errors::Result;
 use crate::uasset_parser::Parsable;
 use crate::uasset_parser::UassetParser;
+use crate::uasset_writer::{UassetWriter, Writable};
 use byteorder::LittleEndian;
-use byteorder::ReadBytesExt;
-use std::io::{Read, Seek};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FName {
     pub index: i32,
     pub number: i32,
@@ -16,22 +17,88 @@ impl<R: Read + Seek> Parsable<FName> for UassetParser<R> {
     fn parse(&mut self) -> Result<FName> {
         let index = self.reader.read_i32::<LittleEndian>()?;
         let number = self.reader.read_i32::<LittleEndian>()?;
-        let len = self.names.len() as i32;
-        let value: String = if index < len {
-            self.names[index as usize].clone()
-        } else {
-            "None".into()
-        };
+        Ok(FName::from_table(&self.names, index, number))
+    }
+}
+
+impl<W: Write + Seek> Writable<FName> for UassetWriter<W> {
+    fn serialize(&mut self, value: &FName) -> Result<()> {
+        let index = self.name_index(&value.value);
+        self.writer.write_i32::<LittleEndian>(index)?;
+        self.writer.write_i32::<LittleEndian>(value.number)?;
+        Ok(())
+    }
+}
+
+impl Default for FName {
+    fn default() -> Self {
+        FName::new("None")
+    }
+}
 
-        Ok(FName {
+/// Names are equal when they spell the same, wherever their index points.
+impl PartialEq for FName {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.number == other.number
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FName {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_string())
+    }
+}
+
+impl FName {
+    /// Name that doesn't come from the name table of the package, e.g. one
+    /// taken from mappings or an import. Like the engine, a `_N` suffix is
+    /// split off into the number.
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        if let Some((base, suffix)) = value.rsplit_once('_') {
+            let is_number = !suffix.is_empty()
+                && suffix.bytes().all(|b| b.is_ascii_digit())
+                && (suffix.len() == 1 || !suffix.starts_with('0'));
+            if let (true, false, Ok(number)) = (is_number, base.is_empty(), suffix.parse::<i32>())
+                && number < i32::MAX
+            {
+                return FName {
+                    index: -1,
+                    number: number + 1,
+                    value: base.to_string(),
+                };
+            }
+        }
+
+        FName {
+            index: -1,
+            number: 0,
+            value,
+        }
+    }
+
+    /// Name at `index` of a name table, `None` when the index is out of range.
+    pub(crate) fn from_table(names: &[String], index: i32, number: i32) -> Self {
+        let value = usize::try_from(index)
+            .ok()
+            .and_then(|i| names.get(i))
+            .map_or_else(|| "None".to_string(), String::clone);
+        FName {
             index,
             number,
             value,
-        })
+        }
+    }
+
+    /// Name without the number suffix, as stored in the name table.
+    pub fn value(&self) -> &str {
+        &self.value
     }
-}
 
-impl FName {
     pub fn as_string(&self) -> String {
         if self.number > 0 {
             format!("{}_{}", self.value, self.number - 1)
@@ -0,0 +1,639 @@
+use crate::custom_version::FUE5ReleaseStreamObjectVersion;
+use crate::errors::{ParseError, Result};
+use crate::fname::FName;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
+
+#[repr(i8)]
+#[allow(dead_code)]
+pub enum ETextHistoryType {
+    None = -1,
+    Base = 0,
+    NamedFormat,
+    OrderedFormat,
+    ArgumentFormat,
+    AsNumber,
+    AsPercent,
+    AsCurrency,
+    AsDate,
+    AsTime,
+    AsDateTime,
+    Transform,
+    StringTableEntry,
+    TextGenerator,
+}
+
+#[repr(i8)]
+#[allow(dead_code)]
+pub enum EFormatArgumentType {
+    Int = 0,
+    UInt,
+    Float,
+    Double,
+    Text,
+    Gender,
+}
+
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum ETransformType {
+    ToLower = 0,
+    ToUpper,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FormatArgumentValue {
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    Text(FText),
+    Gender(u8),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NumberFormattingOptions {
+    pub always_sign: bool,
+    pub use_grouping: bool,
+    pub rounding_mode: i8,
+    pub minimum_integral_digits: i32,
+    pub maximum_integral_digits: i32,
+    pub minimum_fractional_digits: i32,
+    pub maximum_fractional_digits: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TextHistory {
+    None {
+        culture_invariant_string: Option<String>,
+    },
+    Base {
+        namespace: String,
+        key: String,
+        source_string: String,
+    },
+    NamedFormat {
+        source_format: Box<FText>,
+        arguments: Vec<(String, FormatArgumentValue)>,
+    },
+    OrderedFormat {
+        source_format: Box<FText>,
+        arguments: Vec<FormatArgumentValue>,
+    },
+    ArgumentFormat {
+        source_format: Box<FText>,
+        arguments: Vec<(String, FormatArgumentValue)>,
+    },
+    AsNumber {
+        source_value: Box<FormatArgumentValue>,
+        format_options: Option<NumberFormattingOptions>,
+        target_culture: String,
+    },
+    AsPercent {
+        source_value: Box<FormatArgumentValue>,
+        format_options: Option<NumberFormattingOptions>,
+        target_culture: String,
+    },
+    AsCurrency {
+        currency_code: String,
+        source_value: Box<FormatArgumentValue>,
+        format_options: Option<NumberFormattingOptions>,
+        target_culture: String,
+    },
+    AsDate {
+        source_date_time: i64,
+        date_style: i8,
+        time_zone: String,
+        target_culture: String,
+    },
+    AsTime {
+        source_date_time: i64,
+        time_style: i8,
+        time_zone: String,
+        target_culture: String,
+    },
+    AsDateTime {
+        source_date_time: i64,
+        date_style: i8,
+        time_style: i8,
+        time_zone: String,
+        target_culture: String,
+    },
+    Transform {
+        source_text: Box<FText>,
+        transform_type: u8,
+    },
+    StringTableEntry {
+        table_id: FName,
+        key: String,
+    },
+    TextGenerator {
+        generator_type: FName,
+        contents: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FText {
+    pub flags: u32,
+    pub history: TextHistory,
+}
+
+impl FormatArgumentValue {
+    pub fn as_string(&self) -> String {
+        match self {
+            FormatArgumentValue::Int(value) => value.to_string(),
+            FormatArgumentValue::UInt(value) => value.to_string(),
+            FormatArgumentValue::Float(value) => value.to_string(),
+            FormatArgumentValue::Double(value) => value.to_string(),
+            FormatArgumentValue::Text(value) => value.as_string(),
+            FormatArgumentValue::Gender(value) => value.to_string(),
+        }
+    }
+}
+
+impl FText {
+    /// Best effort display string; formatted texts are rendered with their
+    /// arguments, but without culture specific number or date formatting.
+    pub fn as_string(&self) -> String {
+        match &self.history {
+            TextHistory::None {
+                culture_invariant_string,
+            } => culture_invariant_string.clone().unwrap_or_default(),
+            TextHistory::Base { source_string, .. } => source_string.clone(),
+            TextHistory::NamedFormat {
+                source_format,
+                arguments,
+            }
+            | TextHistory::ArgumentFormat {
+                source_format,
+                arguments,
+            } => arguments
+                .iter()
+                .fold(source_format.as_string(), |text, (name, value)| {
+                    text.replace(&format!("{{{}}}", name), &value.as_string())
+                }),
+            TextHistory::OrderedFormat {
+                source_format,
+                arguments,
+            } => arguments
+                .iter()
+                .enumerate()
+                .fold(source_format.as_string(), |text, (index, value)| {
+                    text.replace(&format!("{{{}}}", index), &value.as_string())
+                }),
+            TextHistory::AsNumber { source_value, .. }
+            | TextHistory::AsCurrency { source_value, .. } => source_value.as_string(),
+            TextHistory::AsPercent { source_value, .. } => format!("{}%", source_value.as_string()),
+            TextHistory::AsDate {
+                source_date_time, ..
+            }
+            | TextHistory::AsTime {
+                source_date_time, ..
+            }
+            | TextHistory::AsDateTime {
+                source_date_time, ..
+            } => source_date_time.to_string(),
+            TextHistory::Transform {
+                source_text,
+                transform_type,
+            } => {
+                if *transform_type == ETransformType::ToUpper as u8 {
+                    source_text.as_string().to_uppercase()
+                } else {
+                    source_text.as_string().to_lowercase()
+                }
+            }
+            TextHistory::StringTableEntry { key, .. } => key.clone(),
+            TextHistory::TextGenerator { .. } => String::new(),
+        }
+    }
+}
+
+impl<R: Read + Seek> Parsable<FText> for UassetParser<R> {
+    fn parse(&mut self) -> Result<FText> {
+        let flags = self.reader.read_u32::<LittleEndian>()?;
+        let history_type = self.reader.read_i8()?;
+
+        let history = match history_type {
+            t if t == ETextHistoryType::None as i8 => {
+                let has_culture_invariant_string = self.reader.read_u32::<LittleEndian>()? != 0;
+                TextHistory::None {
+                    culture_invariant_string: if has_culture_invariant_string {
+                        Some(self.read_fstring()?)
+                    } else {
+                        None
+                    },
+                }
+            }
+            t if t == ETextHistoryType::Base as i8 => TextHistory::Base {
+                namespace: self.read_fstring()?,
+                key: self.read_fstring()?,
+                source_string: self.read_fstring()?,
+            },
+            t if t == ETextHistoryType::NamedFormat as i8 => TextHistory::NamedFormat {
+                source_format: Box::new(self.read()?),
+                arguments: self.read_tarray(
+                    |p| Ok((p.read_fstring()?, p.read_format_argument_value()?)),
+                    100000,
+                )?,
+            },
+            t if t == ETextHistoryType::OrderedFormat as i8 => TextHistory::OrderedFormat {
+                source_format: Box::new(self.read()?),
+                arguments: self.read_tarray(|p| p.read_format_argument_value(), 100000)?,
+            },
+            t if t == ETextHistoryType::ArgumentFormat as i8 => TextHistory::ArgumentFormat {
+                source_format: Box::new(self.read()?),
+                arguments: self.read_tarray(|p| p.read_format_argument_data(), 100000)?,
+            },
+            t if t == ETextHistoryType::AsNumber as i8
+                || t == ETextHistoryType::AsPercent as i8
+                || t == ETextHistoryType::AsCurrency as i8 =>
+            {
+                self.read_format_number(t)?
+            }
+            t if t == ETextHistoryType::AsDate as i8 => TextHistory::AsDate {
+                source_date_time: self.reader.read_i64::<LittleEndian>()?,
+                date_style: self.reader.read_i8()?,
+                time_zone: self.read_fstring()?,
+                target_culture: self.read_fstring()?,
+            },
+            t if t == ETextHistoryType::AsTime as i8 => TextHistory::AsTime {
+                source_date_time: self.reader.read_i64::<LittleEndian>()?,
+                time_style: self.reader.read_i8()?,
+                time_zone: self.read_fstring()?,
+                target_culture: self.read_fstring()?,
+            },
+            t if t == ETextHistoryType::AsDateTime as i8 => TextHistory::AsDateTime {
+                source_date_time: self.reader.read_i64::<LittleEndian>()?,
+                date_style: self.reader.read_i8()?,
+                time_style: self.reader.read_i8()?,
+                time_zone: self.read_fstring()?,
+                target_culture: self.read_fstring()?,
+            },
+            t if t == ETextHistoryType::Transform as i8 => TextHistory::Transform {
+                source_text: Box::new(self.read()?),
+                transform_type: self.reader.read_u8()?,
+            },
+            t if t == ETextHistoryType::StringTableEntry as i8 => TextHistory::StringTableEntry {
+                table_id: self.read()?,
+                key: self.read_fstring()?,
+            },
+            t if t == ETextHistoryType::TextGenerator as i8 => {
+                let generator_type: FName = self.read()?;
+                let contents = if generator_type.is_none() {
+                    vec![]
+                } else {
+                    self.read_tarray(|p| Ok(p.reader.read_u8()?), 1024 * 1024 * 64)?
+                };
+                TextHistory::TextGenerator {
+                    generator_type,
+                    contents,
+                }
+            }
+            t => return Err(ParseError::UnsupportedTextHistory(t)),
+        };
+
+        Ok(FText { flags, history })
+    }
+}
+
+/// Integers of argument format histories were widened to 64 bit by a custom
+/// version, unversioned packages that don't know it are assumed to be recent.
+fn wide_format_integers(version: Option<i32>, unversioned: bool) -> bool {
+    version.map_or(unversioned, |version| {
+        version >= FUE5ReleaseStreamObjectVersion::TextFormatArgumentData64bitSupport as i32
+    })
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    fn read_format_argument_value(&mut self) -> Result<FormatArgumentValue> {
+        let argument_type = self.reader.read_i8()?;
+        self.read_format_argument(argument_type, true)
+    }
+
+    /// Named argument of an argument format history.
+    fn read_format_argument_data(&mut self) -> Result<(String, FormatArgumentValue)> {
+        let name = self.read_fstring()?;
+        let argument_type = self.reader.read_i8()?;
+        let wide_integers = wide_format_integers(
+            self.custom_version::<FUE5ReleaseStreamObjectVersion>(),
+            self.summary()?.unversioned,
+        );
+        Ok((
+            name,
+            self.read_format_argument(argument_type, wide_integers)?,
+        ))
+    }
+
+    fn read_format_argument(
+        &mut self,
+        argument_type: i8,
+        wide_integers: bool,
+    ) -> Result<FormatArgumentValue> {
+        let value = match argument_type {
+            t if t == EFormatArgumentType::Int as i8 => {
+                if wide_integers {
+                    FormatArgumentValue::Int(self.reader.read_i64::<LittleEndian>()?)
+                } else {
+                    FormatArgumentValue::Int(self.reader.read_i32::<LittleEndian>()? as i64)
+                }
+            }
+            t if t == EFormatArgumentType::UInt as i8 => {
+                FormatArgumentValue::UInt(self.reader.read_u64::<LittleEndian>()?)
+            }
+            t if t == EFormatArgumentType::Float as i8 => {
+                FormatArgumentValue::Float(self.reader.read_f32::<LittleEndian>()?)
+            }
+            t if t == EFormatArgumentType::Double as i8 => {
+                FormatArgumentValue::Double(self.reader.read_f64::<LittleEndian>()?)
+            }
+            t if t == EFormatArgumentType::Text as i8 => FormatArgumentValue::Text(self.read()?),
+            t if t == EFormatArgumentType::Gender as i8 => {
+                FormatArgumentValue::Gender(self.reader.read_u8()?)
+            }
+            t => return Err(ParseError::UnsupportedFormatArgumentType(t)),
+        };
+        Ok(value)
+    }
+
+    fn read_format_number(&mut self, history_type: i8) -> Result<TextHistory> {
+        let currency_code = if history_type == ETextHistoryType::AsCurrency as i8 {
+            Some(self.read_fstring()?)
+        } else {
+            None
+        };
+
+        let source_value = Box::new(self.read_format_argument_value()?);
+        let has_format_options = self.reader.read_u32::<LittleEndian>()? != 0;
+        let format_options = if has_format_options {
+            Some(NumberFormattingOptions {
+                always_sign: self.reader.read_u32::<LittleEndian>()? != 0,
+                use_grouping: self.reader.read_u32::<LittleEndian>()? != 0,
+                rounding_mode: self.reader.read_i8()?,
+                minimum_integral_digits: self.reader.read_i32::<LittleEndian>()?,
+                maximum_integral_digits: self.reader.read_i32::<LittleEndian>()?,
+                minimum_fractional_digits: self.reader.read_i32::<LittleEndian>()?,
+                maximum_fractional_digits: self.reader.read_i32::<LittleEndian>()?,
+            })
+        } else {
+            None
+        };
+        let target_culture = self.read_fstring()?;
+
+        Ok(match currency_code {
+            Some(currency_code) => TextHistory::AsCurrency {
+                currency_code,
+                source_value,
+                format_options,
+                target_culture,
+            },
+            None if history_type == ETextHistoryType::AsPercent as i8 => TextHistory::AsPercent {
+                source_value,
+                format_options,
+                target_culture,
+            },
+            None => TextHistory::AsNumber {
+                source_value,
+                format_options,
+                target_culture,
+            },
+        })
+    }
+}
+
+impl<W: Write + Seek> Writable<FText> for UassetWriter<W> {
+    fn serialize(&mut self, value: &FText) -> Result<()> {
+        self.writer.write_u32::<LittleEndian>(value.flags)?;
+
+        match &value.history {
+            TextHistory::None {
+                culture_invariant_string,
+            } => {
+                self.writer.write_i8(ETextHistoryType::None as i8)?;
+                self.writer
+                    .write_u32::<LittleEndian>(culture_invariant_string.is_some() as u32)?;
+                if let Some(string) = culture_invariant_string {
+                    self.write_fstring(string)?;
+                }
+            }
+            TextHistory::Base {
+                namespace,
+                key,
+                source_string,
+            } => {
+                self.writer.write_i8(ETextHistoryType::Base as i8)?;
+                self.write_fstring(namespace)?;
+                self.write_fstring(key)?;
+                self.write_fstring(source_string)?;
+            }
+            TextHistory::NamedFormat {
+                source_format,
+                arguments,
+            } => {
+                self.writer.write_i8(ETextHistoryType::NamedFormat as i8)?;
+                self.write(source_format.as_ref())?;
+                self.write_tarray(arguments, |w, (name, value)| {
+                    w.write_fstring(name)?;
+                    w.write_format_argument_value(value)
+                })?;
+            }
+            TextHistory::OrderedFormat {
+                source_format,
+                arguments,
+            } => {
+                self.writer
+                    .write_i8(ETextHistoryType::OrderedFormat as i8)?;
+                self.write(source_format.as_ref())?;
+                self.write_tarray(arguments, |w, value| w.write_format_argument_value(value))?;
+            }
+            TextHistory::ArgumentFormat {
+                source_format,
+                arguments,
+            } => {
+                self.writer
+                    .write_i8(ETextHistoryType::ArgumentFormat as i8)?;
+                self.write(source_format.as_ref())?;
+                let summary = self.get_summary();
+                let wide_integers = wide_format_integers(
+                    summary.custom_version::<FUE5ReleaseStreamObjectVersion>(),
+                    summary.unversioned,
+                );
+                self.write_tarray(arguments, |w, (name, value)| {
+                    w.write_fstring(name)?;
+                    w.write_format_argument(value, wide_integers)
+                })?;
+            }
+            TextHistory::AsNumber {
+                source_value,
+                format_options,
+                target_culture,
+            } => {
+                self.writer.write_i8(ETextHistoryType::AsNumber as i8)?;
+                self.write_format_number(source_value, format_options, target_culture)?;
+            }
+            TextHistory::AsPercent {
+                source_value,
+                format_options,
+                target_culture,
+            } => {
+                self.writer.write_i8(ETextHistoryType::AsPercent as i8)?;
+                self.write_format_number(source_value, format_options, target_culture)?;
+            }
+            TextHistory::AsCurrency {
+                currency_code,
+                source_value,
+                format_options,
+                target_culture,
+            } => {
+                self.writer.write_i8(ETextHistoryType::AsCurrency as i8)?;
+                self.write_fstring(currency_code)?;
+                self.write_format_number(source_value, format_options, target_culture)?;
+            }
+            TextHistory::AsDate {
+                source_date_time,
+                date_style,
+                time_zone,
+                target_culture,
+            } => {
+                self.writer.write_i8(ETextHistoryType::AsDate as i8)?;
+                self.writer.write_i64::<LittleEndian>(*source_date_time)?;
+                self.writer.write_i8(*date_style)?;
+                self.write_fstring(time_zone)?;
+                self.write_fstring(target_culture)?;
+            }
+            TextHistory::AsTime {
+                source_date_time,
+                time_style,
+                time_zone,
+                target_culture,
+            } => {
+                self.writer.write_i8(ETextHistoryType::AsTime as i8)?;
+                self.writer.write_i64::<LittleEndian>(*source_date_time)?;
+                self.writer.write_i8(*time_style)?;
+                self.write_fstring(time_zone)?;
+                self.write_fstring(target_culture)?;
+            }
+            TextHistory::AsDateTime {
+                source_date_time,
+                date_style,
+                time_style,
+                time_zone,
+                target_culture,
+            } => {
+                self.writer.write_i8(ETextHistoryType::AsDateTime as i8)?;
+                self.writer.write_i64::<LittleEndian>(*source_date_time)?;
+                self.writer.write_i8(*date_style)?;
+                self.writer.write_i8(*time_style)?;
+                self.write_fstring(time_zone)?;
+                self.write_fstring(target_culture)?;
+            }
+            TextHistory::Transform {
+                source_text,
+                transform_type,
+            } => {
+                self.writer.write_i8(ETextHistoryType::Transform as i8)?;
+                self.write(source_text.as_ref())?;
+                self.writer.write_u8(*transform_type)?;
+            }
+            TextHistory::StringTableEntry { table_id, key } => {
+                self.writer
+                    .write_i8(ETextHistoryType::StringTableEntry as i8)?;
+                self.write(table_id)?;
+                self.write_fstring(key)?;
+            }
+            TextHistory::TextGenerator {
+                generator_type,
+                contents,
+            } => {
+                self.writer
+                    .write_i8(ETextHistoryType::TextGenerator as i8)?;
+                self.write(generator_type)?;
+                if !generator_type.is_none() {
+                    self.writer
+                        .write_i32::<LittleEndian>(contents.len() as i32)?;
+                    self.writer.write_all(contents)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> UassetWriter<W> {
+    fn write_format_argument_value(&mut self, value: &FormatArgumentValue) -> Result<()> {
+        self.write_format_argument(value, true)
+    }
+
+    fn write_format_argument(
+        &mut self,
+        value: &FormatArgumentValue,
+        wide_integers: bool,
+    ) -> Result<()> {
+        match value {
+            FormatArgumentValue::Int(value) => {
+                self.writer.write_i8(EFormatArgumentType::Int as i8)?;
+                if wide_integers {
+                    self.writer.write_i64::<LittleEndian>(*value)?;
+                } else {
+                    self.writer.write_i32::<LittleEndian>(*value as i32)?;
+                }
+            }
+            FormatArgumentValue::UInt(value) => {
+                self.writer.write_i8(EFormatArgumentType::UInt as i8)?;
+                self.writer.write_u64::<LittleEndian>(*value)?;
+            }
+            FormatArgumentValue::Float(value) => {
+                self.writer.write_i8(EFormatArgumentType::Float as i8)?;
+                self.writer.write_f32::<LittleEndian>(*value)?;
+            }
+            FormatArgumentValue::Double(value) => {
+                self.writer.write_i8(EFormatArgumentType::Double as i8)?;
+                self.writer.write_f64::<LittleEndian>(*value)?;
+            }
+            FormatArgumentValue::Text(value) => {
+                self.writer.write_i8(EFormatArgumentType::Text as i8)?;
+                self.write(value)?;
+            }
+            FormatArgumentValue::Gender(value) => {
+                self.writer.write_i8(EFormatArgumentType::Gender as i8)?;
+                self.writer.write_u8(*value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_format_number(
+        &mut self,
+        source_value: &FormatArgumentValue,
+        format_options: &Option<NumberFormattingOptions>,
+        target_culture: &str,
+    ) -> Result<()> {
+        self.write_format_argument_value(source_value)?;
+        self.writer
+            .write_u32::<LittleEndian>(format_options.is_some() as u32)?;
+        if let Some(options) = format_options {
+            self.writer
+                .write_u32::<LittleEndian>(options.always_sign as u32)?;
+            self.writer
+                .write_u32::<LittleEndian>(options.use_grouping as u32)?;
+            self.writer.write_i8(options.rounding_mode)?;
+            self.writer
+                .write_i32::<LittleEndian>(options.minimum_integral_digits)?;
+            self.writer
+                .write_i32::<LittleEndian>(options.maximum_integral_digits)?;
+            self.writer
+                .write_i32::<LittleEndian>(options.minimum_fractional_digits)?;
+            self.writer
+                .write_i32::<LittleEndian>(options.maximum_fractional_digits)?;
+        }
+        self.write_fstring(target_culture)
+    }
+}
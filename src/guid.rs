@@ -0,0 +1,75 @@
+use crate::errors::Result;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fmt;
+use std::io::{Read, Seek, Write};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Guid {
+    pub a: u32,
+    pub b: u32,
+    pub c: u32,
+    pub d: u32,
+}
+
+impl Guid {
+    pub const fn new(a: u32, b: u32, c: u32, d: u32) -> Self {
+        Self { a, b, c, d }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (self.a | self.b | self.c | self.d) != 0
+    }
+
+    /// Parses the 32 hex digits `Display` writes, the form complete type
+    /// names store struct GUIDs in.
+    pub fn from_digits(digits: &str) -> Option<Self> {
+        if digits.len() != 32 || !digits.is_ascii() {
+            return None;
+        }
+        let part = |i: usize| u32::from_str_radix(&digits[i * 8..i * 8 + 8], 16).ok();
+        Some(Self::new(part(0)?, part(1)?, part(2)?, part(3)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Guid {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08X}{:08X}{:08X}{:08X}",
+            self.a, self.b, self.c, self.d
+        )
+    }
+}
+
+impl<R: Read + Seek> Parsable<Guid> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Guid> {
+        Ok(Guid {
+            a: self.reader.read_u32::<LittleEndian>()?,
+            b: self.reader.read_u32::<LittleEndian>()?,
+            c: self.reader.read_u32::<LittleEndian>()?,
+            d: self.reader.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl<W: Write + Seek> Writable<Guid> for UassetWriter<W> {
+    fn serialize(&mut self, value: &Guid) -> Result<()> {
+        self.writer.write_u32::<LittleEndian>(value.a)?;
+        self.writer.write_u32::<LittleEndian>(value.b)?;
+        self.writer.write_u32::<LittleEndian>(value.c)?;
+        self.writer.write_u32::<LittleEndian>(value.d)?;
+        Ok(())
+    }
+}
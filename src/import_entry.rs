@@ -0,0 +1,77 @@
+use crate::errors::Result;
+use crate::fname::FName;
+use crate::package_index::PackageIndex;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_summary::EPackageFlags;
+use crate::uasset_writer::{UassetWriter, Writable};
+use crate::versions::EUnrealEngineObjectUE5Version;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImportEntry {
+    pub class_package: FName,
+    pub class_name: FName,
+    pub outer_index: PackageIndex,
+    pub object_name: FName,
+    pub package_name: Option<FName>,
+    pub import_optional: bool,
+}
+
+impl<R: Read + Seek> Parsable<ImportEntry> for UassetParser<R> {
+    fn parse(&mut self) -> Result<ImportEntry> {
+        let class_package: FName = self.read()?;
+        let class_name: FName = self.read()?;
+        let outer_index: PackageIndex = self.read()?;
+        let object_name: FName = self.read()?;
+
+        // Editor-only data is stripped from cooked packages, the package name included
+        let package_name =
+            if self.get_summary().package_flags & EPackageFlags::FilterEditorOnly as u32 == 0 {
+                Some(self.read()?)
+            } else {
+                None
+            };
+
+        let import_optional = if self.get_summary().file_version_ue5
+            >= EUnrealEngineObjectUE5Version::OptionalResources as i32
+        {
+            self.reader.read_u32::<LittleEndian>()? != 0
+        } else {
+            false
+        };
+
+        Ok(ImportEntry {
+            class_package,
+            class_name,
+            outer_index,
+            object_name,
+            package_name,
+            import_optional,
+        })
+    }
+}
+
+impl<W: Write + Seek> Writable<ImportEntry> for UassetWriter<W> {
+    fn serialize(&mut self, value: &ImportEntry) -> Result<()> {
+        self.write(&value.class_package)?;
+        self.write(&value.class_name)?;
+        self.write(&value.outer_index)?;
+        self.write(&value.object_name)?;
+
+        if self.get_summary().package_flags & EPackageFlags::FilterEditorOnly as u32 == 0 {
+            let package_name = value.package_name.clone().unwrap_or(FName::new("None"));
+            self.write(&package_name)?;
+        }
+
+        if self.get_summary().file_version_ue5
+            >= EUnrealEngineObjectUE5Version::OptionalResources as i32
+        {
+            self.writer
+                .write_u32::<LittleEndian>(value.import_optional as u32)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,550 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+
+use crate::errors::{ParseError, Result};
+use crate::package_reader::PackageReader;
+use crate::pak::{AES_BLOCK_SIZE, decompress, decrypt, read_count, read_fstring, remaining};
+use crate::uasset_parser::UassetParser;
+use crate::zen_package::ScriptObjects;
+
+const TOC_MAGIC: &[u8; 16] = b"-==--==--==--==-";
+const TOC_HEADER_SIZE: u32 = 144;
+const COMPRESSED_BLOCK_ENTRY_SIZE: u32 = 12;
+/// Size of a chunk id followed by its offset and length
+const CHUNK_ENTRY_SIZE: u64 = 12 + 10;
+const INVALID_INDEX: u32 = u32::MAX;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EIoStoreTocVersion {
+    Invalid = 0,
+    Initial,
+    DirectoryIndex,
+    PartitionSize,
+    PerfectHash,
+    PerfectHashWithOverflow,
+    OnDemandMetaData,
+    RemovedOnDemandMetaData,
+    ReplaceIoChunkHashWithIoHash,
+}
+
+#[repr(u8)]
+pub enum EIoContainerFlags {
+    None = 0,
+    Compressed = 1,
+    Encrypted = 2,
+    Signed = 4,
+    Indexed = 8,
+    OnDemand = 16,
+}
+
+#[repr(u8)]
+pub enum EIoChunkType {
+    Invalid = 0,
+    ExportBundleData,
+    BulkData,
+    OptionalBulkData,
+    MemoryMappedBulkData,
+    ScriptObjects,
+    ContainerHeader,
+    ExternalFile,
+    ShaderCodeLibrary,
+    ShaderCode,
+    PackageStoreEntry,
+    DerivedData,
+    EditorDerivedData,
+    PackageResource,
+}
+
+/// Identifies a chunk of a container, for package data the id is the
+/// package id and the type tells the package header from its bulk data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IoChunkId {
+    pub id: u64,
+    pub index: u16,
+    pub chunk_type: u8,
+}
+
+impl IoChunkId {
+    pub fn new(id: u64, index: u16, chunk_type: EIoChunkType) -> Self {
+        IoChunkId {
+            id,
+            index,
+            chunk_type: chunk_type as u8,
+        }
+    }
+
+    fn read(reader: &mut Cursor<Vec<u8>>) -> Result<Self> {
+        let id = reader.read_u64::<LittleEndian>()?;
+        // The index is stored in network order
+        let index = reader.read_u16::<BigEndian>()?;
+        let _padding = reader.read_u8()?;
+        let chunk_type = reader.read_u8()?;
+        Ok(IoChunkId {
+            id,
+            index,
+            chunk_type,
+        })
+    }
+}
+
+impl fmt::Display for IoChunkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}:{}:{}", self.id, self.index, self.chunk_type)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IoCompressionBlock {
+    offset: u64,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    compression_method: u8,
+}
+
+/// Offset and length of a chunk in the uncompressed data of the container.
+#[derive(Debug, Clone, Copy)]
+struct IoOffsetAndLength {
+    offset: u64,
+    length: u64,
+}
+
+/// Container of the IoStore, the table of contents of a `.utoc` file over the
+/// data of its `.ucas` partitions. Files are looked up by their path below
+/// the mount point when the container has a directory index.
+pub struct IoStoreReader<R: Read + Seek> {
+    partitions: Vec<R>,
+    key: Option<[u8; 32]>,
+    pub version: u8,
+    pub container_id: u64,
+    pub container_flags: u8,
+    pub mount_point: String,
+    pub compression_methods: Vec<String>,
+    compression_block_size: u64,
+    partition_size: u64,
+    chunk_ids: Vec<IoChunkId>,
+    chunk_offsets: Vec<IoOffsetAndLength>,
+    chunk_indices: HashMap<IoChunkId, usize>,
+    compression_blocks: Vec<IoCompressionBlock>,
+    files: BTreeMap<String, usize>,
+}
+
+impl IoStoreReader<BufReader<File>> {
+    /// Opens the `.utoc` at `path` and the `.ucas` partitions next to it,
+    /// named `<name>.ucas`, `<name>_s1.ucas` and so on.
+    pub fn from_file(path: impl AsRef<Path>, key: Option<[u8; 32]>) -> Result<Self> {
+        let path = path.as_ref();
+        let toc = BufReader::new(File::open(path)?);
+
+        let stem = path.with_extension("");
+        let mut partitions = vec![BufReader::new(File::open(path.with_extension("ucas"))?)];
+        while let Ok(partition) = File::open(format!(
+            "{}_s{}.ucas",
+            stem.to_string_lossy(),
+            partitions.len()
+        )) {
+            partitions.push(BufReader::new(partition));
+        }
+
+        IoStoreReader::from_readers(toc, partitions, key)
+    }
+}
+
+impl<R: Read + Seek> IoStoreReader<R> {
+    /// Reads the table of contents from `toc`, `key` is needed when the
+    /// container is encrypted.
+    pub fn from_readers(
+        mut toc: impl Read,
+        partitions: Vec<R>,
+        key: Option<[u8; 32]>,
+    ) -> Result<Self> {
+        let mut data = vec![];
+        toc.read_to_end(&mut data)?;
+        let mut toc = Cursor::new(data);
+
+        let mut magic = [0u8; 16];
+        toc.read_exact(&mut magic)?;
+        if &magic != TOC_MAGIC {
+            return Err(ParseError::InvalidIoStoreToc);
+        }
+
+        let version = toc.read_u8()?;
+        if version == EIoStoreTocVersion::Invalid as u8
+            || version > EIoStoreTocVersion::ReplaceIoChunkHashWithIoHash as u8
+        {
+            return Err(ParseError::UnsupportedIoStoreVersion(version));
+        }
+        let _reserved = (toc.read_u8()?, toc.read_u16::<LittleEndian>()?);
+
+        let header_size = toc.read_u32::<LittleEndian>()?;
+        let entry_count = toc.read_u32::<LittleEndian>()?;
+        let compressed_block_count = toc.read_u32::<LittleEndian>()?;
+        let compressed_block_entry_size = toc.read_u32::<LittleEndian>()?;
+        let compression_method_count = toc.read_u32::<LittleEndian>()?;
+        let compression_method_length = toc.read_u32::<LittleEndian>()?;
+        let compression_block_size = toc.read_u32::<LittleEndian>()?;
+        let directory_index_size = toc.read_u32::<LittleEndian>()?;
+        let _partition_count = toc.read_u32::<LittleEndian>()?;
+        let container_id = toc.read_u64::<LittleEndian>()?;
+        let mut encryption_key_guid = [0u8; 16];
+        toc.read_exact(&mut encryption_key_guid)?;
+        let container_flags = toc.read_u8()?;
+        let _reserved = (toc.read_u8()?, toc.read_u16::<LittleEndian>()?);
+        let perfect_hash_seed_count = toc.read_u32::<LittleEndian>()?;
+        let partition_size = toc.read_u64::<LittleEndian>()?;
+        let chunks_without_perfect_hash_count = toc.read_u32::<LittleEndian>()?;
+
+        if header_size != TOC_HEADER_SIZE
+            || compressed_block_entry_size != COMPRESSED_BLOCK_ENTRY_SIZE
+            || compression_block_size == 0
+        {
+            return Err(ParseError::InvalidIoStoreToc);
+        }
+        toc.seek(SeekFrom::Start(header_size as u64))?;
+
+        check_toc_size(&toc, entry_count as u64 * CHUNK_ENTRY_SIZE)?;
+        let mut chunk_ids = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            chunk_ids.push(IoChunkId::read(&mut toc)?);
+        }
+
+        let mut chunk_offsets = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            // Two 40 bit big endian values
+            let mut bytes = [0u8; 10];
+            toc.read_exact(&mut bytes)?;
+            let read_u40 =
+                |bytes: &[u8]| bytes.iter().fold(0u64, |value, &b| value << 8 | b as u64);
+            chunk_offsets.push(IoOffsetAndLength {
+                offset: read_u40(&bytes[..5]),
+                length: read_u40(&bytes[5..]),
+            });
+        }
+
+        // Perfect hash seeds speed up chunk lookups by the engine
+        if version >= EIoStoreTocVersion::PerfectHash as u8 {
+            toc.seek(SeekFrom::Current(perfect_hash_seed_count as i64 * 4))?;
+        }
+        if version >= EIoStoreTocVersion::PerfectHashWithOverflow as u8 {
+            toc.seek(SeekFrom::Current(
+                chunks_without_perfect_hash_count as i64 * 4,
+            ))?;
+        }
+
+        check_toc_size(
+            &toc,
+            compressed_block_count as u64 * COMPRESSED_BLOCK_ENTRY_SIZE as u64,
+        )?;
+        let mut compression_blocks = Vec::with_capacity(compressed_block_count as usize);
+        for _ in 0..compressed_block_count {
+            // 40 bit offset and two 24 bit sizes, little endian
+            let mut bytes = [0u8; 12];
+            toc.read_exact(&mut bytes)?;
+            let read_le = |bytes: &[u8]| {
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0u64, |value, &b| value << 8 | b as u64)
+            };
+            compression_blocks.push(IoCompressionBlock {
+                offset: read_le(&bytes[..5]),
+                compressed_size: read_le(&bytes[5..8]) as u32,
+                uncompressed_size: read_le(&bytes[8..11]) as u32,
+                compression_method: bytes[11],
+            });
+        }
+
+        check_toc_size(
+            &toc,
+            compression_method_count as u64 * compression_method_length as u64,
+        )?;
+        let mut compression_methods = vec![];
+        for _ in 0..compression_method_count {
+            let mut name = vec![0u8; compression_method_length as usize];
+            toc.read_exact(&mut name)?;
+            let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            compression_methods.push(String::from_utf8_lossy(&name[..len]).into_owned());
+        }
+
+        if container_flags & EIoContainerFlags::Signed as u8 != 0 {
+            let hash_size = toc.read_i32::<LittleEndian>()?;
+            if hash_size < 0 {
+                return Err(ParseError::InvalidIoStoreToc);
+            }
+            // Signatures of the table of contents and the blocks, then the block hashes
+            toc.seek(SeekFrom::Current(
+                hash_size as i64 * 2 + compressed_block_count as i64 * 20,
+            ))?;
+        }
+
+        let mut reader = IoStoreReader {
+            partitions,
+            key,
+            version,
+            container_id,
+            container_flags,
+            mount_point: String::new(),
+            compression_methods,
+            compression_block_size: compression_block_size as u64,
+            partition_size: if version >= EIoStoreTocVersion::PartitionSize as u8
+                && partition_size > 0
+            {
+                partition_size
+            } else {
+                u64::MAX
+            },
+            chunk_indices: chunk_ids
+                .iter()
+                .enumerate()
+                .map(|(index, id)| (*id, index))
+                .collect(),
+            chunk_ids,
+            chunk_offsets,
+            compression_blocks,
+            files: BTreeMap::new(),
+        };
+
+        if version >= EIoStoreTocVersion::DirectoryIndex as u8
+            && container_flags & EIoContainerFlags::Indexed as u8 != 0
+            && directory_index_size > 0
+        {
+            check_toc_size(&toc, directory_index_size as u64)?;
+            let mut directory_index = vec![0u8; directory_index_size as usize];
+            toc.read_exact(&mut directory_index)?;
+            if reader.is_encrypted() {
+                decrypt(reader.key.as_ref(), &mut directory_index)?;
+            }
+            reader.read_directory_index(Cursor::new(directory_index))?;
+        }
+
+        Ok(reader)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.container_flags & EIoContainerFlags::Encrypted as u8 != 0
+    }
+
+    pub fn chunk_ids(&self) -> &[IoChunkId] {
+        &self.chunk_ids
+    }
+
+    pub fn has_chunk(&self, id: &IoChunkId) -> bool {
+        self.chunk_indices.contains_key(id)
+    }
+
+    /// Paths of the files below the mount point, empty without a directory index.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Chunk of the file at `path`, either below the mount point or including it.
+    pub fn chunk_id(&self, path: &str) -> Option<IoChunkId> {
+        let path = path.strip_prefix(&self.mount_point).unwrap_or(path);
+        self.files.get(path).map(|&index| self.chunk_ids[index])
+    }
+
+    /// Reads, decrypts and decompresses the chunk `id`.
+    pub fn read_chunk(&mut self, id: &IoChunkId) -> Result<Vec<u8>> {
+        let index = *self
+            .chunk_indices
+            .get(id)
+            .ok_or_else(|| ParseError::IoChunkNotFound(id.to_string()))?;
+        let IoOffsetAndLength { offset, length } = self.chunk_offsets[index];
+        if length == 0 {
+            return Ok(vec![]);
+        }
+
+        let first_block = offset / self.compression_block_size;
+        let last_block = (offset + length - 1) / self.compression_block_size;
+        let mut data = vec![];
+        for block_index in first_block..=last_block {
+            let block = *self
+                .compression_blocks
+                .get(block_index as usize)
+                .ok_or(ParseError::InvalidIoStoreToc)?;
+            self.read_block(&block, &mut data)?;
+        }
+
+        let start = (offset - first_block * self.compression_block_size) as usize;
+        data.get(start..start + length as usize)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| ParseError::DecompressionFailed(format!("chunk {} is truncated", id)))
+    }
+
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        let id = self
+            .chunk_id(path)
+            .ok_or_else(|| ParseError::IoChunkNotFound(path.to_string()))?;
+        self.read_chunk(&id)
+    }
+
+    /// Script objects stored in the global container, `global.utoc`.
+    pub fn read_script_objects(&mut self) -> Result<ScriptObjects> {
+        let id = self
+            .chunk_ids
+            .iter()
+            .find(|id| id.chunk_type == EIoChunkType::ScriptObjects as u8)
+            .copied()
+            .ok_or_else(|| ParseError::IoChunkNotFound("ScriptObjects".to_string()))?;
+        ScriptObjects::from_reader(Cursor::new(self.read_chunk(&id)?))
+    }
+
+    /// Opens the package at `path` (a `.uasset` or `.umap`) together with its
+    /// bulk data chunks. The package still needs
+    /// [`parse_zen_package`](UassetParser::parse_zen_package).
+    pub fn open_package(
+        &mut self,
+        path: &str,
+    ) -> Result<UassetParser<PackageReader<Cursor<Vec<u8>>>>> {
+        let id = self
+            .chunk_id(path)
+            .ok_or_else(|| ParseError::IoChunkNotFound(path.to_string()))?;
+        let mut reader = PackageReader::new(Cursor::new(self.read_chunk(&id)?))?;
+        reader.bulk = self.read_companion(IoChunkId::new(id.id, 0, EIoChunkType::BulkData))?;
+        reader.optional_bulk =
+            self.read_companion(IoChunkId::new(id.id, 0, EIoChunkType::OptionalBulkData))?;
+        Ok(UassetParser::new(reader))
+    }
+
+    fn read_companion(&mut self, id: IoChunkId) -> Result<Option<Cursor<Vec<u8>>>> {
+        if self.has_chunk(&id) {
+            self.read_chunk(&id).map(|data| Some(Cursor::new(data)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Appends the uncompressed data of `block` to `data`.
+    fn read_block(&mut self, block: &IoCompressionBlock, data: &mut Vec<u8>) -> Result<()> {
+        let size = if self.is_encrypted() {
+            (block.compressed_size as usize).next_multiple_of(AES_BLOCK_SIZE)
+        } else {
+            block.compressed_size as usize
+        };
+        let partition = self
+            .partitions
+            .get_mut((block.offset / self.partition_size) as usize)
+            .ok_or(ParseError::InvalidIoStoreToc)?;
+
+        let mut compressed = vec![0u8; size];
+        partition.seek(SeekFrom::Start(block.offset % self.partition_size))?;
+        partition.read_exact(&mut compressed)?;
+        if self.is_encrypted() {
+            decrypt(self.key.as_ref(), &mut compressed)?;
+        }
+
+        if block.compression_method == 0 {
+            data.extend_from_slice(&compressed[..block.uncompressed_size as usize]);
+            return Ok(());
+        }
+
+        let method = self
+            .compression_methods
+            .get(block.compression_method as usize - 1)
+            .cloned()
+            .unwrap_or_else(|| block.compression_method.to_string());
+        let start = data.len();
+        decompress(&method, &compressed[..block.compressed_size as usize], data)?;
+        if data.len() - start != block.uncompressed_size as usize {
+            return Err(ParseError::DecompressionFailed(format!(
+                "expected {} bytes, got {}",
+                block.uncompressed_size,
+                data.len() - start
+            )));
+        }
+        Ok(())
+    }
+
+    /// Directory tree whose files point at entries of the table of contents,
+    /// names are indices into a string table at its end.
+    fn read_directory_index(&mut self, mut index: Cursor<Vec<u8>>) -> Result<()> {
+        self.mount_point = read_fstring(&mut index)?;
+
+        let mut directories = vec![];
+        for _ in 0..read_count(&mut index)? {
+            let mut entry = [0u32; 4];
+            index.read_u32_into::<LittleEndian>(&mut entry)?;
+            directories.push(entry);
+        }
+        let mut files = vec![];
+        for _ in 0..read_count(&mut index)? {
+            let mut entry = [0u32; 3];
+            index.read_u32_into::<LittleEndian>(&mut entry)?;
+            files.push(entry);
+        }
+        let mut strings = vec![];
+        for _ in 0..read_count(&mut index)? {
+            strings.push(read_fstring(&mut index)?);
+        }
+        let string = |name: u32| {
+            strings
+                .get(name as usize)
+                .map(String::as_str)
+                .ok_or(ParseError::InvalidIoStoreToc)
+        };
+
+        // Directories are linked through their first child and next sibling
+        let mut pending = vec![(0u32, String::new())];
+        let mut visited = 0;
+        let mut visited_files = 0;
+        while let Some((directory_index, parent)) = pending.pop() {
+            // Links of a corrupt index could loop forever
+            visited += 1;
+            if visited > directories.len() {
+                return Err(ParseError::InvalidIoStoreToc);
+            }
+            let Some(&[name, first_child, next_sibling, first_file]) =
+                directories.get(directory_index as usize)
+            else {
+                return Err(ParseError::InvalidIoStoreToc);
+            };
+            // The root directory has no name
+            let path = if name == INVALID_INDEX {
+                parent.clone()
+            } else {
+                format!("{}{}/", parent, string(name)?)
+            };
+
+            let mut file_index = first_file;
+            while file_index != INVALID_INDEX {
+                // So could the links between the files
+                visited_files += 1;
+                if visited_files > files.len() {
+                    return Err(ParseError::InvalidIoStoreToc);
+                }
+                let &[file_name, next_file, chunk_index] = files
+                    .get(file_index as usize)
+                    .ok_or(ParseError::InvalidIoStoreToc)?;
+                if (chunk_index as usize) < self.chunk_ids.len() {
+                    self.files.insert(
+                        format!("{}{}", path, string(file_name)?),
+                        chunk_index as usize,
+                    );
+                }
+                file_index = next_file;
+            }
+
+            if next_sibling != INVALID_INDEX && directory_index != 0 {
+                pending.push((next_sibling, parent));
+            }
+            if first_child != INVALID_INDEX {
+                pending.push((first_child, path));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `size` bytes are left in the table of contents before
+/// allocating for them.
+fn check_toc_size(toc: &Cursor<Vec<u8>>, size: u64) -> Result<()> {
+    if size > remaining(toc) as u64 {
+        return Err(ParseError::InvalidIoStoreToc);
+    }
+    Ok(())
+}
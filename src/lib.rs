@@ -1,10 +1,30 @@
+pub mod compressed_package;
+pub mod custom_version;
 pub mod data;
 pub mod errors;
 pub mod export_entry;
 pub mod fname;
+pub mod ftext;
+pub mod guid;
+pub mod import_entry;
+pub mod io_store;
+pub mod name_hash;
+#[cfg(feature = "serde")]
+pub mod package_dump;
+pub mod package_index;
+pub mod package_reader;
+pub mod pak;
+pub mod parser_registry;
 pub mod property_data;
+#[cfg(feature = "serde")]
+pub mod property_deserializer;
 pub mod property_tag;
 pub mod property_type_name;
+pub mod struct_value;
 pub mod uasset_parser;
 pub mod uasset_summary;
+pub mod uasset_writer;
+pub mod unversioned_properties;
+pub mod usmap;
 pub mod versions;
+pub mod zen_package;
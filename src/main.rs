@@ -1,28 +1,131 @@
-use std::{env, fs::File, io::Seek, process};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufWriter, Cursor, Write},
+    path::Path,
+    process,
+    sync::Arc,
+};
 
-use rust_uasset::{data::data_table::DataTable, uasset_parser::UassetParser};
+use rust_uasset::{uasset_parser::UassetParser, uasset_writer::UassetWriter, usmap::Usmap};
+
+const USAGE: &str = "Usage: {} <filename> [--format csv|json] [--output <file>] [--mappings <file.usmap>]\n       \
+     {} <filename> --import <rows.csv|rows.json> --output <file.uasset>";
+
+enum Format {
+    Csv,
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    let usage = USAGE.replace("{}", &args[0]);
+
+    let mut file_path = None;
+    let mut format = Format::Csv;
+    let mut output = None;
+    let mut mappings = None;
+    let mut import = None;
 
-    if args.len() != 2 {
-        eprintln!("Usage: {} <filename>", args[0]);
-        process::exit(1);
+    let mut remaining = args.iter().skip(1);
+    while let Some(arg) = remaining.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match remaining.next().map(String::as_str) {
+                    Some("csv") => Format::Csv,
+                    #[cfg(feature = "serde")]
+                    Some("json") => Format::Json,
+                    #[cfg(not(feature = "serde"))]
+                    Some("json") => fail("JSON support requires the serde feature"),
+                    _ => fail(&usage),
+                }
+            }
+            "--output" => output = Some(remaining.next().unwrap_or_else(|| fail(&usage))),
+            "--mappings" => mappings = Some(remaining.next().unwrap_or_else(|| fail(&usage))),
+            "--import" => import = Some(remaining.next().unwrap_or_else(|| fail(&usage))),
+            _ if file_path.is_none() => file_path = Some(arg),
+            _ => fail(&usage),
+        }
     }
 
-    let file_path = &args[1];
-    let file = File::open(&file_path).unwrap();
-    let mut parser = UassetParser::new(file);
+    let Some(file_path) = file_path else {
+        fail(&usage);
+    };
+
+    let mut parser = UassetParser::open(file_path).expect("Failed to open the package");
+    if let Some(mappings) = mappings {
+        let usmap = Usmap::from_file(mappings).expect("Failed to read mappings");
+        parser.mappings = Some(Arc::new(usmap));
+    }
     parser.parse_asset().expect("managed to parse the asset");
 
-    parser
-        .reader
-        .seek(std::io::SeekFrom::Start(
-            parser.entries[1].serial_offset as u64,
-        ))
-        .expect("failed to seek");
+    let data_table_index = parser
+        .find_data_table()
+        .unwrap_or_else(|| fail("No DataTable export found in the package"));
+    let mut data_table = parser
+        .read_export(data_table_index)
+        .expect("Failed to parse data table")
+        .into_data_table()
+        .unwrap_or_else(|| fail("Failed to decode the DataTable, is it missing --mappings?"));
 
-    let data_table: DataTable = parser.read().expect("Failed to parse data table");
+    if let Some(import) = import {
+        let Some(output) = output else {
+            fail(&usage);
+        };
 
-    println!("{:#?}", data_table.rows);
+        let rows = File::open(import).expect("Failed to open the rows to import");
+        if Path::new(import)
+            .extension()
+            .is_some_and(|ext| ext == "json")
+        {
+            #[cfg(feature = "serde")]
+            data_table
+                .import_json(rows)
+                .expect("Failed to import the rows");
+            #[cfg(not(feature = "serde"))]
+            fail("JSON support requires the serde feature");
+        } else {
+            data_table
+                .import_csv(rows)
+                .expect("Failed to import the rows");
+        }
+
+        let mut export_data: Vec<Vec<u8>> = (0..parser.entries.len())
+            .map(|i| parser.read_export_data(i))
+            .collect::<Result<_, _>>()
+            .expect("Failed to read the exports");
+
+        let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+        writer
+            .write(&data_table)
+            .expect("Failed to write the data table");
+        export_data[data_table_index] = writer.writer.get_ref().clone();
+
+        writer.writer = Cursor::new(vec![]);
+        writer
+            .write_package(&mut parser, &export_data)
+            .expect("Failed to write the package");
+        fs::write(output, writer.into_inner().into_inner()).expect("Failed to write the package");
+        return;
+    }
+
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path).expect("Failed to create output file")),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    match format {
+        Format::Csv => data_table.write_csv(&mut writer),
+        #[cfg(feature = "serde")]
+        Format::Json => data_table.write_json(&mut writer),
+    }
+    .and_then(|_| writer.flush())
+    .expect("Failed to write the export");
 }
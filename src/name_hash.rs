@@ -0,0 +1,93 @@
+/// Table of the deprecated CRC the case insensitive hash is based on, most
+/// significant bit first.
+const CRC_TABLE_DEPRECATED: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80000000 != 0 {
+                (crc << 1) ^ 0x04C11DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Table of the standard reflected CRC32.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Case insensitive and case preserving hashes stored after each entry of the
+/// name table. The engine doesn't use them when loading, they're written so
+/// saved packages match the editor's.
+pub fn name_hashes(name: &str) -> (u16, u16) {
+    if name.is_ascii() {
+        let upper = name.bytes().map(|b| b.to_ascii_uppercase() as u32);
+        (
+            (strihash(upper, 1) & 0xFFFF) as u16,
+            (str_crc32(name.bytes().map(u32::from)) & 0xFFFF) as u16,
+        )
+    } else {
+        // Wide names hash every byte of their UTF-16 characters
+        let upper = name.encode_utf16().map(|unit| {
+            let upper = char::from_u32(unit as u32)
+                .map(|c| c.to_uppercase().collect::<Vec<_>>())
+                .unwrap_or_default();
+            match upper.as_slice() {
+                [c] if (*c as u32) <= 0xFFFF => *c as u32,
+                _ => unit as u32,
+            }
+        });
+        (
+            (strihash(upper, 2) & 0xFFFF) as u16,
+            (str_crc32(name.encode_utf16().map(u32::from)) & 0xFFFF) as u16,
+        )
+    }
+}
+
+fn strihash(chars: impl Iterator<Item = u32>, char_size: usize) -> u32 {
+    let mut hash = 0u32;
+    for c in chars {
+        for byte in 0..char_size {
+            let b = (c >> (byte * 8)) & 0xFF;
+            hash = ((hash >> 8) & 0x00FFFFFF) ^ CRC_TABLE_DEPRECATED[((hash ^ b) & 0xFF) as usize];
+        }
+    }
+    hash
+}
+
+/// CRC32 over characters widened to 32 bits.
+fn str_crc32(chars: impl Iterator<Item = u32>) -> u32 {
+    let mut crc = !0u32;
+    for c in chars {
+        for byte in 0..4 {
+            let b = (c >> (byte * 8)) & 0xFF;
+            crc = (crc >> 8) ^ CRC_TABLE[((crc ^ b) & 0xFF) as usize];
+        }
+    }
+    !crc
+}
@@ -0,0 +1,66 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use serde::Serialize;
+
+use crate::data::uclass::UClassData;
+use crate::errors::Result;
+use crate::export_entry::ExportEntry;
+use crate::import_entry::ImportEntry;
+use crate::property_data::PropertyData;
+use crate::struct_value::SoftObjectPath;
+use crate::uasset_parser::UassetParser;
+use crate::uasset_summary::UassetSummary;
+
+/// Everything parsed from a package, for feeding into other tools as JSON or
+/// any other serde format.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageDump {
+    pub summary: UassetSummary,
+    pub names: Vec<String>,
+    pub imports: Vec<ImportEntry>,
+    pub exports: Vec<ExportDump>,
+    pub soft_object_paths: Vec<SoftObjectPath>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportDump {
+    #[serde(flatten)]
+    pub entry: ExportEntry,
+    pub class_path: Option<String>,
+    /// Tagged properties at the start of the export data
+    pub properties: Vec<PropertyData>,
+    /// Why the properties couldn't be read, e.g. missing mappings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Dumps the package parsed by [`parse_asset`](Self::parse_asset) along
+    /// with the properties of its exports. Exports whose properties fail to
+    /// parse are dumped without them.
+    pub fn dump_package(&mut self) -> Result<PackageDump> {
+        let mut exports = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.clone() {
+            self.reader
+                .seek(SeekFrom::Start(entry.serial_offset as u64))?;
+            let (properties, error) = match self.read::<UClassData>() {
+                Ok(class_data) => (class_data.properties, None),
+                Err(error) => (vec![], Some(error.to_string())),
+            };
+            exports.push(ExportDump {
+                class_path: self.object_path(entry.class_index),
+                entry,
+                properties,
+                error,
+            });
+        }
+
+        Ok(PackageDump {
+            summary: self.get_summary().clone(),
+            names: self.names.clone(),
+            imports: self.imports.clone(),
+            exports,
+            soft_object_paths: self.soft_object_paths.clone(),
+        })
+    }
+}
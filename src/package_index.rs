@@ -0,0 +1,90 @@
+use crate::errors::Result;
+use crate::export_entry::ExportEntry;
+use crate::fname::FName;
+use crate::import_entry::ImportEntry;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
+
+/// Reference to an object of the package: negative values point into the
+/// import table, positive values into the export table and zero is null.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct PackageIndex {
+    pub index: i32,
+}
+
+pub enum ResolvedObject<'a> {
+    Import(&'a ImportEntry),
+    Export(&'a ExportEntry),
+}
+
+impl ResolvedObject<'_> {
+    pub fn object_name(&self) -> &FName {
+        match self {
+            ResolvedObject::Import(import) => &import.object_name,
+            ResolvedObject::Export(export) => &export.object_name,
+        }
+    }
+}
+
+impl PackageIndex {
+    pub fn new(index: i32) -> Self {
+        PackageIndex { index }
+    }
+
+    pub fn from_import(import_index: usize) -> Self {
+        PackageIndex {
+            index: -(import_index as i32) - 1,
+        }
+    }
+
+    pub fn from_export(export_index: usize) -> Self {
+        PackageIndex {
+            index: export_index as i32 + 1,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.index == 0
+    }
+
+    pub fn is_import(&self) -> bool {
+        self.index < 0
+    }
+
+    pub fn is_export(&self) -> bool {
+        self.index > 0
+    }
+
+    pub fn import_index(&self) -> Option<usize> {
+        if self.is_import() {
+            Some((-self.index - 1) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn export_index(&self) -> Option<usize> {
+        if self.is_export() {
+            Some((self.index - 1) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl<R: Read + Seek> Parsable<PackageIndex> for UassetParser<R> {
+    fn parse(&mut self) -> Result<PackageIndex> {
+        let index = self.reader.read_i32::<LittleEndian>()?;
+        Ok(PackageIndex { index })
+    }
+}
+
+impl<W: Write + Seek> Writable<PackageIndex> for UassetWriter<W> {
+    fn serialize(&mut self, value: &PackageIndex) -> Result<()> {
+        self.writer.write_i32::<LittleEndian>(value.index)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::errors::Result;
+use crate::uasset_parser::UassetParser;
+
+/// Reader over the files of a package. Cooked packages split the exports off
+/// the header into a `.uexp` file, the two are read as one stream so export
+/// offsets point at the right data. Bulk data files are kept alongside.
+pub struct PackageReader<R: Read + Seek> {
+    header: R,
+    exports: Option<R>,
+    header_size: u64,
+    exports_size: u64,
+    position: u64,
+    /// Whether the reader under `position` is positioned there
+    synced: bool,
+    /// `.ubulk` file holding the bulk data stored outside the package
+    pub bulk: Option<R>,
+    /// `.uptnl` file holding the optional bulk data
+    pub optional_bulk: Option<R>,
+}
+
+fn stream_len<R: Seek>(reader: &mut R) -> io::Result<u64> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(len)
+}
+
+impl<R: Read + Seek> PackageReader<R> {
+    /// Package stored in a single file.
+    pub fn new(mut package: R) -> io::Result<Self> {
+        let header_size = stream_len(&mut package)?;
+        Ok(PackageReader {
+            header: package,
+            exports: None,
+            header_size,
+            exports_size: 0,
+            position: 0,
+            synced: true,
+            bulk: None,
+            optional_bulk: None,
+        })
+    }
+
+    /// Cooked package, the `.uexp` data continues where the header ends.
+    pub fn split(mut header: R, mut exports: R) -> io::Result<Self> {
+        let header_size = stream_len(&mut header)?;
+        let exports_size = stream_len(&mut exports)?;
+        Ok(PackageReader {
+            header,
+            exports: Some(exports),
+            header_size,
+            exports_size,
+            position: 0,
+            synced: true,
+            bulk: None,
+            optional_bulk: None,
+        })
+    }
+
+    pub fn with_bulk(mut self, bulk: R) -> Self {
+        self.bulk = Some(bulk);
+        self
+    }
+
+    pub fn with_optional_bulk(mut self, optional_bulk: R) -> Self {
+        self.optional_bulk = Some(optional_bulk);
+        self
+    }
+
+    pub fn is_split(&self) -> bool {
+        self.exports.is_some()
+    }
+
+    /// Reads `size` bytes of the `.ubulk` file at `offset`.
+    pub fn read_bulk_data(&mut self, offset: u64, size: usize) -> io::Result<Vec<u8>> {
+        Self::read_at(self.bulk.as_mut(), offset, size)
+    }
+
+    /// Reads `size` bytes of the `.uptnl` file at `offset`.
+    pub fn read_optional_bulk_data(&mut self, offset: u64, size: usize) -> io::Result<Vec<u8>> {
+        Self::read_at(self.optional_bulk.as_mut(), offset, size)
+    }
+
+    fn read_at(reader: Option<&mut R>, offset: u64, size: usize) -> io::Result<Vec<u8>> {
+        let reader = reader.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "bulk data file not attached")
+        })?;
+        let mut data = vec![0u8; size];
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl<R: Read + Seek> Read for PackageReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (reader, offset, remaining) = if self.position < self.header_size {
+            (
+                &mut self.header,
+                self.position,
+                self.header_size - self.position,
+            )
+        } else if let Some(exports) = self.exports.as_mut() {
+            let offset = self.position - self.header_size;
+            (exports, offset, self.exports_size.saturating_sub(offset))
+        } else {
+            return Ok(0);
+        };
+
+        if !self.synced {
+            reader.seek(SeekFrom::Start(offset))?;
+            self.synced = true;
+        }
+        let len = buf.len().min(remaining.try_into().unwrap_or(usize::MAX));
+        let read = reader.read(&mut buf[..len])?;
+
+        self.position += read as u64;
+        // Moving on to the exports file, which has to be positioned at its start
+        if self.position == self.header_size {
+            self.synced = false;
+        }
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for PackageReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let len = self.header_size + self.exports_size;
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )
+        })?;
+
+        if position != self.position {
+            self.position = position;
+            self.synced = false;
+        }
+        Ok(position)
+    }
+}
+
+impl UassetParser<PackageReader<File>> {
+    /// Opens the `.uasset` at `path` together with the `.uexp`, `.ubulk` and
+    /// `.uptnl` files next to it when they exist. The package still needs
+    /// [`parse_asset`](Self::parse_asset).
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let header = File::open(path)?;
+        // Only a missing file means the package doesn't have it
+        let open_companion = |extension: &str| match File::open(path.with_extension(extension)) {
+            Ok(file) => Ok(Some(file)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        };
+
+        let mut reader = match open_companion("uexp")? {
+            Some(exports) => PackageReader::split(header, exports)?,
+            None => PackageReader::new(header)?,
+        };
+        reader.bulk = open_companion("ubulk")?;
+        reader.optional_bulk = open_companion("uptnl")?;
+
+        Ok(UassetParser::new(reader))
+    }
+}
@@ -0,0 +1,640 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use aes::Aes256;
+use aes::cipher::{BlockDecrypt, KeyInit, generic_array::GenericArray};
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::errors::{ParseError, Result};
+use crate::package_reader::PackageReader;
+use crate::uasset_parser::UassetParser;
+
+const PAK_MAGIC: u32 = 0x5A6F12E1;
+const COMPRESSION_METHOD_NAME_LEN: usize = 32;
+pub(crate) const AES_BLOCK_SIZE: usize = 16;
+const MAX_INDEX_ENTRIES: i32 = 10_000_000;
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EPakFileVersion {
+    Initial = 1,
+    NoTimestamps,
+    CompressionEncryption,
+    IndexEncryption,
+    RelativeChunkOffsets,
+    DeleteRecords,
+    EncryptionKeyGuid,
+    FNameBasedCompressionMethod,
+    FrozenIndex,
+    PathHashIndex,
+    Fnv64BugFix,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PakCompressedBlock {
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Location and encoding of a file in the pak.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PakEntry {
+    /// Offset of the entry header that precedes the data
+    pub offset: i64,
+    pub size: i64,
+    pub uncompressed_size: i64,
+    /// Zero for uncompressed entries, otherwise one past the index of the
+    /// method in [`PakFile::compression_methods`]
+    pub compression_method: u32,
+    /// Blocks of compressed data, relative to `offset`
+    pub compression_blocks: Vec<PakCompressedBlock>,
+    pub compression_block_size: u32,
+    pub encrypted: bool,
+}
+
+/// Footer at the end of every pak file.
+#[derive(Debug, Clone)]
+struct PakInfo {
+    version: i32,
+    encrypted_index: bool,
+    index_offset: i64,
+    index_size: i64,
+    compression_methods: Vec<String>,
+}
+
+/// Archive of files as shipped by the engine. Files are looked up by their
+/// path below the mount point, e.g. `Content/Data/DT_Items.uasset`.
+pub struct PakFile<R: Read + Seek> {
+    reader: R,
+    file_size: u64,
+    key: Option<[u8; 32]>,
+    pub version: i32,
+    pub mount_point: String,
+    pub compression_methods: Vec<String>,
+    entries: BTreeMap<String, PakEntry>,
+    /// Entries by path hash, for paks saved without the full directory index
+    path_hashes: HashMap<u64, PakEntry>,
+    path_hash_seed: u64,
+}
+
+impl PakFile<BufReader<File>> {
+    pub fn from_file(path: impl AsRef<Path>, key: Option<[u8; 32]>) -> Result<Self> {
+        PakFile::from_reader(BufReader::new(File::open(path)?), key)
+    }
+}
+
+impl<R: Read + Seek> PakFile<R> {
+    /// Reads the index of the pak, `key` is needed when the index or any of
+    /// the files are encrypted.
+    pub fn from_reader(mut reader: R, key: Option<[u8; 32]>) -> Result<Self> {
+        let info = read_pak_info(&mut reader)?;
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let mut pak = PakFile {
+            reader,
+            file_size,
+            key,
+            version: info.version,
+            mount_point: String::new(),
+            compression_methods: info.compression_methods,
+            entries: BTreeMap::new(),
+            path_hashes: HashMap::new(),
+            path_hash_seed: 0,
+        };
+
+        let index =
+            pak.read_index_data(info.index_offset, info.index_size, info.encrypted_index)?;
+        if info.version >= EPakFileVersion::PathHashIndex as i32 {
+            pak.read_index(index, info.encrypted_index)?;
+        } else {
+            pak.read_legacy_index(index)?;
+        }
+        Ok(pak)
+    }
+
+    /// Paths of the files below the mount point, empty for paks that only
+    /// store path hashes.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Entry of `path`, either below the mount point or including it.
+    pub fn entry(&self, path: &str) -> Option<&PakEntry> {
+        let path = path.strip_prefix(&self.mount_point).unwrap_or(path);
+        self.entries.get(path).or_else(|| {
+            // Hashes of older versions used a buggy FNV implementation
+            if self.version < EPakFileVersion::Fnv64BugFix as i32 {
+                return None;
+            }
+            self.path_hashes.get(&path_hash(path, self.path_hash_seed))
+        })
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.entry(path).is_some()
+    }
+
+    /// Reads, decrypts and decompresses the file at `path`.
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        let entry = self
+            .entry(path)
+            .cloned()
+            .ok_or_else(|| ParseError::PakEntryNotFound(path.to_string()))?;
+        self.read_entry(&entry)
+    }
+
+    /// The file at `path` as a stream that can be handed to [`UassetParser::new`].
+    pub fn open_file(&mut self, path: &str) -> Result<Cursor<Vec<u8>>> {
+        self.read_file(path).map(Cursor::new)
+    }
+
+    /// Opens the package at `path` (a `.uasset` or `.umap`) together with
+    /// the `.uexp`, `.ubulk` and `.uptnl` files the cooker split off it.
+    pub fn open_package(
+        &mut self,
+        path: &str,
+    ) -> Result<UassetParser<PackageReader<Cursor<Vec<u8>>>>> {
+        let header = self.open_file(path)?;
+        let companion = |extension: &str| {
+            Path::new(path)
+                .with_extension(extension)
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let mut reader = match self.open_companion(&companion("uexp"))? {
+            Some(exports) => PackageReader::split(header, exports)?,
+            None => PackageReader::new(header)?,
+        };
+        reader.bulk = self.open_companion(&companion("ubulk"))?;
+        reader.optional_bulk = self.open_companion(&companion("uptnl"))?;
+        Ok(UassetParser::new(reader))
+    }
+
+    fn open_companion(&mut self, path: &str) -> Result<Option<Cursor<Vec<u8>>>> {
+        if self.contains(path) {
+            self.open_file(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn read_entry(&mut self, entry: &PakEntry) -> Result<Vec<u8>> {
+        let header_size = serialized_entry_size(entry);
+
+        if entry.compression_method == 0 {
+            return self.read_data(
+                (entry.offset + header_size) as u64,
+                entry.size as usize,
+                entry.encrypted,
+            );
+        }
+
+        let method = self
+            .compression_methods
+            .get(entry.compression_method as usize - 1)
+            .cloned()
+            .unwrap_or_else(|| entry.compression_method.to_string());
+
+        // Block offsets became relative to the entry with relative chunk offsets
+        let base = if self.version >= EPakFileVersion::RelativeChunkOffsets as i32 {
+            entry.offset
+        } else {
+            0
+        };
+
+        if entry.uncompressed_size < 0 {
+            return Err(ParseError::DecompressionFailed(format!(
+                "invalid uncompressed size {}",
+                entry.uncompressed_size
+            )));
+        }
+        // The size comes from the index, only trust it as far as the pak goes
+        let mut data =
+            Vec::with_capacity((entry.uncompressed_size as u64).min(self.file_size) as usize);
+        for block in &entry.compression_blocks {
+            let compressed = self.read_data(
+                (base + block.start) as u64,
+                (block.end - block.start) as usize,
+                entry.encrypted,
+            )?;
+            decompress(&method, &compressed, &mut data)?;
+            if data.len() as i64 > entry.uncompressed_size {
+                break;
+            }
+        }
+
+        if data.len() as i64 != entry.uncompressed_size {
+            return Err(ParseError::DecompressionFailed(format!(
+                "expected {} bytes, got {}",
+                entry.uncompressed_size,
+                data.len()
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Reads `size` bytes at `offset`, encrypted data is padded to the AES block size.
+    fn read_data(&mut self, offset: u64, size: usize, encrypted: bool) -> Result<Vec<u8>> {
+        let padded_size = if encrypted {
+            size.checked_next_multiple_of(AES_BLOCK_SIZE)
+        } else {
+            Some(size)
+        };
+        let Some(padded_size) = padded_size
+            .filter(|&padded_size| offset.checked_add(padded_size as u64) <= Some(self.file_size))
+        else {
+            return Err(ParseError::InvalidPakFooter);
+        };
+
+        let mut data = vec![0u8; padded_size];
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(&mut data)?;
+
+        if encrypted {
+            decrypt(self.key.as_ref(), &mut data)?;
+            data.truncate(size);
+        }
+        Ok(data)
+    }
+
+    fn read_index_data(
+        &mut self,
+        offset: i64,
+        size: i64,
+        encrypted: bool,
+    ) -> Result<Cursor<Vec<u8>>> {
+        if offset < 0 || size < 0 {
+            return Err(ParseError::InvalidPakFooter);
+        }
+        self.read_data(offset as u64, size as usize, encrypted)
+            .map(Cursor::new)
+    }
+
+    /// Index since path hash indices: entries are bit packed and listed by the
+    /// directory index or the path hash index, kept in separate blocks.
+    fn read_index(&mut self, mut index: Cursor<Vec<u8>>, encrypted: bool) -> Result<()> {
+        self.mount_point = read_fstring(&mut index)?;
+        let _entry_count = index.read_i32::<LittleEndian>()?;
+        self.path_hash_seed = index.read_u64::<LittleEndian>()?;
+
+        let path_hash_index = read_index_location(&mut index)?;
+        let directory_index = read_index_location(&mut index)?;
+
+        let encoded_size = read_count(&mut index)?;
+        if encoded_size > remaining(&index) {
+            return Err(ParseError::InvalidPakFooter);
+        }
+        let mut encoded_entries = vec![0u8; encoded_size];
+        index.read_exact(&mut encoded_entries)?;
+
+        // Full entries take more than a byte each
+        let file_count = read_count(&mut index)?;
+        if file_count > remaining(&index) {
+            return Err(ParseError::InvalidPakFooter);
+        }
+        let mut files = Vec::with_capacity(file_count);
+        for _ in 0..file_count {
+            files.push(read_entry(&mut index, self.version)?);
+        }
+
+        let entry_at = |location: i32| -> Result<Option<PakEntry>> {
+            if location == i32::MIN {
+                Ok(None)
+            } else if location >= 0 {
+                decode_entry(&encoded_entries, location as usize).map(Some)
+            } else {
+                Ok(files.get((-(location as i64) - 1) as usize).cloned())
+            }
+        };
+
+        if let Some((offset, size)) = directory_index {
+            let mut directories = self.read_index_data(offset, size, encrypted)?;
+            for _ in 0..read_count(&mut directories)? {
+                let directory = read_fstring(&mut directories)?;
+                // The root directory is `/`, the others end with a slash
+                let directory = directory
+                    .strip_prefix('/')
+                    .unwrap_or(&directory)
+                    .to_string();
+                for _ in 0..read_count(&mut directories)? {
+                    let file_name = read_fstring(&mut directories)?;
+                    let location = directories.read_i32::<LittleEndian>()?;
+                    if let Some(entry) = entry_at(location)? {
+                        self.entries
+                            .insert(format!("{}{}", directory, file_name), entry);
+                    }
+                }
+            }
+        } else if let Some((offset, size)) = path_hash_index {
+            let mut hashes = self.read_index_data(offset, size, encrypted)?;
+            for _ in 0..read_count(&mut hashes)? {
+                let hash = hashes.read_u64::<LittleEndian>()?;
+                let location = hashes.read_i32::<LittleEndian>()?;
+                if let Some(entry) = entry_at(location)? {
+                    self.path_hashes.insert(hash, entry);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Index before path hash indices, a list of file names and entries.
+    fn read_legacy_index(&mut self, mut index: Cursor<Vec<u8>>) -> Result<()> {
+        self.mount_point = read_fstring(&mut index)?;
+        for _ in 0..read_count(&mut index)? {
+            let file_name = read_fstring(&mut index)?;
+            let entry = read_entry(&mut index, self.version)?;
+            self.entries.insert(file_name, entry);
+        }
+        Ok(())
+    }
+}
+
+/// Parses a key given as 64 hex digits, optionally prefixed with `0x`, or
+/// as base64 like in the project's crypto settings.
+pub fn parse_key(text: &str) -> Result<[u8; 32]> {
+    let text = text.trim();
+    let hex = text.strip_prefix("0x").unwrap_or(text);
+    let bytes = if hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        (0..32)
+            .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16))
+            .collect::<std::result::Result<Vec<u8>, _>>()
+            .ok()
+    } else {
+        decode_base64(text)
+    };
+
+    bytes
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| ParseError::InvalidAesKey(text.to_string()))
+}
+
+fn read_pak_info<R: Read + Seek>(reader: &mut R) -> Result<PakInfo> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+
+    // The footer grew over the versions: 5 compression method names since
+    // 4.23, with an extra frozen index flag for version 9 only, 4 before that
+    for (footer_size, method_count) in [(221u64, 5usize), (222, 5), (189, 4)] {
+        if file_size < footer_size {
+            continue;
+        }
+        reader.seek(SeekFrom::Start(file_size - footer_size))?;
+
+        let mut encryption_key_guid = [0u8; 16];
+        reader.read_exact(&mut encryption_key_guid)?;
+        let encrypted_index = reader.read_u8()? != 0;
+        if reader.read_u32::<LittleEndian>()? != PAK_MAGIC {
+            continue;
+        }
+
+        let version = reader.read_i32::<LittleEndian>()?;
+        let expected_size = match version {
+            v if v == EPakFileVersion::FrozenIndex as i32 => 222,
+            v if v == EPakFileVersion::FNameBasedCompressionMethod as i32 => footer_size,
+            _ => 221,
+        };
+        if version < EPakFileVersion::FNameBasedCompressionMethod as i32
+            || version > EPakFileVersion::Fnv64BugFix as i32
+        {
+            return Err(ParseError::UnsupportedPakVersion(version));
+        }
+        if expected_size != footer_size {
+            continue;
+        }
+
+        let index_offset = reader.read_i64::<LittleEndian>()?;
+        let index_size = reader.read_i64::<LittleEndian>()?;
+        let mut index_hash = [0u8; 20];
+        reader.read_exact(&mut index_hash)?;
+
+        if version == EPakFileVersion::FrozenIndex as i32 && reader.read_u8()? != 0 {
+            // Frozen indices are memory images of the engine's structures
+            return Err(ParseError::UnsupportedPakVersion(version));
+        }
+
+        let mut compression_methods = vec![];
+        for _ in 0..method_count {
+            let mut name = [0u8; COMPRESSION_METHOD_NAME_LEN];
+            reader.read_exact(&mut name)?;
+            let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            compression_methods.push(String::from_utf8_lossy(&name[..len]).into_owned());
+        }
+        while compression_methods.last().is_some_and(String::is_empty) {
+            compression_methods.pop();
+        }
+
+        return Ok(PakInfo {
+            version,
+            encrypted_index,
+            index_offset,
+            index_size,
+            compression_methods,
+        });
+    }
+
+    Err(ParseError::InvalidPakFooter)
+}
+
+pub(crate) fn read_count(reader: &mut Cursor<Vec<u8>>) -> Result<usize> {
+    let count = reader.read_i32::<LittleEndian>()?;
+    if !(0..=MAX_INDEX_ENTRIES).contains(&count) {
+        return Err(ParseError::InvalidArraySize(count));
+    }
+    Ok(count as usize)
+}
+
+/// Bytes left to read in an index block.
+pub(crate) fn remaining(reader: &Cursor<Vec<u8>>) -> usize {
+    reader
+        .get_ref()
+        .len()
+        .saturating_sub(reader.position() as usize)
+}
+
+pub(crate) fn read_fstring(reader: &mut Cursor<Vec<u8>>) -> Result<String> {
+    let size = reader.read_i32::<LittleEndian>()?;
+    if size == 0 {
+        return Ok(String::new());
+    }
+    if size < 0 {
+        let mut units = vec![0u16; size.unsigned_abs() as usize];
+        reader.read_u16_into::<LittleEndian>(&mut units)?;
+        units.pop();
+        return String::from_utf16(&units).map_err(|_| ParseError::InvalidUtf16);
+    }
+
+    let mut buffer = vec![0u8; size as usize];
+    reader.read_exact(&mut buffer)?;
+    buffer.pop();
+    Ok(String::from_utf8(buffer)?)
+}
+
+/// Offset and size of a secondary index block, if the pak has it.
+fn read_index_location(reader: &mut Cursor<Vec<u8>>) -> Result<Option<(i64, i64)>> {
+    if reader.read_u32::<LittleEndian>()? == 0 {
+        return Ok(None);
+    }
+    let offset = reader.read_i64::<LittleEndian>()?;
+    let size = reader.read_i64::<LittleEndian>()?;
+    let mut hash = [0u8; 20];
+    reader.read_exact(&mut hash)?;
+    Ok(Some((offset, size)))
+}
+
+/// Entry as serialized in full, in legacy indices and before the data of
+/// every file.
+fn read_entry(reader: &mut Cursor<Vec<u8>>, version: i32) -> Result<PakEntry> {
+    let mut entry = PakEntry {
+        offset: reader.read_i64::<LittleEndian>()?,
+        size: reader.read_i64::<LittleEndian>()?,
+        uncompressed_size: reader.read_i64::<LittleEndian>()?,
+        compression_method: reader.read_u32::<LittleEndian>()?,
+        ..Default::default()
+    };
+    if version < EPakFileVersion::NoTimestamps as i32 {
+        reader.read_i64::<LittleEndian>()?;
+    }
+    let mut hash = [0u8; 20];
+    reader.read_exact(&mut hash)?;
+
+    if entry.compression_method != 0 {
+        for _ in 0..read_count(reader)? {
+            entry.compression_blocks.push(PakCompressedBlock {
+                start: reader.read_i64::<LittleEndian>()?,
+                end: reader.read_i64::<LittleEndian>()?,
+            });
+        }
+    }
+    entry.encrypted = reader.read_u8()? & 0x01 != 0;
+    entry.compression_block_size = reader.read_u32::<LittleEndian>()?;
+    Ok(entry)
+}
+
+/// Size of the full entry that precedes the data of a file.
+fn serialized_entry_size(entry: &PakEntry) -> i64 {
+    // Offset, sizes, compression method, hash, flags and block size
+    let mut size = 8 + 8 + 8 + 4 + 20 + 1 + 4;
+    if entry.compression_method != 0 {
+        size += 4 + 16 * entry.compression_blocks.len() as i64;
+    }
+    size
+}
+
+/// Decodes a bit packed entry: a u32 of flags and counts followed by the
+/// sizes, each stored in 32 bits when they fit.
+fn decode_entry(encoded: &[u8], offset: usize) -> Result<PakEntry> {
+    let mut reader = Cursor::new(encoded.get(offset..).ok_or(ParseError::InvalidPakFooter)?);
+    let value = reader.read_u32::<LittleEndian>()?;
+
+    let compression_block_size = if value & 0x3f == 0x3f {
+        reader.read_u32::<LittleEndian>()?
+    } else {
+        (value & 0x3f) << 11
+    };
+    let compression_method = (value >> 23) & 0x3f;
+    let mut read_size = |fits_32_bits: bool| -> Result<i64> {
+        Ok(if fits_32_bits {
+            reader.read_u32::<LittleEndian>()? as i64
+        } else {
+            reader.read_i64::<LittleEndian>()?
+        })
+    };
+
+    let offset = read_size(value & (1 << 31) != 0)?;
+    let uncompressed_size = read_size(value & (1 << 30) != 0)?;
+    let size = if compression_method != 0 {
+        read_size(value & (1 << 29) != 0)?
+    } else {
+        uncompressed_size
+    };
+    let encrypted = value & (1 << 22) != 0;
+    let block_count = (value >> 6) & 0xffff;
+
+    let mut entry = PakEntry {
+        offset,
+        size,
+        uncompressed_size,
+        compression_method,
+        compression_blocks: vec![PakCompressedBlock::default(); block_count as usize],
+        compression_block_size,
+        encrypted,
+    };
+    // Blocks follow the full entry written before the data
+    let mut block_start = serialized_entry_size(&entry);
+
+    if block_count == 1 && !encrypted {
+        entry.compression_blocks[0] = PakCompressedBlock {
+            start: block_start,
+            end: block_start + size,
+        };
+    } else {
+        let alignment = if encrypted { AES_BLOCK_SIZE as i64 } else { 1 };
+        for block in entry.compression_blocks.iter_mut() {
+            let block_size = reader.read_u32::<LittleEndian>()? as i64;
+            *block = PakCompressedBlock {
+                start: block_start,
+                end: block_start + block_size,
+            };
+            block_start += (block_size + alignment - 1) / alignment * alignment;
+        }
+    }
+
+    Ok(entry)
+}
+
+/// Decrypts AES-256 ECB encrypted data in place, `data` is a multiple of the block size.
+pub(crate) fn decrypt(key: Option<&[u8; 32]>, data: &mut [u8]) -> Result<()> {
+    let key = key.ok_or(ParseError::MissingAesKey)?;
+    let cipher = Aes256::new(&GenericArray::from(*key));
+    for block in data.chunks_exact_mut(AES_BLOCK_SIZE) {
+        cipher.decrypt_block(GenericArray::from_mut_slice(block));
+    }
+    Ok(())
+}
+
+pub(crate) fn decompress(method: &str, compressed: &[u8], data: &mut Vec<u8>) -> Result<()> {
+    let result = match method.to_ascii_lowercase().as_str() {
+        "zlib" => flate2::read::ZlibDecoder::new(compressed).read_to_end(data),
+        "gzip" => flate2::read::GzDecoder::new(compressed).read_to_end(data),
+        _ => return Err(ParseError::UnsupportedCompressionMethod(method.to_string())),
+    };
+    result.map_err(|e| ParseError::DecompressionFailed(e.to_string()))?;
+    Ok(())
+}
+
+/// FNV-1a over the lowercase UTF-16 path, with the seed added to the offset basis.
+fn path_hash(path: &str, seed: u64) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64.wrapping_add(seed);
+    for unit in path.to_lowercase().encode_utf16() {
+        for byte in unit.to_le_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    let mut bytes = vec![];
+    for c in text.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Some(bytes)
+}
@@ -0,0 +1,120 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+use crate::errors::Result;
+use crate::uasset_parser::UassetParser;
+
+/// Value decoded by a parser registered with a [`ParserRegistry`], get the
+/// concrete type back with `downcast_ref`.
+pub trait CustomValue: Any + fmt::Debug + Send + Sync {
+    /// Text for exports such as CSV, empty by default.
+    fn as_string(&self) -> String {
+        String::new()
+    }
+}
+
+impl dyn CustomValue {
+    pub fn downcast_ref<T: CustomValue>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+}
+
+/// Result of a registered parser with the bytes it consumed, which are what
+/// gets written back when the package is saved.
+#[derive(Debug, Clone)]
+pub struct PluginValue {
+    pub value: Arc<dyn CustomValue>,
+    pub data: Vec<u8>,
+}
+
+impl PartialEq for PluginValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PluginValue {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.value.as_string())
+    }
+}
+
+/// Parser called with the reader at the start of the data, it must leave the
+/// reader at the end of what it decoded.
+pub type CustomParser<R> =
+    Arc<dyn Fn(&mut UassetParser<R>) -> Result<Arc<dyn CustomValue>> + Send + Sync>;
+
+/// Game specific parsers for data the crate can't decode on its own: structs
+/// with a native serializer and classes that override `Serialize`.
+pub struct ParserRegistry<R: Read + Seek> {
+    structs: HashMap<String, CustomParser<R>>,
+    exports: HashMap<String, CustomParser<R>>,
+}
+
+impl<R: Read + Seek> Default for ParserRegistry<R> {
+    fn default() -> Self {
+        ParserRegistry {
+            structs: HashMap::new(),
+            exports: HashMap::new(),
+        }
+    }
+}
+
+impl<R: Read + Seek> ParserRegistry<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the values of struct `struct_name`, e.g. `MyNativeStruct`,
+    /// wherever a property holds one.
+    pub fn register_struct<F>(&mut self, struct_name: impl Into<String>, parser: F) -> &mut Self
+    where
+        F: Fn(&mut UassetParser<R>) -> Result<Arc<dyn CustomValue>> + Send + Sync + 'static,
+    {
+        self.structs.insert(struct_name.into(), Arc::new(parser));
+        self
+    }
+
+    /// Parses the exports of class `class_path`, e.g. `/Script/MyGame.MyActor`,
+    /// from the start of their data, properties included.
+    pub fn register_export<F>(&mut self, class_path: impl Into<String>, parser: F) -> &mut Self
+    where
+        F: Fn(&mut UassetParser<R>) -> Result<Arc<dyn CustomValue>> + Send + Sync + 'static,
+    {
+        self.exports.insert(class_path.into(), Arc::new(parser));
+        self
+    }
+
+    pub fn struct_parser(&self, struct_name: &str) -> Option<CustomParser<R>> {
+        self.structs.get(struct_name).cloned()
+    }
+
+    pub fn export_parser(&self, class_path: &str) -> Option<CustomParser<R>> {
+        self.exports.get(class_path).cloned()
+    }
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    pub(crate) fn registered_struct_parser(&self, struct_name: &str) -> Option<CustomParser<R>> {
+        self.registry.as_ref()?.struct_parser(struct_name)
+    }
+
+    /// Runs a registered parser and keeps the bytes it read.
+    pub(crate) fn read_plugin_value(&mut self, parser: CustomParser<R>) -> Result<PluginValue> {
+        let start = self.reader.stream_position()?;
+        let value = parser(self)?;
+        let end = self.reader.stream_position()?;
+
+        let mut data = vec![0u8; end.saturating_sub(start) as usize];
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.reader.read_exact(&mut data)?;
+        Ok(PluginValue { value, data })
+    }
+}
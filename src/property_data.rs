@@ -1,37 +1,680 @@
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom, Write};
 
-use crate::errors::Result;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::errors::{ParseError, Result};
+use crate::fname::FName;
+use crate::ftext::FText;
+use crate::guid::Guid;
+use crate::package_index::PackageIndex;
+use crate::property_tag::EPropertyTagFlags;
+use crate::property_type_name::PropertyTypeName;
+use crate::struct_value::{NATIVE_STRUCTS, SoftObjectPath, StructValue};
+use crate::uasset_writer::{UassetWriter, Writable};
+use crate::versions::EUnrealEngineObjectUE5Version;
 use crate::{
     property_tag::PropertyTag,
     uasset_parser::{Parsable, UassetParser},
 };
-use crate::fname::FName;
-use crate::property_data::PropertyValue::StrProperty;
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PropertyValue {
+    BoolProperty(bool),
+    Int8Property(i8),
+    Int16Property(i16),
+    IntProperty(i32),
+    Int64Property(i64),
+    UInt16Property(u16),
+    UInt32Property(u32),
+    UInt64Property(u64),
+    FloatProperty(f32),
+    DoubleProperty(f64),
+    ByteProperty(u8),
+    EnumProperty(FName),
+    NameProperty(FName),
     StrProperty(String),
-    Undefined
+    Utf8StrProperty(String),
+    AnsiStrProperty(String),
+    TextProperty(FText),
+    ObjectProperty(PackageIndex),
+    LazyObjectProperty(Guid),
+    SoftObjectProperty(SoftObjectPath),
+    DelegateProperty(ScriptDelegate),
+    MulticastDelegateProperty(Vec<ScriptDelegate>),
+    FieldPathProperty(FieldPath),
+    StructProperty(StructValue),
+    ArrayProperty(Vec<PropertyValue>),
+    SetProperty {
+        elements: Vec<PropertyValue>,
+        removed: Vec<PropertyValue>,
+    },
+    MapProperty {
+        entries: Vec<(PropertyValue, PropertyValue)>,
+        removed: Vec<PropertyValue>,
+    },
+    Undefined,
+}
+
+const MAX_CONTAINER_ELEMENTS: usize = 1_000_000;
+
+/// Function bound to a delegate, the object is null when nothing is bound.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScriptDelegate {
+    pub object: PackageIndex,
+    pub function_name: FName,
+}
+
+/// Path to a property, `path` holds the names of the property and its outer
+/// fields innermost first, `owner` is the struct or class declaring them.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldPath {
+    pub path: Vec<FName>,
+    pub owner: PackageIndex,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PropertyData {
     pub tag: PropertyTag,
     pub value: PropertyValue,
 }
 
+impl PropertyValue {
+    /// Text of the value, objects by package index.
+    pub fn as_string(&self) -> String {
+        self.text(&|index| index.index.to_string(), false)
+    }
+
+    /// Text of the value the way the editor exports it, objects written by
+    /// `object_text`, e.g. by path. Enum values lose their `EEnum::` prefix
+    /// and bools are `True` or `False`.
+    pub fn export_text(&self, object_text: &dyn Fn(PackageIndex) -> String) -> String {
+        self.text(object_text, true)
+    }
+
+    pub(crate) fn text(
+        &self,
+        object_text: &dyn Fn(PackageIndex) -> String,
+        editor: bool,
+    ) -> String {
+        match self {
+            PropertyValue::BoolProperty(true) if editor => "True".to_string(),
+            PropertyValue::BoolProperty(false) if editor => "False".to_string(),
+            PropertyValue::BoolProperty(value) => value.to_string(),
+            PropertyValue::Int8Property(value) => value.to_string(),
+            PropertyValue::Int16Property(value) => value.to_string(),
+            PropertyValue::IntProperty(value) => value.to_string(),
+            PropertyValue::Int64Property(value) => value.to_string(),
+            PropertyValue::UInt16Property(value) => value.to_string(),
+            PropertyValue::UInt32Property(value) => value.to_string(),
+            PropertyValue::UInt64Property(value) => value.to_string(),
+            PropertyValue::FloatProperty(value) => value.to_string(),
+            PropertyValue::DoubleProperty(value) => value.to_string(),
+            PropertyValue::ByteProperty(value) => value.to_string(),
+            PropertyValue::EnumProperty(value) => {
+                let value = value.as_string();
+                match value.split_once("::") {
+                    Some((_, name)) if editor => name.to_string(),
+                    _ => value,
+                }
+            }
+            PropertyValue::NameProperty(value) => value.as_string(),
+            PropertyValue::StrProperty(value)
+            | PropertyValue::Utf8StrProperty(value)
+            | PropertyValue::AnsiStrProperty(value) => value.clone(),
+            PropertyValue::TextProperty(value) => value.as_string(),
+            PropertyValue::ObjectProperty(value) => object_text(*value),
+            PropertyValue::LazyObjectProperty(value) => value.to_string(),
+            PropertyValue::SoftObjectProperty(value) => value.as_string(),
+            PropertyValue::DelegateProperty(value) => value.export_text(object_text),
+            PropertyValue::MulticastDelegateProperty(values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| value.export_text(object_text))
+                    .collect();
+                format!("({})", values.join(","))
+            }
+            PropertyValue::FieldPathProperty(value) => value.export_text(object_text),
+            PropertyValue::StructProperty(value) => value.text(object_text, editor),
+            PropertyValue::ArrayProperty(elements)
+            | PropertyValue::SetProperty { elements, .. } => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|e| e.text(object_text, editor))
+                    .collect();
+                format!("({})", elements.join(","))
+            }
+            PropertyValue::MapProperty { entries, .. } => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        format!(
+                            "({}, {})",
+                            key.text(object_text, editor),
+                            value.text(object_text, editor)
+                        )
+                    })
+                    .collect();
+                format!("({})", entries.join(","))
+            }
+            PropertyValue::Undefined => String::new(),
+        }
+    }
+}
+
+impl ScriptDelegate {
+    /// `Object.Function` like the editor writes it, `None` when unbound.
+    pub fn export_text(&self, object_text: &dyn Fn(PackageIndex) -> String) -> String {
+        if self.object.is_null() {
+            return "None".to_string();
+        }
+        format!(
+            "{}.{}",
+            object_text(self.object),
+            self.function_name.as_string()
+        )
+    }
+}
+
+impl FieldPath {
+    /// Owner followed by the field names outermost first, separated by `:`.
+    pub fn export_text(&self, object_text: &dyn Fn(PackageIndex) -> String) -> String {
+        if self.path.is_empty() {
+            return "None".to_string();
+        }
+        let names = self.path.iter().rev().map(FName::as_string);
+        if self.owner.is_null() {
+            return names.collect::<Vec<_>>().join(":");
+        }
+        std::iter::once(object_text(self.owner))
+            .chain(names)
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+impl<R: Read + Seek> Parsable<ScriptDelegate> for UassetParser<R> {
+    fn parse(&mut self) -> Result<ScriptDelegate> {
+        Ok(ScriptDelegate {
+            object: self.read()?,
+            function_name: self.read()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<FieldPath> for UassetParser<R> {
+    fn parse(&mut self) -> Result<FieldPath> {
+        // Packages of every supported version store the owner after the path
+        Ok(FieldPath {
+            path: self.read_tarray(|parser| parser.read(), MAX_CONTAINER_ELEMENTS)?,
+            owner: self.read()?,
+        })
+    }
+}
+
 impl<R: Read + Seek> Parsable<PropertyData> for UassetParser<R> {
     fn parse(&mut self) -> Result<PropertyData> {
-        let tag: PropertyTag = self.read()?;
-        let value: PropertyValue = if tag.type_name.name == "StrProperty" {
-            StrProperty(self.read_fstring()?)
-        } else if tag.type_name.name == "NameProperty" {
-            let fname: FName = self.read()?;
-            StrProperty(fname.as_string())
-        } else {
-            self.skip_bytes(tag.size as i64)?;
-            PropertyValue::Undefined
+        let mut tag: PropertyTag = self.read()?;
+        if tag.name.is_none() {
+            return Ok(PropertyData {
+                tag,
+                value: PropertyValue::Undefined,
+            });
+        }
+        if tag.size < 0 {
+            return Err(ParseError::InvalidPropertySize {
+                name: tag.name.as_string(),
+                size: tag.size,
+            });
+        }
+
+        let start = self.reader.stream_position()?;
+        if self.has_legacy_struct_array_tag(&tag.type_name)? {
+            // Only the inner tag names the struct, keep it in the type like newer tags do
+            self.reader.read_i32::<LittleEndian>()?;
+            let inner_tag: PropertyTag = self.read()?;
+            tag.type_name.parameters = vec![inner_tag.type_name];
+            self.reader.seek(SeekFrom::Start(start))?;
+        }
+
+        let end = start + tag.size as u64;
+        let has_struct = Self::has_struct(&tag.type_name);
+        let value = match self.read_tagged_value(&tag) {
+            Ok(value) if !has_struct || self.reader.stream_position()? == end => value,
+            Err(err) if !has_struct => return Err(err),
+            // Legacy tags only guess which structs serialize natively, keep
+            // the bytes of a struct that doesn't read as tagged properties
+            _ => {
+                self.reader.seek(SeekFrom::Start(start))?;
+                let mut buffer = vec![0; tag.size as usize];
+                self.reader.read_exact(&mut buffer)?;
+                PropertyValue::StructProperty(StructValue::Raw(buffer))
+            }
         };
 
+        // The tag size is authoritative, don't let a partially decoded value derail the stream
+        self.reader.seek(SeekFrom::Start(end))?;
 
         Ok(PropertyData { tag, value })
     }
 }
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Reads tagged properties up to the terminating `None` tag.
+    pub fn read_tagged_properties(&mut self) -> Result<Vec<PropertyData>> {
+        let mut properties = Vec::new();
+
+        loop {
+            let property: PropertyData = self.read()?;
+            if property.tag.name.is_none() {
+                break;
+            }
+            properties.push(property);
+        }
+
+        Ok(properties)
+    }
+
+    fn read_tagged_value(&mut self, tag: &PropertyTag) -> Result<PropertyValue> {
+        match tag.type_name.name.as_str() {
+            "BoolProperty" => Ok(PropertyValue::BoolProperty(
+                tag.flags & (EPropertyTagFlags::BoolTrue as u8) != 0,
+            )),
+            "ByteProperty" if tag.size == 1 => {
+                Ok(PropertyValue::ByteProperty(self.reader.read_u8()?))
+            }
+            "StructProperty" => {
+                let struct_name =
+                    Self::required_parameter(&tag.type_name, tag.type_name.struct_name())?;
+                if let Some(parser) = self.registered_struct_parser(struct_name) {
+                    return Ok(PropertyValue::StructProperty(StructValue::Custom(
+                        self.read_plugin_value(parser)?,
+                    )));
+                }
+                if tag.flags & (EPropertyTagFlags::HasBinaryOrNativeSerialize as u8) == 0 {
+                    return Ok(PropertyValue::StructProperty(StructValue::Properties(
+                        self.read_tagged_properties()?,
+                    )));
+                }
+
+                match self.read_native_struct(struct_name)? {
+                    Some(value) => Ok(PropertyValue::StructProperty(value)),
+                    None => {
+                        let mut buffer = vec![0u8; tag.size as usize];
+                        self.reader.read_exact(&mut buffer)?;
+                        Ok(PropertyValue::StructProperty(StructValue::Raw(buffer)))
+                    }
+                }
+            }
+            _ => match self.read_property_value(&tag.type_name) {
+                Err(ParseError::UnsupportedPropertyType(_))
+                | Err(ParseError::UnsupportedTextHistory(_)) => Ok(PropertyValue::Undefined),
+                result => result,
+            },
+        }
+    }
+
+    /// Reads a value that isn't preceded by a property tag, e.g. a container element.
+    pub fn read_property_value(&mut self, type_name: &PropertyTypeName) -> Result<PropertyValue> {
+        let value = match type_name.name.as_str() {
+            "BoolProperty" => PropertyValue::BoolProperty(self.reader.read_u8()? != 0),
+            "Int8Property" => PropertyValue::Int8Property(self.reader.read_i8()?),
+            "Int16Property" => {
+                PropertyValue::Int16Property(self.reader.read_i16::<LittleEndian>()?)
+            }
+            "IntProperty" => PropertyValue::IntProperty(self.reader.read_i32::<LittleEndian>()?),
+            "Int64Property" => {
+                PropertyValue::Int64Property(self.reader.read_i64::<LittleEndian>()?)
+            }
+            "UInt16Property" => {
+                PropertyValue::UInt16Property(self.reader.read_u16::<LittleEndian>()?)
+            }
+            "UInt32Property" => {
+                PropertyValue::UInt32Property(self.reader.read_u32::<LittleEndian>()?)
+            }
+            "UInt64Property" => {
+                PropertyValue::UInt64Property(self.reader.read_u64::<LittleEndian>()?)
+            }
+            "FloatProperty" => {
+                PropertyValue::FloatProperty(self.reader.read_f32::<LittleEndian>()?)
+            }
+            "DoubleProperty" => {
+                PropertyValue::DoubleProperty(self.reader.read_f64::<LittleEndian>()?)
+            }
+            // Bytes backed by an enum are stored by name
+            "ByteProperty" if type_name.enum_name().is_none() => {
+                PropertyValue::ByteProperty(self.reader.read_u8()?)
+            }
+            // Unversioned enums are stored as their underlying integer
+            "EnumProperty" if self.has_unversioned_properties() => {
+                self.read_unversioned_enum(type_name)?
+            }
+            "ByteProperty" | "EnumProperty" => PropertyValue::EnumProperty(self.read()?),
+            "NameProperty" => PropertyValue::NameProperty(self.read()?),
+            "StrProperty" => PropertyValue::StrProperty(self.read_fstring()?),
+            "Utf8StrProperty" => {
+                PropertyValue::Utf8StrProperty(String::from_utf8(self.read_char_array()?)?)
+            }
+            // Latin-1, every byte is the code point of its character
+            "AnsiStrProperty" => PropertyValue::AnsiStrProperty(
+                self.read_char_array()?
+                    .into_iter()
+                    .map(char::from)
+                    .collect(),
+            ),
+            "TextProperty" => PropertyValue::TextProperty(self.read()?),
+            "ObjectProperty" | "ClassProperty" | "WeakObjectProperty" | "InterfaceProperty" => {
+                PropertyValue::ObjectProperty(self.read()?)
+            }
+            "LazyObjectProperty" => PropertyValue::LazyObjectProperty(self.read()?),
+            "SoftObjectProperty" | "SoftClassProperty" => {
+                PropertyValue::SoftObjectProperty(self.read()?)
+            }
+            "DelegateProperty" => PropertyValue::DelegateProperty(self.read()?),
+            "MulticastDelegateProperty"
+            | "MulticastInlineDelegateProperty"
+            | "MulticastSparseDelegateProperty" => PropertyValue::MulticastDelegateProperty(
+                self.read_tarray(|parser| parser.read(), MAX_CONTAINER_ELEMENTS)?,
+            ),
+            "FieldPathProperty" => PropertyValue::FieldPathProperty(self.read()?),
+            "StructProperty" => {
+                // Legacy map tags don't name the struct types of their keys and values
+                let struct_name = type_name.struct_name();
+                let parser = struct_name.and_then(|name| self.registered_struct_parser(name));
+                let value = match (parser, struct_name) {
+                    (Some(parser), _) => Some(StructValue::Custom(self.read_plugin_value(parser)?)),
+                    (None, Some(struct_name)) if NATIVE_STRUCTS.contains(&struct_name) => {
+                        self.read_native_struct(struct_name)?
+                    }
+                    _ => None,
+                };
+                match (value, type_name.struct_name()) {
+                    (Some(value), _) => PropertyValue::StructProperty(value),
+                    (None, Some(struct_name)) if self.has_unversioned_properties() => {
+                        PropertyValue::StructProperty(StructValue::Properties(
+                            self.read_unversioned_properties(struct_name)?,
+                        ))
+                    }
+                    (None, _) => PropertyValue::StructProperty(StructValue::Properties(
+                        self.read_tagged_properties()?,
+                    )),
+                }
+            }
+            "ArrayProperty" => {
+                let inner = Self::required_parameter(type_name, type_name.inner())?;
+                if self.has_legacy_struct_array_tag(type_name)? {
+                    // Before complete type names, struct arrays carry a tag naming the struct
+                    let count = self.reader.read_i32::<LittleEndian>()?;
+                    let inner_tag: PropertyTag = self.read()?;
+                    if count < 0 || count as usize > MAX_CONTAINER_ELEMENTS {
+                        return Err(ParseError::InvalidArraySize(count));
+                    }
+                    let mut elements = Vec::with_capacity(count as usize);
+                    for _ in 0..count {
+                        elements.push(self.read_property_value(&inner_tag.type_name)?);
+                    }
+                    return Ok(PropertyValue::ArrayProperty(elements));
+                }
+
+                PropertyValue::ArrayProperty(self.read_tarray(
+                    |parser| parser.read_property_value(inner),
+                    MAX_CONTAINER_ELEMENTS,
+                )?)
+            }
+            "SetProperty" => {
+                let inner = Self::required_parameter(type_name, type_name.inner())?;
+                let removed = self.read_tarray(
+                    |parser| parser.read_property_value(inner),
+                    MAX_CONTAINER_ELEMENTS,
+                )?;
+                let elements = self.read_tarray(
+                    |parser| parser.read_property_value(inner),
+                    MAX_CONTAINER_ELEMENTS,
+                )?;
+                PropertyValue::SetProperty { elements, removed }
+            }
+            "MapProperty" => {
+                let (key, value) = Self::required_parameter(type_name, type_name.key_value())?;
+                let removed = self.read_tarray(
+                    |parser| parser.read_property_value(key),
+                    MAX_CONTAINER_ELEMENTS,
+                )?;
+                let entries = self.read_tarray(
+                    |parser| {
+                        Ok((
+                            parser.read_property_value(key)?,
+                            parser.read_property_value(value)?,
+                        ))
+                    },
+                    MAX_CONTAINER_ELEMENTS,
+                )?;
+                PropertyValue::MapProperty { entries, removed }
+            }
+            other => return Err(ParseError::UnsupportedPropertyType(other.to_string())),
+        };
+
+        Ok(value)
+    }
+
+    /// Characters of an ANSI or UTF-8 string, stored as an array that ends in
+    /// a null terminator unless it's empty.
+    fn read_char_array(&mut self) -> Result<Vec<u8>> {
+        let count = self.reader.read_i32::<LittleEndian>()?;
+        if count < 0 || count as usize > MAX_CONTAINER_ELEMENTS {
+            return Err(ParseError::InvalidArraySize(count));
+        }
+        let mut chars = vec![0u8; count as usize];
+        self.reader.read_exact(&mut chars)?;
+        if chars.last() == Some(&0) {
+            chars.pop();
+        }
+        Ok(chars)
+    }
+
+    /// Before complete type names, struct arrays carry a tag naming the struct.
+    fn has_legacy_struct_array_tag(&self, type_name: &PropertyTypeName) -> Result<bool> {
+        Ok(type_name.name == "ArrayProperty"
+            && type_name
+                .inner()
+                .is_some_and(|inner| inner.name == "StructProperty")
+            && !self.has_unversioned_properties()
+            && self.summary()?.file_version_ue5
+                < EUnrealEngineObjectUE5Version::PropertyTagCompleteTypeName as i32)
+    }
+
+    fn has_struct(type_name: &PropertyTypeName) -> bool {
+        type_name.name == "StructProperty" || type_name.parameters.iter().any(Self::has_struct)
+    }
+
+    fn required_parameter<T>(type_name: &PropertyTypeName, parameter: Option<T>) -> Result<T> {
+        parameter.ok_or_else(|| ParseError::UnsupportedPropertyType(type_name.to_string()))
+    }
+}
+
+impl<W: Write + Seek> Writable<ScriptDelegate> for UassetWriter<W> {
+    fn serialize(&mut self, value: &ScriptDelegate) -> Result<()> {
+        self.write(&value.object)?;
+        self.write(&value.function_name)
+    }
+}
+
+impl<W: Write + Seek> Writable<FieldPath> for UassetWriter<W> {
+    fn serialize(&mut self, value: &FieldPath) -> Result<()> {
+        self.write_tarray(&value.path, |writer, name| writer.write(name))?;
+        self.write(&value.owner)
+    }
+}
+
+impl<W: Write + Seek> Writable<PropertyData> for UassetWriter<W> {
+    fn serialize(&mut self, value: &PropertyData) -> Result<()> {
+        if value.tag.name.is_none() {
+            return self.write(&value.tag.name);
+        }
+        if self.has_unversioned_properties() {
+            return Err(ParseError::UnversionedWriteNotSupported);
+        }
+
+        let mut tag = value.tag.clone();
+        if let PropertyValue::BoolProperty(bool_val) = value.value {
+            tag.flags &= !(EPropertyTagFlags::BoolTrue as u8);
+            if bool_val {
+                tag.flags |= EPropertyTagFlags::BoolTrue as u8;
+            }
+        }
+
+        let size_position = self.write_tag(&tag)?;
+        let start = self.writer.stream_position()?;
+        self.write_tagged_value(&tag, &value.value)?;
+        self.patch_size(size_position, start)
+    }
+}
+
+impl<W: Write + Seek> UassetWriter<W> {
+    /// Writes tagged properties followed by the terminating `None` tag.
+    pub fn write_tagged_properties(&mut self, properties: &[PropertyData]) -> Result<()> {
+        for property in properties {
+            self.write(property)?;
+        }
+        self.write(&FName::new("None"))
+    }
+
+    fn write_tagged_value(&mut self, tag: &PropertyTag, value: &PropertyValue) -> Result<()> {
+        match (tag.type_name.name.as_str(), value) {
+            // Stored in the tag flags
+            ("BoolProperty", _) => Ok(()),
+            ("ArrayProperty", PropertyValue::ArrayProperty(elements))
+                if self.has_legacy_struct_array_tag(&tag.type_name) =>
+            {
+                let inner = tag.type_name.inner().cloned().unwrap_or_default();
+                self.writer
+                    .write_i32::<LittleEndian>(elements.len() as i32)?;
+                let mut inner_tag = PropertyTag {
+                    type_name: inner.clone(),
+                    ..PropertyTag::new(tag.name.clone())
+                };
+                inner_tag.array_index = 0;
+                let size_position = self.write_tag(&inner_tag)?;
+                let start = self.writer.stream_position()?;
+                for element in elements {
+                    self.write_property_value(&inner, element)?;
+                }
+                self.patch_size(size_position, start)
+            }
+            _ => self.write_property_value(&tag.type_name, value),
+        }
+    }
+
+    /// Writes a value that isn't preceded by a property tag, e.g. a container element.
+    pub fn write_property_value(
+        &mut self,
+        type_name: &PropertyTypeName,
+        value: &PropertyValue,
+    ) -> Result<()> {
+        let element_type = || {
+            type_name
+                .inner()
+                .cloned()
+                .ok_or_else(|| ParseError::UnsupportedPropertyType(type_name.to_string()))
+        };
+
+        match value {
+            PropertyValue::BoolProperty(value) => self.writer.write_u8(*value as u8)?,
+            PropertyValue::Int8Property(value) => self.writer.write_i8(*value)?,
+            PropertyValue::Int16Property(value) => self.writer.write_i16::<LittleEndian>(*value)?,
+            PropertyValue::IntProperty(value) => self.writer.write_i32::<LittleEndian>(*value)?,
+            PropertyValue::Int64Property(value) => self.writer.write_i64::<LittleEndian>(*value)?,
+            PropertyValue::UInt16Property(value) => {
+                self.writer.write_u16::<LittleEndian>(*value)?
+            }
+            PropertyValue::UInt32Property(value) => {
+                self.writer.write_u32::<LittleEndian>(*value)?
+            }
+            PropertyValue::UInt64Property(value) => {
+                self.writer.write_u64::<LittleEndian>(*value)?
+            }
+            PropertyValue::FloatProperty(value) => self.writer.write_f32::<LittleEndian>(*value)?,
+            PropertyValue::DoubleProperty(value) => {
+                self.writer.write_f64::<LittleEndian>(*value)?
+            }
+            PropertyValue::ByteProperty(value) => self.writer.write_u8(*value)?,
+            PropertyValue::EnumProperty(value) | PropertyValue::NameProperty(value) => {
+                self.write(value)?
+            }
+            PropertyValue::StrProperty(value) => self.write_fstring(value)?,
+            PropertyValue::Utf8StrProperty(value) => self.write_char_array(value.as_bytes())?,
+            PropertyValue::AnsiStrProperty(value) => {
+                let chars: Vec<u8> = value
+                    .chars()
+                    .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                    .collect();
+                self.write_char_array(&chars)?
+            }
+            PropertyValue::TextProperty(value) => self.write(value)?,
+            PropertyValue::ObjectProperty(value) => self.write(value)?,
+            PropertyValue::LazyObjectProperty(value) => self.write(value)?,
+            PropertyValue::SoftObjectProperty(value) => self.write(value)?,
+            PropertyValue::DelegateProperty(value) => self.write(value)?,
+            PropertyValue::MulticastDelegateProperty(values) => {
+                self.write_tarray(values, |writer, value| writer.write(value))?
+            }
+            PropertyValue::FieldPathProperty(value) => self.write(value)?,
+            PropertyValue::StructProperty(value) => self.write_native_struct(value)?,
+            PropertyValue::ArrayProperty(elements) => {
+                let inner = element_type()?;
+                self.write_tarray(elements, |writer, element| {
+                    writer.write_property_value(&inner, element)
+                })?;
+            }
+            PropertyValue::SetProperty { elements, removed } => {
+                let inner = element_type()?;
+                for values in [removed, elements] {
+                    self.write_tarray(values, |writer, element| {
+                        writer.write_property_value(&inner, element)
+                    })?;
+                }
+            }
+            PropertyValue::MapProperty { entries, removed } => {
+                let (key, value) = type_name
+                    .key_value()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .ok_or_else(|| ParseError::UnsupportedPropertyType(type_name.to_string()))?;
+                self.write_tarray(removed, |writer, element| {
+                    writer.write_property_value(&key, element)
+                })?;
+                self.write_tarray(entries, |writer, (entry_key, entry_value)| {
+                    writer.write_property_value(&key, entry_key)?;
+                    writer.write_property_value(&value, entry_value)
+                })?;
+            }
+            PropertyValue::Undefined => {
+                return Err(ParseError::UnsupportedPropertyType(type_name.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_char_array(&mut self, chars: &[u8]) -> Result<()> {
+        if chars.is_empty() {
+            self.writer.write_i32::<LittleEndian>(0)?;
+        } else {
+            self.writer
+                .write_i32::<LittleEndian>(chars.len() as i32 + 1)?;
+            self.writer.write_all(chars)?;
+            self.writer.write_u8(0)?;
+        }
+        Ok(())
+    }
+
+    fn has_legacy_struct_array_tag(&self, type_name: &PropertyTypeName) -> bool {
+        type_name.name == "ArrayProperty"
+            && type_name
+                .inner()
+                .is_some_and(|inner| inner.name == "StructProperty")
+            && self.get_summary().file_version_ue5
+                < EUnrealEngineObjectUE5Version::PropertyTagCompleteTypeName as i32
+    }
+}
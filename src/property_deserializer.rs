@@ -0,0 +1,425 @@
+use serde::Deserialize;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, IntoDeserializer, Visitor};
+
+use crate::errors::{ParseError, Result};
+use crate::package_index::PackageIndex;
+use crate::property_data::{PropertyData, PropertyValue};
+use crate::struct_value::StructValue;
+
+/// Text of an object reference, e.g. its path in the package.
+pub type ObjectText<'a> = &'a dyn Fn(PackageIndex) -> String;
+
+/// Deserializes a value into a Rust type. Structs serialized as properties
+/// and native structs are maps keyed by the engine's field names (`X`, `Y`,
+/// ...), containers are sequences and maps, names, texts and paths are
+/// strings. Objects are strings of their package index, see
+/// [`from_property_value_with`] to give their paths instead.
+pub fn from_property_value<'de, T: Deserialize<'de>>(value: &'de PropertyValue) -> Result<T> {
+    from_property_value_with(value, &package_index_text)
+}
+
+/// Like [`from_property_value`], objects are strings from `object_text`.
+pub fn from_property_value_with<'de, T: Deserialize<'de>>(
+    value: &'de PropertyValue,
+    object_text: ObjectText,
+) -> Result<T> {
+    T::deserialize(PropertyValueDeserializer { value, object_text })
+}
+
+/// Deserializes a property list, e.g. the properties of a data table row, into
+/// a Rust type. Properties left at their default aren't serialized, mark the
+/// fields that may be missing with `#[serde(default)]`. The elements of a
+/// static array share a name and come as a sequence, the ones left at their
+/// default as unit values.
+pub fn from_properties<'de, T: Deserialize<'de>>(properties: &'de [PropertyData]) -> Result<T> {
+    from_properties_with(properties, &package_index_text)
+}
+
+/// Like [`from_properties`], objects are strings from `object_text`.
+pub fn from_properties_with<'de, T: Deserialize<'de>>(
+    properties: &'de [PropertyData],
+    object_text: ObjectText,
+) -> Result<T> {
+    T::deserialize(PropertiesDeserializer {
+        properties,
+        object_text,
+    })
+}
+
+fn package_index_text(index: PackageIndex) -> String {
+    index.index.to_string()
+}
+
+pub struct PropertyValueDeserializer<'de, 'a> {
+    value: &'de PropertyValue,
+    object_text: ObjectText<'a>,
+}
+
+pub struct PropertiesDeserializer<'de, 'a> {
+    properties: &'de [PropertyData],
+    object_text: ObjectText<'a>,
+}
+
+/// Properties sharing a name, the elements of a static array by index.
+struct StaticArrayDeserializer<'de, 'a> {
+    elements: Vec<&'de PropertyValue>,
+    object_text: ObjectText<'a>,
+}
+
+impl<'de, 'a> PropertyValueDeserializer<'de, 'a> {
+    fn nested(&self, value: &'de PropertyValue) -> Self {
+        PropertyValueDeserializer {
+            value,
+            object_text: self.object_text,
+        }
+    }
+
+    fn visit_elements<V: Visitor<'de>>(
+        &self,
+        elements: impl Iterator<Item = &'de PropertyValue>,
+        visitor: V,
+    ) -> Result<V::Value> {
+        let mut seq = SeqDeserializer::new(elements.map(|element| self.nested(element)));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ParseError> for &'de PropertyValue {
+    type Deserializer = PropertyValueDeserializer<'de, 'static>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        PropertyValueDeserializer {
+            value: self,
+            object_text: &package_index_text,
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ParseError> for &'de [PropertyData] {
+    type Deserializer = PropertiesDeserializer<'de, 'static>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        PropertiesDeserializer {
+            properties: self,
+            object_text: &package_index_text,
+        }
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, ParseError> for PropertyValueDeserializer<'de, 'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, ParseError> for StaticArrayDeserializer<'de, 'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for PropertiesDeserializer<'de, '_> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Static arrays serialize one property per element, with the same name
+        let mut fields: Vec<(String, Vec<&'de PropertyValue>)> = vec![];
+        for property in self.properties {
+            let name = property.tag.name.as_string();
+            let index = property.tag.array_index.max(0) as usize;
+            let elements = match fields.iter().position(|(field, _)| *field == name) {
+                Some(position) => &mut fields[position].1,
+                None => {
+                    fields.push((name, vec![]));
+                    &mut fields.last_mut().unwrap().1
+                }
+            };
+            if elements.len() <= index {
+                elements.resize(index + 1, &PropertyValue::Undefined);
+            }
+            elements[index] = &property.value;
+        }
+
+        let mut map = MapDeserializer::new(fields.into_iter().map(|(name, elements)| {
+            (
+                name,
+                StaticArrayDeserializer {
+                    elements,
+                    object_text: self.object_text,
+                },
+            )
+        }));
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+fn visit_fields<'de, V, T>(
+    visitor: V,
+    fields: impl IntoIterator<Item = (&'static str, T)>,
+) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+    T: IntoDeserializer<'de, ParseError>,
+{
+    let mut map = MapDeserializer::new(fields.into_iter());
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+fn visit_struct<'de, V: Visitor<'de>>(
+    value: &'de StructValue,
+    object_text: ObjectText,
+    visitor: V,
+) -> Result<V::Value> {
+    match value {
+        StructValue::Vector(v) => visit_fields(visitor, [("X", v.x), ("Y", v.y), ("Z", v.z)]),
+        StructValue::Vector2D(v) => visit_fields(visitor, [("X", v.x), ("Y", v.y)]),
+        StructValue::Vector4(v) => {
+            visit_fields(visitor, [("X", v.x), ("Y", v.y), ("Z", v.z), ("W", v.w)])
+        }
+        StructValue::Rotator(r) => visit_fields(
+            visitor,
+            [("Pitch", r.pitch), ("Yaw", r.yaw), ("Roll", r.roll)],
+        ),
+        StructValue::Quat(q) => {
+            visit_fields(visitor, [("X", q.x), ("Y", q.y), ("Z", q.z), ("W", q.w)])
+        }
+        StructValue::Color(c) => {
+            visit_fields(visitor, [("R", c.r), ("G", c.g), ("B", c.b), ("A", c.a)])
+        }
+        StructValue::LinearColor(c) => {
+            visit_fields(visitor, [("R", c.r), ("G", c.g), ("B", c.b), ("A", c.a)])
+        }
+        StructValue::IntPoint(p) => visit_fields(visitor, [("X", p.x), ("Y", p.y)]),
+        StructValue::Guid(guid) => visitor.visit_string(guid.to_string()),
+        StructValue::DateTime(ticks) | StructValue::Timespan(ticks) => visitor.visit_i64(*ticks),
+        StructValue::SoftObjectPath(path) => visitor.visit_string(path.as_string()),
+        StructValue::GameplayTag(tag) => visitor.visit_string(tag.as_string()),
+        StructValue::GameplayTagContainer(tags) => {
+            let mut seq =
+                SeqDeserializer::<_, ParseError>::new(tags.iter().map(|tag| tag.as_string()));
+            let value = visitor.visit_seq(&mut seq)?;
+            seq.end()?;
+            Ok(value)
+        }
+        StructValue::Properties(properties) => {
+            let properties = PropertiesDeserializer {
+                properties,
+                object_text,
+            };
+            de::Deserializer::deserialize_any(properties, visitor)
+        }
+        StructValue::Raw(bytes) => visitor.visit_borrowed_bytes(bytes),
+        StructValue::Custom(custom) => visitor.visit_borrowed_bytes(&custom.data),
+    }
+}
+
+impl<'de> de::Deserializer<'de> for StaticArrayDeserializer<'de, '_> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.single() {
+            Some(value) => value.deserialize_any(visitor),
+            None => self.visit_elements(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.single() {
+            Some(value) => value.deserialize_option(visitor),
+            None => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.single() {
+            Some(value) => value.deserialize_newtype_struct(name, visitor),
+            None => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.single() {
+            Some(value) => value.deserialize_enum(name, variants, visitor),
+            None => self.visit_elements(visitor),
+        }
+    }
+
+    /// A single property is a static array of one element unless it holds a
+    /// container itself.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.single() {
+            Some(value) if value.is_container() => value.deserialize_seq(visitor),
+            _ => self.visit_elements(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de, 'a> StaticArrayDeserializer<'de, 'a> {
+    fn single(&self) -> Option<PropertyValueDeserializer<'de, 'a>> {
+        match self.elements.as_slice() {
+            [value] => Some(PropertyValueDeserializer {
+                value,
+                object_text: self.object_text,
+            }),
+            _ => None,
+        }
+    }
+
+    fn visit_elements<V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value> {
+        let mut seq =
+            SeqDeserializer::new(self.elements.iter().map(|value| PropertyValueDeserializer {
+                value,
+                object_text: self.object_text,
+            }));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+}
+
+impl PropertyValueDeserializer<'_, '_> {
+    fn is_container(&self) -> bool {
+        matches!(
+            self.value,
+            PropertyValue::ArrayProperty(_)
+                | PropertyValue::SetProperty { .. }
+                | PropertyValue::StructProperty(StructValue::GameplayTagContainer(_))
+        )
+    }
+}
+
+impl<'de> de::Deserializer<'de> for PropertyValueDeserializer<'de, '_> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            PropertyValue::BoolProperty(value) => visitor.visit_bool(*value),
+            PropertyValue::Int8Property(value) => visitor.visit_i8(*value),
+            PropertyValue::Int16Property(value) => visitor.visit_i16(*value),
+            PropertyValue::IntProperty(value) => visitor.visit_i32(*value),
+            PropertyValue::Int64Property(value) => visitor.visit_i64(*value),
+            PropertyValue::UInt16Property(value) => visitor.visit_u16(*value),
+            PropertyValue::UInt32Property(value) => visitor.visit_u32(*value),
+            PropertyValue::UInt64Property(value) => visitor.visit_u64(*value),
+            PropertyValue::FloatProperty(value) => visitor.visit_f32(*value),
+            PropertyValue::DoubleProperty(value) => visitor.visit_f64(*value),
+            PropertyValue::ByteProperty(value) => visitor.visit_u8(*value),
+            PropertyValue::EnumProperty(value) | PropertyValue::NameProperty(value) => {
+                visitor.visit_string(value.as_string())
+            }
+            PropertyValue::StrProperty(value)
+            | PropertyValue::Utf8StrProperty(value)
+            | PropertyValue::AnsiStrProperty(value) => visitor.visit_borrowed_str(value),
+            PropertyValue::TextProperty(value) => visitor.visit_string(value.as_string()),
+            PropertyValue::ObjectProperty(value) => {
+                visitor.visit_string((self.object_text)(*value))
+            }
+            PropertyValue::LazyObjectProperty(value) => visitor.visit_string(value.to_string()),
+            PropertyValue::SoftObjectProperty(value) => visitor.visit_string(value.as_string()),
+            PropertyValue::DelegateProperty(_)
+            | PropertyValue::MulticastDelegateProperty(_)
+            | PropertyValue::FieldPathProperty(_) => {
+                visitor.visit_string(self.value.export_text(self.object_text))
+            }
+            PropertyValue::StructProperty(value) => visit_struct(value, self.object_text, visitor),
+            PropertyValue::ArrayProperty(elements)
+            | PropertyValue::SetProperty { elements, .. } => {
+                self.visit_elements(elements.iter(), visitor)
+            }
+            PropertyValue::MapProperty { entries, .. } => {
+                let mut map = MapDeserializer::new(
+                    entries
+                        .iter()
+                        .map(|(key, value)| (self.nested(key), self.nested(value))),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            PropertyValue::Undefined => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            PropertyValue::Undefined => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Enum values are matched to unit variants by name without the enum
+    /// prefix, `EItemType::Weapon` is the `Weapon` variant. Byte enums give
+    /// their variant index.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.value {
+            PropertyValue::EnumProperty(value) | PropertyValue::NameProperty(value) => {
+                let value = value.as_string();
+                let variant = value.rsplit("::").next().unwrap_or_default().to_string();
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            PropertyValue::StrProperty(value) => {
+                let variant = value.rsplit("::").next().unwrap_or_default();
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            PropertyValue::ByteProperty(value) => {
+                visitor.visit_enum((*value as u32).into_deserializer())
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
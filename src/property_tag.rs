@@ -1,12 +1,16 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use crate::errors::Result;
 use crate::{
     fname::FName,
+    guid::Guid,
     property_type_name::PropertyTypeName,
+    struct_value::NATIVE_STRUCTS,
     uasset_parser::{Parsable, UassetParser},
+    uasset_writer::{UassetWriter, Writable},
+    versions::EUnrealEngineObjectUE5Version,
 };
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 #[repr(u8)]
 pub enum EPropertyTagFlags {
@@ -19,6 +23,24 @@ pub enum EPropertyTagFlags {
     SkippedSerialize = 0x20,
 }
 
+#[repr(u8)]
+pub enum EPropertyTagExtension {
+    NoExtension = 0x00,
+    ReserveForFutureUse = 0x01,
+    OverridableInformation = 0x02,
+}
+
+#[repr(u8)]
+pub enum EOverriddenPropertyOperation {
+    None = 0,
+    Modified,
+    Replaced,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PropertyTag {
     pub name: FName,
     pub type_name: PropertyTypeName,
@@ -26,19 +48,23 @@ pub struct PropertyTag {
     pub flags: u8,
     pub array_index: i32,
     pub guid: u128,
+    pub extensions: u8,
+    pub overridable_operation: u8,
+    pub experimental_overridable_logic: bool,
 }
 
 impl PropertyTag {
-    fn new(name: FName) -> Self {
+    pub(crate) fn new(name: FName) -> Self {
         PropertyTag {
             name,
-            type_name: PropertyTypeName {
-                name: String::new(),
-            },
+            type_name: PropertyTypeName::default(),
             size: 0,
             flags: 0,
             array_index: -1,
             guid: 0,
+            extensions: EPropertyTagExtension::NoExtension as u8,
+            overridable_operation: EOverriddenPropertyOperation::None as u8,
+            experimental_overridable_logic: false,
         }
     }
 }
@@ -50,6 +76,12 @@ impl<R: Read + Seek> Parsable<PropertyTag> for UassetParser<R> {
             return Ok(PropertyTag::new(name));
         }
 
+        if self.summary()?.file_version_ue5
+            < EUnrealEngineObjectUE5Version::PropertyTagCompleteTypeName as i32
+        {
+            return self.parse_legacy_tag(name);
+        }
+
         let type_name: PropertyTypeName = self.read()?;
         let size = self.reader.read_i32::<LittleEndian>()?;
         let flags: u8 = self.reader.read_u8()?;
@@ -65,17 +97,211 @@ impl<R: Read + Seek> Parsable<PropertyTag> for UassetParser<R> {
             0
         };
 
+        let mut tag = PropertyTag {
+            type_name,
+            size,
+            flags,
+            array_index,
+            guid,
+            ..PropertyTag::new(name)
+        };
+
         if flags & (EPropertyTagFlags::HasPropertyExtensions as u8) != 0 {
-            todo!();
+            self.read_tag_extensions(&mut tag)?;
         }
 
-        Ok(PropertyTag {
-            name,
-            type_name,
+        Ok(tag)
+    }
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Reads the tag layout used before UE 5.4, where type specific fields are
+    /// stored inline, and maps it onto the complete type name representation.
+    fn parse_legacy_tag(&mut self, name: FName) -> Result<PropertyTag> {
+        let type_name: FName = self.read()?;
+        let size = self.reader.read_i32::<LittleEndian>()?;
+        let array_index = self.reader.read_i32::<LittleEndian>()?;
+
+        let mut flags: u8 = 0;
+        let mut parameters = vec![];
+
+        match type_name.as_string().as_str() {
+            "StructProperty" => {
+                let struct_name: FName = self.read()?;
+                let struct_guid: Guid = self.read()?;
+                // Legacy tags don't flag native serialization, a struct that
+                // fails to read as tagged properties is kept raw instead
+                if NATIVE_STRUCTS.contains(&struct_name.as_string().as_str()) {
+                    flags |= EPropertyTagFlags::HasBinaryOrNativeSerialize as u8;
+                }
+                parameters.push(PropertyTypeName::new(struct_name.as_string()));
+                if struct_guid.is_valid() {
+                    parameters.push(PropertyTypeName::new(struct_guid.to_string()));
+                }
+            }
+            "BoolProperty" => {
+                let bool_val = self.reader.read_u8()?;
+                if bool_val != 0 {
+                    flags |= EPropertyTagFlags::BoolTrue as u8;
+                }
+            }
+            "ByteProperty" | "EnumProperty" => {
+                let enum_name: FName = self.read()?;
+                if !enum_name.is_none() {
+                    parameters.push(PropertyTypeName::new(enum_name.as_string()));
+                }
+            }
+            "ArrayProperty" | "SetProperty" | "OptionalProperty" => {
+                let inner_type: FName = self.read()?;
+                parameters.push(PropertyTypeName::new(inner_type.as_string()));
+            }
+            "MapProperty" => {
+                let key_type: FName = self.read()?;
+                let value_type: FName = self.read()?;
+                parameters.push(PropertyTypeName::new(key_type.as_string()));
+                parameters.push(PropertyTypeName::new(value_type.as_string()));
+            }
+            _ => {}
+        }
+
+        let guid: u128 = if self.reader.read_u8()? != 0 {
+            flags |= EPropertyTagFlags::HasPropertyGuid as u8;
+            self.reader.read_u128::<LittleEndian>()?
+        } else {
+            0
+        };
+
+        if array_index != 0 {
+            flags |= EPropertyTagFlags::HasArrayIndex as u8;
+        }
+
+        let mut tag = PropertyTag {
+            type_name: PropertyTypeName::with_parameters(type_name.as_string(), parameters),
             size,
             flags,
             array_index,
             guid,
-        })
+            ..PropertyTag::new(name)
+        };
+
+        if self.summary()?.file_version_ue5
+            >= EUnrealEngineObjectUE5Version::PropertyTagExtensionAndOverridableSerialization as i32
+        {
+            tag.flags |= EPropertyTagFlags::HasPropertyExtensions as u8;
+            self.read_tag_extensions(&mut tag)?;
+        }
+
+        Ok(tag)
+    }
+
+    fn read_tag_extensions(&mut self, tag: &mut PropertyTag) -> Result<()> {
+        tag.extensions = self.reader.read_u8()?;
+        if tag.extensions & (EPropertyTagExtension::OverridableInformation as u8) != 0 {
+            tag.overridable_operation = self.reader.read_u8()?;
+            tag.experimental_overridable_logic = self.reader.read_u32::<LittleEndian>()? != 0;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Writable<PropertyTag> for UassetWriter<W> {
+    fn serialize(&mut self, value: &PropertyTag) -> Result<()> {
+        self.write_tag(value)?;
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> UassetWriter<W> {
+    /// Writes a tag, returns the position of its size so it can be patched
+    /// once the value is written.
+    pub(crate) fn write_tag(&mut self, tag: &PropertyTag) -> Result<u64> {
+        self.write(&tag.name)?;
+        if tag.name.is_none() {
+            return Ok(0);
+        }
+
+        if self.get_summary().file_version_ue5
+            < EUnrealEngineObjectUE5Version::PropertyTagCompleteTypeName as i32
+        {
+            return self.write_legacy_tag(tag);
+        }
+
+        self.write(&tag.type_name)?;
+        let size_position = self.writer.stream_position()?;
+        self.writer.write_i32::<LittleEndian>(tag.size)?;
+        self.writer.write_u8(tag.flags)?;
+        if tag.flags & (EPropertyTagFlags::HasArrayIndex as u8) != 0 {
+            self.writer.write_i32::<LittleEndian>(tag.array_index)?;
+        }
+        if tag.flags & (EPropertyTagFlags::HasPropertyGuid as u8) != 0 {
+            self.writer.write_u128::<LittleEndian>(tag.guid)?;
+        }
+        if tag.flags & (EPropertyTagFlags::HasPropertyExtensions as u8) != 0 {
+            self.write_tag_extensions(tag)?;
+        }
+
+        Ok(size_position)
+    }
+
+    fn write_legacy_tag(&mut self, tag: &PropertyTag) -> Result<u64> {
+        let type_name = &tag.type_name;
+        let parameter_name = |index: usize| {
+            FName::new(
+                type_name
+                    .parameters
+                    .get(index)
+                    .map_or("None", |parameter| parameter.name.as_str()),
+            )
+        };
+
+        self.write(&FName::new(type_name.name.as_str()))?;
+        let size_position = self.writer.stream_position()?;
+        self.writer.write_i32::<LittleEndian>(tag.size)?;
+        self.writer.write_i32::<LittleEndian>(tag.array_index)?;
+
+        match type_name.name.as_str() {
+            "StructProperty" => {
+                self.write(&parameter_name(0))?;
+                self.write(&type_name.struct_guid().unwrap_or_default())?;
+            }
+            "BoolProperty" => {
+                let bool_val = tag.flags & (EPropertyTagFlags::BoolTrue as u8) != 0;
+                self.writer.write_u8(bool_val as u8)?;
+            }
+            "ByteProperty" | "EnumProperty" | "ArrayProperty" | "SetProperty"
+            | "OptionalProperty" => {
+                self.write(&parameter_name(0))?;
+            }
+            "MapProperty" => {
+                self.write(&parameter_name(0))?;
+                self.write(&parameter_name(1))?;
+            }
+            _ => {}
+        }
+
+        if tag.flags & (EPropertyTagFlags::HasPropertyGuid as u8) != 0 {
+            self.writer.write_u8(1)?;
+            self.writer.write_u128::<LittleEndian>(tag.guid)?;
+        } else {
+            self.writer.write_u8(0)?;
+        }
+
+        if self.get_summary().file_version_ue5
+            >= EUnrealEngineObjectUE5Version::PropertyTagExtensionAndOverridableSerialization as i32
+        {
+            self.write_tag_extensions(tag)?;
+        }
+
+        Ok(size_position)
+    }
+
+    fn write_tag_extensions(&mut self, tag: &PropertyTag) -> Result<()> {
+        self.writer.write_u8(tag.extensions)?;
+        if tag.extensions & (EPropertyTagExtension::OverridableInformation as u8) != 0 {
+            self.writer.write_u8(tag.overridable_operation)?;
+            self.writer
+                .write_u32::<LittleEndian>(tag.experimental_overridable_logic as u32)?;
+        }
+        Ok(())
     }
 }
@@ -1,32 +1,161 @@
-use crate::errors::Result;
+use crate::errors::{ParseError, Result};
 use crate::fname::FName;
+use crate::guid::Guid;
 use crate::uasset_parser::{Parsable, UassetParser};
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Read, Seek};
+use crate::uasset_writer::{UassetWriter, Writable};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fmt;
+use std::io::{Read, Seek, Write};
 
+/// Deepest nesting of type parameters read from a file, real types stay far
+/// below it.
+pub(crate) const MAX_TYPE_NAME_DEPTH: usize = 16;
+
+/// Complete type of a property, e.g. `ArrayProperty(StructProperty(Vector(/Script/CoreUObject)))`.
+/// Parameters hold the inner types of containers, the struct or enum of a
+/// property and, nested below those, the package they live in.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PropertyTypeName {
     pub name: String,
+    pub parameters: Vec<PropertyTypeName>,
 }
 
-impl<R: Read + Seek> Parsable<PropertyTypeName> for UassetParser<R> {
-    fn parse(&mut self) -> Result<PropertyTypeName> {
-        let mut remaning: i32 = 1;
-        let mut names: Vec<String> = vec![];
-        loop {
-            let name: FName = self.read()?;
-            let inner_count: i32 = self.reader.read_i32::<LittleEndian>()?;
+impl PropertyTypeName {
+    pub fn new(name: impl Into<String>) -> Self {
+        PropertyTypeName {
+            name: name.into(),
+            parameters: vec![],
+        }
+    }
+
+    pub fn with_parameters(name: impl Into<String>, parameters: Vec<PropertyTypeName>) -> Self {
+        PropertyTypeName {
+            name: name.into(),
+            parameters,
+        }
+    }
+
+    /// Element type of an array, set or optional property.
+    pub fn inner(&self) -> Option<&PropertyTypeName> {
+        match self.name.as_str() {
+            "ArrayProperty" | "SetProperty" | "OptionalProperty" => self.parameters.first(),
+            _ => None,
+        }
+    }
+
+    /// Key and value types of a map property.
+    pub fn key_value(&self) -> Option<(&PropertyTypeName, &PropertyTypeName)> {
+        match (self.name.as_str(), self.parameters.as_slice()) {
+            ("MapProperty", [key, value, ..]) => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    /// Short name of the struct of a struct property, e.g. `Vector`.
+    pub fn struct_name(&self) -> Option<&str> {
+        match self.name.as_str() {
+            "StructProperty" => self.parameters.first().map(|p| p.name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Full path of the struct of a struct property, e.g. `/Script/CoreUObject.Vector`.
+    /// Tags written before UE 5.4 only know the short name.
+    pub fn struct_path(&self) -> Option<String> {
+        match self.name.as_str() {
+            "StructProperty" => self.parameters.first().map(PropertyTypeName::object_path),
+            _ => None,
+        }
+    }
+
+    /// GUID of the user defined struct of a struct property, stored as the
+    /// parameter after the struct.
+    pub fn struct_guid(&self) -> Option<Guid> {
+        match (self.name.as_str(), self.parameters.as_slice()) {
+            ("StructProperty", [_, guid, ..]) => Guid::from_digits(&guid.name),
+            _ => None,
+        }
+    }
 
-            names.push(name.as_string());
+    /// Name of the enum backing a byte or enum property.
+    pub fn enum_name(&self) -> Option<&str> {
+        match self.name.as_str() {
+            "ByteProperty" | "EnumProperty" => self.parameters.first().map(|p| p.name.as_str()),
+            _ => None,
+        }
+    }
 
-            remaning += inner_count - 1;
+    fn object_path(&self) -> String {
+        match self.parameters.first() {
+            Some(package) => format!("{}.{}", package.name, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
 
-            if remaning <= 0 {
-                break;
+impl fmt::Display for PropertyTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.parameters.is_empty() {
+            write!(f, "(")?;
+            for (i, parameter) in self.parameters.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", parameter)?;
             }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as the complete type string, like the editor prints it.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PropertyTypeName {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<R: Read + Seek> Parsable<PropertyTypeName> for UassetParser<R> {
+    fn parse(&mut self) -> Result<PropertyTypeName> {
+        self.read_type_name(0)
+    }
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    fn read_type_name(&mut self, depth: usize) -> Result<PropertyTypeName> {
+        if depth > MAX_TYPE_NAME_DEPTH {
+            return Err(ParseError::PropertyTypeTooDeep(MAX_TYPE_NAME_DEPTH));
+        }
+        let name: FName = self.read()?;
+        let inner_count: i32 = self.reader.read_i32::<LittleEndian>()?;
+
+        // The count isn't reserved up front, a corrupt one runs out of data instead
+        let mut parameters = vec![];
+        for _ in 0..inner_count {
+            parameters.push(self.read_type_name(depth + 1)?);
         }
 
         Ok(PropertyTypeName {
-            name: names.join("_"),
+            name: name.as_string(),
+            parameters,
         })
     }
 }
+
+impl<W: Write + Seek> Writable<PropertyTypeName> for UassetWriter<W> {
+    fn serialize(&mut self, value: &PropertyTypeName) -> Result<()> {
+        self.write(&FName::new(value.name.as_str()))?;
+        self.writer
+            .write_i32::<LittleEndian>(value.parameters.len() as i32)?;
+        for parameter in &value.parameters {
+            self.write(parameter)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+use crate::errors::{ParseError, Result};
+use crate::fname::FName;
+use crate::guid::Guid;
+use crate::package_index::PackageIndex;
+use crate::parser_registry::PluginValue;
+use crate::property_data::PropertyData;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
+use crate::versions::EUnrealEngineObjectUE5Version;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, Write};
+
+/// Structs that are always written with their native serializer, even when
+/// the surrounding data carries no tag to say so (e.g. container elements).
+pub const NATIVE_STRUCTS: &[&str] = &[
+    "Vector",
+    "Vector2D",
+    "Vector4",
+    "Rotator",
+    "Quat",
+    "Guid",
+    "Color",
+    "LinearColor",
+    "IntPoint",
+    "DateTime",
+    "Timespan",
+    "SoftObjectPath",
+    "SoftClassPath",
+    "GameplayTagContainer",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Vector {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Vector2D {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Vector4 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Rotator {
+    pub pitch: f64,
+    pub yaw: f64,
+    pub roll: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Quat {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Color {
+    pub b: u8,
+    pub g: u8,
+    pub r: u8,
+    pub a: u8,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IntPoint {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SoftObjectPath {
+    pub package_name: FName,
+    /// Missing for packages saved before top level asset paths, `package_name`
+    /// then holds the whole asset path.
+    pub asset_name: Option<FName>,
+    pub sub_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum StructValue {
+    Vector(Vector),
+    Vector2D(Vector2D),
+    Vector4(Vector4),
+    Rotator(Rotator),
+    Quat(Quat),
+    Guid(Guid),
+    Color(Color),
+    LinearColor(LinearColor),
+    IntPoint(IntPoint),
+    DateTime(i64),
+    Timespan(i64),
+    SoftObjectPath(SoftObjectPath),
+    GameplayTag(FName),
+    GameplayTagContainer(Vec<FName>),
+    /// Struct serialized as a tagged property list
+    Properties(Vec<PropertyData>),
+    /// Natively serialized struct without a known serializer
+    Raw(Vec<u8>),
+    /// Struct decoded by a parser from the registry
+    Custom(PluginValue),
+}
+
+impl SoftObjectPath {
+    pub fn as_string(&self) -> String {
+        let mut path = self.package_name.as_string();
+        if let Some(asset_name) = self.asset_name.as_ref().filter(|n| !n.is_none()) {
+            path = format!("{}.{}", path, asset_name.as_string());
+        }
+        if !self.sub_path.is_empty() {
+            path = format!("{}:{}", path, self.sub_path);
+        }
+        path
+    }
+}
+
+impl StructValue {
+    pub fn as_string(&self) -> String {
+        self.text(&|index| index.index.to_string(), false)
+    }
+
+    /// Text of the value the way the editor exports it, see
+    /// [`PropertyValue::export_text`](crate::property_data::PropertyValue::export_text).
+    pub fn export_text(&self, object_text: &dyn Fn(PackageIndex) -> String) -> String {
+        self.text(object_text, true)
+    }
+
+    pub(crate) fn text(
+        &self,
+        object_text: &dyn Fn(PackageIndex) -> String,
+        editor: bool,
+    ) -> String {
+        match self {
+            StructValue::Vector(v) => format!("(X={},Y={},Z={})", v.x, v.y, v.z),
+            StructValue::Vector2D(v) => format!("(X={},Y={})", v.x, v.y),
+            StructValue::Vector4(v) => format!("(X={},Y={},Z={},W={})", v.x, v.y, v.z, v.w),
+            StructValue::Rotator(r) => {
+                format!("(Pitch={},Yaw={},Roll={})", r.pitch, r.yaw, r.roll)
+            }
+            StructValue::Quat(q) => format!("(X={},Y={},Z={},W={})", q.x, q.y, q.z, q.w),
+            StructValue::Guid(guid) => guid.to_string(),
+            StructValue::Color(c) => format!("(B={},G={},R={},A={})", c.b, c.g, c.r, c.a),
+            StructValue::LinearColor(c) => format!("(R={},G={},B={},A={})", c.r, c.g, c.b, c.a),
+            StructValue::IntPoint(p) => format!("(X={},Y={})", p.x, p.y),
+            StructValue::DateTime(ticks) | StructValue::Timespan(ticks) => ticks.to_string(),
+            StructValue::SoftObjectPath(path) => path.as_string(),
+            StructValue::GameplayTag(tag) => tag.as_string(),
+            StructValue::GameplayTagContainer(tags) => {
+                let tags: Vec<String> = tags.iter().map(|t| t.as_string()).collect();
+                format!("({})", tags.join(","))
+            }
+            StructValue::Properties(properties) => {
+                let fields: Vec<String> = properties
+                    .iter()
+                    .map(|p| {
+                        format!(
+                            "{}={}",
+                            p.tag.name.as_string(),
+                            p.value.text(object_text, editor)
+                        )
+                    })
+                    .collect();
+                format!("({})", fields.join(","))
+            }
+            StructValue::Raw(_) => String::new(),
+            StructValue::Custom(custom) => custom.value.as_string(),
+        }
+    }
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Reads a natively serialized struct, `None` if there is no serializer for it.
+    pub fn read_native_struct(&mut self, struct_name: &str) -> Result<Option<StructValue>> {
+        let value = match struct_name {
+            "Vector" => StructValue::Vector(self.read()?),
+            "Vector2D" => StructValue::Vector2D(self.read()?),
+            "Vector4" => StructValue::Vector4(self.read()?),
+            "Rotator" => StructValue::Rotator(self.read()?),
+            "Quat" => StructValue::Quat(self.read()?),
+            "Guid" => StructValue::Guid(self.read()?),
+            "Color" => StructValue::Color(self.read()?),
+            "LinearColor" => StructValue::LinearColor(self.read()?),
+            "IntPoint" => StructValue::IntPoint(self.read()?),
+            "DateTime" => StructValue::DateTime(self.reader.read_i64::<LittleEndian>()?),
+            "Timespan" => StructValue::Timespan(self.reader.read_i64::<LittleEndian>()?),
+            "SoftObjectPath" | "SoftClassPath" => StructValue::SoftObjectPath(self.read()?),
+            "GameplayTag" => StructValue::GameplayTag(self.read()?),
+            "GameplayTagContainer" => {
+                StructValue::GameplayTagContainer(self.read_tarray(|p| p.read(), 100000)?)
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Reads a component of the math types, which are doubles since large world coordinates.
+    fn read_real(&mut self) -> Result<f64> {
+        if self.summary()?.file_version_ue5
+            >= EUnrealEngineObjectUE5Version::LargeWorldCoordinates as i32
+        {
+            Ok(self.reader.read_f64::<LittleEndian>()?)
+        } else {
+            Ok(self.reader.read_f32::<LittleEndian>()? as f64)
+        }
+    }
+}
+
+impl<R: Read + Seek> Parsable<Vector> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Vector> {
+        Ok(Vector {
+            x: self.read_real()?,
+            y: self.read_real()?,
+            z: self.read_real()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<Vector2D> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Vector2D> {
+        Ok(Vector2D {
+            x: self.read_real()?,
+            y: self.read_real()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<Vector4> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Vector4> {
+        Ok(Vector4 {
+            x: self.read_real()?,
+            y: self.read_real()?,
+            z: self.read_real()?,
+            w: self.read_real()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<Rotator> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Rotator> {
+        Ok(Rotator {
+            pitch: self.read_real()?,
+            yaw: self.read_real()?,
+            roll: self.read_real()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<Quat> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Quat> {
+        Ok(Quat {
+            x: self.read_real()?,
+            y: self.read_real()?,
+            z: self.read_real()?,
+            w: self.read_real()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<Color> for UassetParser<R> {
+    fn parse(&mut self) -> Result<Color> {
+        Ok(Color {
+            b: self.reader.read_u8()?,
+            g: self.reader.read_u8()?,
+            r: self.reader.read_u8()?,
+            a: self.reader.read_u8()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<LinearColor> for UassetParser<R> {
+    fn parse(&mut self) -> Result<LinearColor> {
+        Ok(LinearColor {
+            r: self.reader.read_f32::<LittleEndian>()?,
+            g: self.reader.read_f32::<LittleEndian>()?,
+            b: self.reader.read_f32::<LittleEndian>()?,
+            a: self.reader.read_f32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<IntPoint> for UassetParser<R> {
+    fn parse(&mut self) -> Result<IntPoint> {
+        Ok(IntPoint {
+            x: self.reader.read_i32::<LittleEndian>()?,
+            y: self.reader.read_i32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Parsable<SoftObjectPath> for UassetParser<R> {
+    fn parse(&mut self) -> Result<SoftObjectPath> {
+        // Packages with a soft object path list reference its entries by index
+        if !self.soft_object_paths.is_empty() {
+            let index = self.reader.read_i32::<LittleEndian>()?;
+            return self
+                .soft_object_paths
+                .get(index as usize)
+                .cloned()
+                .ok_or(ParseError::InvalidSoftObjectPathIndex(index));
+        }
+
+        if self.summary()?.file_version_ue5
+            >= EUnrealEngineObjectUE5Version::FSoftObjectPathRemoveAssetPathFNames as i32
+        {
+            Ok(SoftObjectPath {
+                package_name: self.read()?,
+                asset_name: Some(self.read()?),
+                sub_path: self.read_fstring()?,
+            })
+        } else {
+            Ok(SoftObjectPath {
+                package_name: self.read()?,
+                asset_name: None,
+                sub_path: self.read_fstring()?,
+            })
+        }
+    }
+}
+
+impl<W: Write + Seek> UassetWriter<W> {
+    /// Writes a struct with its native serializer. Property lists are written
+    /// tagged, raw structs as the bytes they were read from.
+    pub fn write_native_struct(&mut self, value: &StructValue) -> Result<()> {
+        match value {
+            StructValue::Vector(v) => self.write_reals(&[v.x, v.y, v.z])?,
+            StructValue::Vector2D(v) => self.write_reals(&[v.x, v.y])?,
+            StructValue::Vector4(v) => self.write_reals(&[v.x, v.y, v.z, v.w])?,
+            StructValue::Rotator(r) => self.write_reals(&[r.pitch, r.yaw, r.roll])?,
+            StructValue::Quat(q) => self.write_reals(&[q.x, q.y, q.z, q.w])?,
+            StructValue::Guid(guid) => self.write(guid)?,
+            StructValue::Color(c) => self.writer.write_all(&[c.b, c.g, c.r, c.a])?,
+            StructValue::LinearColor(c) => {
+                for component in [c.r, c.g, c.b, c.a] {
+                    self.writer.write_f32::<LittleEndian>(component)?;
+                }
+            }
+            StructValue::IntPoint(p) => {
+                self.writer.write_i32::<LittleEndian>(p.x)?;
+                self.writer.write_i32::<LittleEndian>(p.y)?;
+            }
+            StructValue::DateTime(ticks) | StructValue::Timespan(ticks) => {
+                self.writer.write_i64::<LittleEndian>(*ticks)?
+            }
+            StructValue::SoftObjectPath(path) => self.write(path)?,
+            StructValue::GameplayTag(tag) => self.write(tag)?,
+            StructValue::GameplayTagContainer(tags) => {
+                self.write_tarray(tags, |writer, tag| writer.write(tag))?
+            }
+            StructValue::Properties(properties) => self.write_tagged_properties(properties)?,
+            StructValue::Raw(bytes) => self.writer.write_all(bytes)?,
+            StructValue::Custom(custom) => self.writer.write_all(&custom.data)?,
+        }
+        Ok(())
+    }
+
+    fn write_reals(&mut self, components: &[f64]) -> Result<()> {
+        let large_world_coordinates = self.get_summary().file_version_ue5
+            >= EUnrealEngineObjectUE5Version::LargeWorldCoordinates as i32;
+        for &component in components {
+            if large_world_coordinates {
+                self.writer.write_f64::<LittleEndian>(component)?;
+            } else {
+                self.writer.write_f32::<LittleEndian>(component as f32)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a soft object path in full, the way entries of the soft object
+    /// path list are stored.
+    pub(crate) fn write_soft_object_path_value(&mut self, value: &SoftObjectPath) -> Result<()> {
+        self.write(&value.package_name)?;
+        if self.get_summary().file_version_ue5
+            >= EUnrealEngineObjectUE5Version::FSoftObjectPathRemoveAssetPathFNames as i32
+        {
+            let asset_name = value.asset_name.clone().unwrap_or(FName::new("None"));
+            self.write(&asset_name)?;
+        }
+        self.write_fstring(&value.sub_path)
+    }
+}
+
+impl<W: Write + Seek> Writable<SoftObjectPath> for UassetWriter<W> {
+    fn serialize(&mut self, value: &SoftObjectPath) -> Result<()> {
+        // Packages with a soft object path list reference its entries by index
+        if !self.soft_object_paths.is_empty() {
+            let index = match self.soft_object_paths.iter().position(|p| p == value) {
+                Some(index) => index,
+                None => {
+                    self.soft_object_paths.push(value.clone());
+                    self.soft_object_paths.len() - 1
+                }
+            };
+            self.writer.write_i32::<LittleEndian>(index as i32)?;
+            return Ok(());
+        }
+
+        self.write_soft_object_path_value(value)
+    }
+}
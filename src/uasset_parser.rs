@@ -1,18 +1,41 @@
 use byteorder::{LittleEndian, ReadBytesExt};
 
 use crate::{
+    compressed_package::{COMPRESSED_CHUNK_TAG, COMPRESSED_CHUNK_V2_TAG},
+    custom_version::CustomVersionKey,
     errors::{ParseError, Result},
     export_entry::ExportEntry,
-    uasset_summary::UassetSummary,
+    import_entry::ImportEntry,
+    package_index::{PackageIndex, ResolvedObject},
+    parser_registry::ParserRegistry,
+    property_data::PropertyData,
+    struct_value::SoftObjectPath,
+    uasset_summary::{EPackageFlags, UassetSummary},
+    usmap::Usmap,
+    versions::PackageFileVersion,
+    zen_package::{ScriptObjects, ZenPackageHeader},
 };
 use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
 
 pub struct UassetParser<R: Read + Seek> {
     pub reader: R,
     summary: Option<UassetSummary>,
     pub allow_unversioned: bool,
+    /// Layouts for packages saved with unversioned properties
+    pub mappings: Option<Arc<Usmap>>,
+    /// Versions to assume for unversioned packages, taken from the mappings if not set
+    pub unversioned_file_version: Option<PackageFileVersion>,
+    /// Parsers for game specific structs and exports
+    pub registry: Option<Arc<ParserRegistry<R>>>,
+    /// Script objects of the global container, to name the imports of packages from IoStore containers
+    pub script_objects: Option<Arc<ScriptObjects>>,
     pub names: Vec<String>,
     pub entries: Vec<ExportEntry>,
+    pub imports: Vec<ImportEntry>,
+    pub soft_object_paths: Vec<SoftObjectPath>,
+    /// Header of a package from an IoStore container
+    pub zen_header: Option<ZenPackageHeader>,
 }
 
 pub trait Parsable<T> {
@@ -25,8 +48,15 @@ impl<R: Read + Seek> UassetParser<R> {
             reader,
             summary: None,
             allow_unversioned: true,
+            mappings: None,
+            unversioned_file_version: None,
+            registry: None,
+            script_objects: None,
             names: vec![],
             entries: vec![],
+            imports: vec![],
+            soft_object_paths: vec![],
+            zen_header: None,
         }
     }
 
@@ -41,14 +71,163 @@ impl<R: Read + Seek> UassetParser<R> {
         self.summary.as_ref().unwrap()
     }
 
+    /// Summary for reads that depend on the package versions, an error when
+    /// no summary was parsed or set yet.
+    pub fn summary(&self) -> Result<&UassetSummary> {
+        self.summary.as_ref().ok_or(ParseError::MissingSummary)
+    }
+
+    pub fn set_summary(&mut self, summary: UassetSummary) {
+        self.summary = Some(summary);
+    }
+
     pub fn parse_asset(&mut self) -> Result<()> {
         let summary: UassetSummary = self.read()?;
+        let first_chunk = summary.compressed_chunks.first().copied();
         self.summary = Some(summary);
+        if let Some(chunk) = first_chunk {
+            // Read as stored, the chunk starts with the tag of its blocks
+            self.reader
+                .seek(SeekFrom::Start(chunk.compressed_offset as u64))?;
+            let tag = self.reader.read_i64::<LittleEndian>().unwrap_or(0);
+            if tag == COMPRESSED_CHUNK_TAG || tag == COMPRESSED_CHUNK_V2_TAG {
+                return Err(ParseError::CompressedPackage);
+            }
+        }
         self.names = self.parse_names()?;
+        self.imports = self.parse_imports()?;
         self.entries = self.parse_entries()?;
+        self.soft_object_paths = self.parse_soft_object_paths()?;
         Ok(())
     }
 
+    /// Version of the custom version `T` the package was saved with.
+    /// Unversioned packages take it from the mappings, when they have it.
+    pub fn custom_version<T: CustomVersionKey>(&self) -> Option<i32> {
+        let summary = self.summary.as_ref()?;
+        if summary.unversioned {
+            self.mappings.as_ref().and_then(|mappings| {
+                mappings
+                    .custom_versions
+                    .iter()
+                    .find(|version| version.key == T::KEY)
+                    .map(|version| version.version)
+            })
+        } else {
+            summary.custom_version::<T>()
+        }
+    }
+
+    /// Properties are tagged unless the summary says otherwise, or when there
+    /// is no summary yet.
+    pub fn has_unversioned_properties(&self) -> bool {
+        self.summary.as_ref().is_some_and(|summary| {
+            summary.package_flags & (EPackageFlags::UnversionedProperties as u32) != 0
+        })
+    }
+
+    /// Reads the property list of an object or struct, `struct_name` picks the
+    /// schema when the package has unversioned properties.
+    pub fn read_properties(&mut self, struct_name: &str) -> Result<Vec<PropertyData>> {
+        if self.has_unversioned_properties() {
+            self.read_unversioned_properties(struct_name)
+        } else {
+            self.read_tagged_properties()
+        }
+    }
+
+    /// Export whose serialized data starts at `offset`.
+    pub fn export_at(&self, offset: u64) -> Option<&ExportEntry> {
+        self.entries
+            .iter()
+            .find(|export| export.serial_offset as u64 == offset)
+    }
+
+    /// Serialized data of the export at `export_index` as stored in the package.
+    pub fn read_export_data(&mut self, export_index: usize) -> Result<Vec<u8>> {
+        let export = self
+            .entries
+            .get(export_index)
+            .ok_or(ParseError::InvalidExportIndex(export_index))?;
+        let (offset, size) = (export.serial_offset, export.serial_size);
+        let file_size = self.reader.seek(SeekFrom::End(0))?;
+        if size < 0 || offset < 0 || offset.saturating_add(size) as u64 > file_size {
+            return Err(ParseError::InvalidArraySize(
+                size.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            ));
+        }
+        let mut data = vec![0u8; size as usize];
+        self.reader.seek(SeekFrom::Start(offset as u64))?;
+        self.reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn resolve(&self, index: PackageIndex) -> Option<ResolvedObject<'_>> {
+        if let Some(import_index) = index.import_index() {
+            self.imports.get(import_index).map(ResolvedObject::Import)
+        } else if let Some(export_index) = index.export_index() {
+            self.entries.get(export_index).map(ResolvedObject::Export)
+        } else {
+            None
+        }
+    }
+
+    /// Builds the full path of the referenced object by walking its outers,
+    /// e.g. `/Game/Data/DT_Items.DT_Items:SubObject`.
+    pub fn object_path(&self, index: PackageIndex) -> Option<String> {
+        // Every object appears at most once in a chain of outers, a longer one
+        // is a cycle
+        self.object_path_within(index, self.imports.len() + self.entries.len())
+    }
+
+    fn object_path_within(&self, index: PackageIndex, max_depth: usize) -> Option<String> {
+        if max_depth == 0 {
+            return None;
+        }
+        let (name, outer_index) = match self.resolve(index)? {
+            ResolvedObject::Import(import) => {
+                if import.outer_index.is_null() {
+                    return Some(import.object_name.as_string());
+                }
+                (import.object_name.as_string(), import.outer_index)
+            }
+            ResolvedObject::Export(export) => (export.object_name.as_string(), export.outer_index),
+        };
+
+        let outer_path = if outer_index.is_null() {
+            self.get_summary().package_name.clone()
+        } else {
+            self.object_path_within(outer_index, max_depth - 1)?
+        };
+
+        // Objects whose outer is a top level asset rather than a package use ':'
+        let delimiter = if !self.is_package(outer_index)
+            && self.is_package(self.outer_of(outer_index).unwrap_or_default())
+        {
+            ':'
+        } else {
+            '.'
+        };
+
+        Some(format!("{}{}{}", outer_path, delimiter, name))
+    }
+
+    fn outer_of(&self, index: PackageIndex) -> Option<PackageIndex> {
+        match self.resolve(index)? {
+            ResolvedObject::Import(import) => Some(import.outer_index),
+            ResolvedObject::Export(export) => Some(export.outer_index),
+        }
+    }
+
+    fn is_package(&self, index: PackageIndex) -> bool {
+        match self.resolve(index) {
+            Some(ResolvedObject::Import(import)) => import.class_name.as_string() == "Package",
+            Some(ResolvedObject::Export(_)) => false,
+            // Null outer stands for the package being parsed
+            None => true,
+        }
+    }
+
     pub fn read_fstring(&mut self) -> Result<String> {
         let size = self.reader.read_i32::<LittleEndian>()?;
 
@@ -177,4 +356,42 @@ impl<R: Read + Seek> UassetParser<R> {
 
         Ok(entries)
     }
+
+    fn parse_imports(&mut self) -> Result<Vec<ImportEntry>> {
+        let summary = self.summary.as_ref().unwrap();
+        let count = summary.import_count;
+        let offset = summary.import_offset;
+
+        self.reader.seek(SeekFrom::Start(offset as u64))?;
+
+        let mut imports = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            let import: ImportEntry = self.read()?;
+            imports.push(import);
+        }
+
+        Ok(imports)
+    }
+
+    fn parse_soft_object_paths(&mut self) -> Result<Vec<SoftObjectPath>> {
+        let summary = self.summary.as_ref().unwrap();
+        let (Some(count), Some(offset)) = (
+            summary.soft_object_paths_count,
+            summary.soft_object_paths_offset,
+        ) else {
+            return Ok(vec![]);
+        };
+
+        self.reader.seek(SeekFrom::Start(offset as u64))?;
+
+        let mut paths = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            let path: SoftObjectPath = self.read()?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
 }
@@ -1,10 +1,39 @@
+use crate::custom_version::{CustomVersion, CustomVersionKey};
 use crate::errors::{ParseError, Result};
 use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_writer::{UassetWriter, Writable};
 use crate::versions::EUnrealEngineObjectUE5Version;
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Read, Seek, SeekFrom};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+#[repr(u32)]
+pub enum EPackageFlags {
+    None = 0x00000000,
+    NewlyCreated = 0x00000001,
+    ClientOptional = 0x00000002,
+    ServerSideOnly = 0x00000004,
+    CompiledIn = 0x00000010,
+    ForDiffing = 0x00000020,
+    EditorOnly = 0x00000040,
+    Developer = 0x00000080,
+    UncookedOnlyScriptCompiled = 0x00000100,
+    Cooked = 0x00000200,
+    UnversionedProperties = 0x00002000,
+    ContainsMapData = 0x00004000,
+    Compiling = 0x00010000,
+    ContainsMap = 0x00020000,
+    RequiresLocalizationGather = 0x00040000,
+    PlayInEditor = 0x00100000,
+    ContainsScript = 0x00200000,
+    DisallowExport = 0x00400000,
+    DynamicImports = 0x10000000,
+    RuntimeGenerated = 0x20000000,
+    ReloadingForCooker = 0x40000000,
+    FilterEditorOnly = 0x80000000,
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UassetSummary {
     pub tag: u32,
     pub legacy_file_version: i32,
@@ -12,9 +41,11 @@ pub struct UassetSummary {
     pub file_version_ue4: i32,
     pub file_version_ue5: i32,
     pub file_version_licensee_ue4: u32,
+    /// Saved without versions, the file versions are the assumed ones
+    pub unversioned: bool,
     pub saved_hash: Option<[u8; 20]>,
     pub total_header_size: i32,
-    pub custom_versions: Vec<[u8; 20]>,
+    pub custom_versions: Vec<CustomVersion>,
     pub package_name: String,
     pub package_flags: u32,
     pub name_count: i32,
@@ -52,20 +83,74 @@ pub struct UassetSummary {
     pub compatible_engine_version_changelist: u32,
     pub compatible_engine_version_name: String,
     pub compression_flags: u32,
-    pub compressed_chunks: Vec<[u8; 16]>,
+    pub compressed_chunks: Vec<CompressedChunk>,
     pub package_source: u32,
     pub additional_packages_to_cook: Vec<String>,
     pub asset_registry_data_offset: i32,
     pub bulk_data_start_offset: i64,
+    pub world_tile_info_data_offset: i32,
+    pub chunk_ids: Vec<i32>,
+    pub preload_dependency_count: i32,
+    pub preload_dependency_offset: i32,
+    pub names_referenced_from_export_data_count: Option<i32>,
+    pub payload_toc_offset: Option<i64>,
+    pub data_resource_offset: Option<i32>,
+}
+
+impl UassetSummary {
+    /// Version of the custom version `T` stored in the summary.
+    pub fn custom_version<T: CustomVersionKey>(&self) -> Option<i32> {
+        self.custom_versions
+            .iter()
+            .find(|version| version.key == T::KEY)
+            .map(|version| version.version)
+    }
+}
+
+/// Range of the package data stored compressed, the data before the first
+/// chunk (the summary) is stored as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CompressedChunk {
+    pub uncompressed_offset: i32,
+    pub uncompressed_size: i32,
+    pub compressed_offset: i32,
+    pub compressed_size: i32,
+}
+
+impl<R: Read + Seek> Parsable<CompressedChunk> for UassetParser<R> {
+    fn parse(&mut self) -> Result<CompressedChunk> {
+        Ok(CompressedChunk {
+            uncompressed_offset: self.reader.read_i32::<LittleEndian>()?,
+            uncompressed_size: self.reader.read_i32::<LittleEndian>()?,
+            compressed_offset: self.reader.read_i32::<LittleEndian>()?,
+            compressed_size: self.reader.read_i32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl<W: Write + Seek> Writable<CompressedChunk> for UassetWriter<W> {
+    fn serialize(&mut self, value: &CompressedChunk) -> Result<()> {
+        self.writer
+            .write_i32::<LittleEndian>(value.uncompressed_offset)?;
+        self.writer
+            .write_i32::<LittleEndian>(value.uncompressed_size)?;
+        self.writer
+            .write_i32::<LittleEndian>(value.compressed_offset)?;
+        self.writer
+            .write_i32::<LittleEndian>(value.compressed_size)?;
+        Ok(())
+    }
 }
 
 impl<R: Read + Seek> Parsable<UassetSummary> for UassetParser<R> {
     fn parse(&mut self) -> Result<UassetSummary> {
         self.reader.seek(SeekFrom::Start(0))?;
 
-        let mut s = UassetSummary::default();
-
-        s.tag = self.reader.read_u32::<LittleEndian>()?;
+        let mut s = UassetSummary {
+            tag: self.reader.read_u32::<LittleEndian>()?,
+            ..Default::default()
+        };
 
         if s.tag != 0x9e2a83c1 {
             return Err(ParseError::InvalidTag);
@@ -88,6 +173,21 @@ impl<R: Read + Seek> Parsable<UassetSummary> for UassetParser<R> {
 
         s.file_version_licensee_ue4 = self.reader.read_u32::<LittleEndian>()?;
 
+        if s.file_version_ue4 == 0 && s.file_version_ue5 == 0 && s.file_version_licensee_ue4 == 0 {
+            // Cooked packages may be saved without versions, the rest of the
+            // summary is still laid out for the engine that saved them
+            if !self.allow_unversioned {
+                return Err(ParseError::UnversionedAssetNotAllowed);
+            }
+            let version = self
+                .unversioned_file_version
+                .or_else(|| self.mappings.as_ref().and_then(|m| m.file_version))
+                .ok_or(ParseError::MissingUnversionedFileVersion)?;
+            s.unversioned = true;
+            s.file_version_ue4 = version.file_version_ue4;
+            s.file_version_ue5 = version.file_version_ue5;
+        }
+
         const KNOWN_SUPPORTED_UE5VER: i32 = 1017;
         if s.file_version_ue5 > KNOWN_SUPPORTED_UE5VER {
             eprintln!(
@@ -104,14 +204,7 @@ impl<R: Read + Seek> Parsable<UassetSummary> for UassetParser<R> {
             s.total_header_size = self.reader.read_i32::<LittleEndian>()?;
         }
 
-        s.custom_versions = self.read_tarray(
-            |parser| {
-                let mut buf = [0u8; 20];
-                parser.reader.read_exact(&mut buf)?;
-                Ok(buf)
-            },
-            100000,
-        )?;
+        s.custom_versions = self.read_tarray(|parser| parser.read(), 100000)?;
 
         if s.file_version_ue5 < EUnrealEngineObjectUE5Version::PackageSavedHash as i32 {
             s.total_header_size = self.reader.read_i32::<LittleEndian>()?;
@@ -209,18 +302,7 @@ impl<R: Read + Seek> Parsable<UassetSummary> for UassetParser<R> {
         let remaining_bytes = (s.total_header_size as u64).saturating_sub(current_pos + 1);
         let max_chunks = (remaining_bytes / 16) as usize;
 
-        s.compressed_chunks = self.read_tarray(
-            |parser| {
-                let mut buf = [0u8; 16];
-                parser.reader.read_exact(&mut buf)?;
-                Ok(buf)
-            },
-            max_chunks,
-        )?;
-
-        if !s.compressed_chunks.is_empty() {
-            return Err(ParseError::CompressedChunksNotSupported);
-        }
+        s.compressed_chunks = self.read_tarray(|parser| parser.read(), max_chunks)?;
 
         s.package_source = self.reader.read_u32::<LittleEndian>()?;
 
@@ -236,6 +318,164 @@ impl<R: Read + Seek> Parsable<UassetSummary> for UassetParser<R> {
         self.check_file_offset(s.asset_registry_data_offset as i64)?;
         self.check_file_offset(s.bulk_data_start_offset)?;
 
+        s.world_tile_info_data_offset = self.reader.read_i32::<LittleEndian>()?;
+
+        let current_pos = self.reader.stream_position()?;
+        let remaining_bytes = (s.total_header_size as u64).saturating_sub(current_pos + 1);
+        s.chunk_ids = self.read_tarray(
+            |parser| Ok(parser.reader.read_i32::<LittleEndian>()?),
+            (remaining_bytes / 4) as usize,
+        )?;
+
+        s.preload_dependency_count = self.reader.read_i32::<LittleEndian>()?;
+        s.preload_dependency_offset = self.reader.read_i32::<LittleEndian>()?;
+
+        if s.file_version_ue5 >= EUnrealEngineObjectUE5Version::NamesReferencedFromExportData as i32
+        {
+            s.names_referenced_from_export_data_count =
+                Some(self.reader.read_i32::<LittleEndian>()?);
+        }
+
+        if s.file_version_ue5 >= EUnrealEngineObjectUE5Version::PayloadToc as i32 {
+            s.payload_toc_offset = Some(self.reader.read_i64::<LittleEndian>()?);
+        }
+
+        if s.file_version_ue5 >= EUnrealEngineObjectUE5Version::DataResources as i32 {
+            s.data_resource_offset = Some(self.reader.read_i32::<LittleEndian>()?);
+        }
+
         Ok(s)
     }
 }
+
+impl<W: Write + Seek> Writable<UassetSummary> for UassetWriter<W> {
+    fn serialize(&mut self, s: &UassetSummary) -> Result<()> {
+        let ue5 = s.file_version_ue5;
+        let w = &mut self.writer;
+        w.write_u32::<LittleEndian>(s.tag)?;
+        w.write_i32::<LittleEndian>(s.legacy_file_version)?;
+        w.write_i32::<LittleEndian>(s.legacy_ue3_version)?;
+
+        // Unversioned packages keep their versions out of the file
+        let (file_version_ue4, file_version_ue5) = if s.unversioned {
+            (0, 0)
+        } else {
+            (s.file_version_ue4, s.file_version_ue5)
+        };
+        w.write_i32::<LittleEndian>(file_version_ue4)?;
+        if s.legacy_file_version <= -8 {
+            w.write_i32::<LittleEndian>(file_version_ue5)?;
+        }
+        w.write_u32::<LittleEndian>(s.file_version_licensee_ue4)?;
+
+        if ue5 >= EUnrealEngineObjectUE5Version::PackageSavedHash as i32 {
+            w.write_all(&s.saved_hash.unwrap_or_default())?;
+            w.write_i32::<LittleEndian>(s.total_header_size)?;
+        }
+
+        self.write_tarray(&s.custom_versions, |writer, version| writer.write(version))?;
+
+        if ue5 < EUnrealEngineObjectUE5Version::PackageSavedHash as i32 {
+            self.writer.write_i32::<LittleEndian>(s.total_header_size)?;
+        }
+
+        self.write_fstring(&s.package_name)?;
+        let w = &mut self.writer;
+        w.write_u32::<LittleEndian>(s.package_flags)?;
+        w.write_i32::<LittleEndian>(s.name_count)?;
+        w.write_i32::<LittleEndian>(s.name_offset)?;
+
+        if ue5 >= EUnrealEngineObjectUE5Version::AddSoftObjectPathList as i32 {
+            w.write_i32::<LittleEndian>(s.soft_object_paths_count.unwrap_or_default())?;
+            w.write_i32::<LittleEndian>(s.soft_object_paths_offset.unwrap_or_default())?;
+        }
+
+        self.write_fstring(&s.localization_id)?;
+
+        let w = &mut self.writer;
+        w.write_i32::<LittleEndian>(s.gatherable_text_data_count)?;
+        w.write_i32::<LittleEndian>(s.gatherable_text_data_offset)?;
+        w.write_i32::<LittleEndian>(s.export_count)?;
+        w.write_i32::<LittleEndian>(s.export_offset)?;
+        w.write_i32::<LittleEndian>(s.import_count)?;
+        w.write_i32::<LittleEndian>(s.import_offset)?;
+
+        if ue5 >= EUnrealEngineObjectUE5Version::VerseCells as i32 {
+            w.write_i32::<LittleEndian>(s.cell_export_count.unwrap_or_default())?;
+            w.write_i32::<LittleEndian>(s.cell_export_offset.unwrap_or_default())?;
+            w.write_i32::<LittleEndian>(s.cell_import_count.unwrap_or_default())?;
+            w.write_i32::<LittleEndian>(s.cell_import_offset.unwrap_or_default())?;
+        }
+
+        if ue5 >= EUnrealEngineObjectUE5Version::MetadataSerializationOffset as i32 {
+            w.write_i32::<LittleEndian>(s.metadata_offset.unwrap_or_default())?;
+        }
+
+        w.write_i32::<LittleEndian>(s.depends_offset)?;
+        w.write_i32::<LittleEndian>(s.soft_package_references_count)?;
+        w.write_i32::<LittleEndian>(s.soft_package_references_offset)?;
+        w.write_i32::<LittleEndian>(s.searchable_names_offset)?;
+        w.write_i32::<LittleEndian>(s.thumbnail_table_offset)?;
+
+        if ue5 < EUnrealEngineObjectUE5Version::PackageSavedHash as i32 {
+            w.write_all(&s.guid.unwrap_or_default())?;
+        }
+        w.write_all(&s.persistent_guid)?;
+
+        self.write_tarray(&s.generations, |writer, generation| {
+            Ok(writer.writer.write_all(generation)?)
+        })?;
+
+        let w = &mut self.writer;
+        w.write_u16::<LittleEndian>(s.saved_by_engine_version_major)?;
+        w.write_u16::<LittleEndian>(s.saved_by_engine_version_minor)?;
+        w.write_u16::<LittleEndian>(s.saved_by_engine_version_patch)?;
+        w.write_u32::<LittleEndian>(s.saved_by_engine_version_changelist)?;
+        self.write_fstring(&s.saved_by_engine_version_name)?;
+
+        let w = &mut self.writer;
+        w.write_u16::<LittleEndian>(s.compatible_engine_version_major)?;
+        w.write_u16::<LittleEndian>(s.compatible_engine_version_minor)?;
+        w.write_u16::<LittleEndian>(s.compatible_engine_version_patch)?;
+        w.write_u32::<LittleEndian>(s.compatible_engine_version_changelist)?;
+        self.write_fstring(&s.compatible_engine_version_name)?;
+
+        self.writer.write_u32::<LittleEndian>(s.compression_flags)?;
+        self.write_tarray(&s.compressed_chunks, |writer, chunk| writer.write(chunk))?;
+        self.writer.write_u32::<LittleEndian>(s.package_source)?;
+
+        self.write_tarray(&s.additional_packages_to_cook, |writer, package| {
+            writer.write_fstring(package)
+        })?;
+
+        let w = &mut self.writer;
+        w.write_i32::<LittleEndian>(s.asset_registry_data_offset)?;
+        w.write_i64::<LittleEndian>(s.bulk_data_start_offset)?;
+        w.write_i32::<LittleEndian>(s.world_tile_info_data_offset)?;
+
+        self.write_tarray(&s.chunk_ids, |writer, chunk_id| {
+            Ok(writer.writer.write_i32::<LittleEndian>(*chunk_id)?)
+        })?;
+
+        let w = &mut self.writer;
+        w.write_i32::<LittleEndian>(s.preload_dependency_count)?;
+        w.write_i32::<LittleEndian>(s.preload_dependency_offset)?;
+
+        if ue5 >= EUnrealEngineObjectUE5Version::NamesReferencedFromExportData as i32 {
+            w.write_i32::<LittleEndian>(
+                s.names_referenced_from_export_data_count
+                    .unwrap_or(s.name_count),
+            )?;
+        }
+
+        if ue5 >= EUnrealEngineObjectUE5Version::PayloadToc as i32 {
+            w.write_i64::<LittleEndian>(s.payload_toc_offset.unwrap_or(-1))?;
+        }
+
+        if ue5 >= EUnrealEngineObjectUE5Version::DataResources as i32 {
+            w.write_i32::<LittleEndian>(s.data_resource_offset.unwrap_or(-1))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    errors::{ParseError, Result},
+    export_entry::ExportEntry,
+    name_hash::name_hashes,
+    struct_value::SoftObjectPath,
+    uasset_parser::UassetParser,
+    uasset_summary::{EPackageFlags, UassetSummary},
+};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+pub struct UassetWriter<W: Write + Seek> {
+    pub writer: W,
+    summary: UassetSummary,
+    pub names: Vec<String>,
+    name_indices: HashMap<String, i32>,
+    /// Number of leading names referenced from export data, names written
+    /// after it is set go at the end of that range
+    export_name_count: Option<usize>,
+    pub soft_object_paths: Vec<SoftObjectPath>,
+}
+
+pub trait Writable<T> {
+    fn serialize(&mut self, value: &T) -> Result<()>;
+}
+
+impl<W: Write + Seek> UassetWriter<W> {
+    /// Writer for data of a package saved with the versions and flags of `summary`.
+    pub fn new(writer: W, summary: UassetSummary) -> Self {
+        Self {
+            writer,
+            summary,
+            names: vec![],
+            name_indices: HashMap::new(),
+            export_name_count: None,
+            soft_object_paths: vec![],
+        }
+    }
+
+    /// Writer for data of the package read by `parser`, starting from its name
+    /// table. Names that aren't in it yet are added as they're written, after
+    /// the names the export data of the package already references.
+    pub fn for_package<R: Read + Seek>(writer: W, parser: &UassetParser<R>) -> Self {
+        let mut uasset_writer = Self::new(writer, parser.get_summary().clone());
+        for name in &parser.names {
+            uasset_writer.name_index(name);
+        }
+        uasset_writer.export_name_count = parser
+            .get_summary()
+            .names_referenced_from_export_data_count
+            .map(|count| (count.max(0) as usize).min(parser.names.len()));
+        uasset_writer.soft_object_paths = parser.soft_object_paths.clone();
+        uasset_writer
+    }
+
+    pub fn write<T>(&mut self, value: &T) -> Result<()>
+    where
+        Self: Writable<T>,
+    {
+        self.serialize(value)
+    }
+
+    pub fn get_summary(&self) -> &UassetSummary {
+        &self.summary
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Index of `name` in the name table, adding it if it's not there yet.
+    /// While the names referenced from export data are tracked, names that
+    /// are new to that range are moved to its end, which only shifts names
+    /// the export data doesn't reference.
+    pub fn name_index(&mut self, name: &str) -> i32 {
+        let existing = self.name_indices.get(name).map(|&index| index as usize);
+        let count = match (existing, self.export_name_count) {
+            (Some(index), None) => return index as i32,
+            (Some(index), Some(count)) if index < count => return index as i32,
+            (None, None) => {
+                let index = self.names.len() as i32;
+                self.names.push(name.to_string());
+                self.name_indices.insert(name.to_string(), index);
+                return index;
+            }
+            (_, Some(count)) => count,
+        };
+
+        match existing {
+            Some(index) => {
+                let name = self.names.remove(index);
+                self.names.insert(count, name);
+            }
+            None => self.names.insert(count, name.to_string()),
+        }
+        for (index, name) in self.names.iter().enumerate().skip(count) {
+            self.name_indices.insert(name.clone(), index as i32);
+        }
+        self.export_name_count = Some(count + 1);
+        count as i32
+    }
+
+    pub fn has_unversioned_properties(&self) -> bool {
+        self.summary.package_flags & (EPackageFlags::UnversionedProperties as u32) != 0
+    }
+
+    /// Writes a string the way the engine does: empty strings as a bare zero
+    /// length, ANSI when possible and UTF-16 otherwise, both null terminated.
+    pub fn write_fstring(&mut self, value: &str) -> Result<()> {
+        if value.is_empty() {
+            self.writer.write_i32::<LittleEndian>(0)?;
+        } else if value.is_ascii() {
+            self.writer
+                .write_i32::<LittleEndian>(value.len() as i32 + 1)?;
+            self.writer.write_all(value.as_bytes())?;
+            self.writer.write_u8(0)?;
+        } else {
+            let units: Vec<u16> = value.encode_utf16().collect();
+            self.writer
+                .write_i32::<LittleEndian>(-(units.len() as i32 + 1))?;
+            for unit in units {
+                self.writer.write_u16::<LittleEndian>(unit)?;
+            }
+            self.writer.write_u16::<LittleEndian>(0)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_tarray<T, F>(&mut self, items: &[T], mut writer_fn: F) -> Result<()>
+    where
+        F: FnMut(&mut Self, &T) -> Result<()>,
+    {
+        self.writer.write_i32::<LittleEndian>(items.len() as i32)?;
+        for item in items {
+            writer_fn(self, item)?;
+        }
+        Ok(())
+    }
+
+    /// Patches the size written at `position` with the bytes written since `start`.
+    pub(crate) fn patch_size(&mut self, position: u64, start: u64) -> Result<()> {
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(position))?;
+        self.writer
+            .write_i32::<LittleEndian>((end - start) as i32)?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+
+    fn write_name_table(&mut self, names: &[String]) -> Result<()> {
+        for name in names {
+            self.write_fstring(name)?;
+            let (non_case_preserving_hash, case_preserving_hash) = name_hashes(name);
+            self.writer
+                .write_u16::<LittleEndian>(non_case_preserving_hash)?;
+            self.writer
+                .write_u16::<LittleEndian>(case_preserving_hash)?;
+        }
+        Ok(())
+    }
+
+    fn write_soft_object_path_list(&mut self, paths: &[SoftObjectPath]) -> Result<()> {
+        for path in paths {
+            self.write_soft_object_path_value(path)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a package with the tables of `source` and new export payloads,
+    /// one per export in export table order. The name table and the soft
+    /// object path list are taken from this writer, so names added while
+    /// serializing the payloads end up in the package. Header sections the
+    /// crate doesn't model are copied over and every offset pointing past a
+    /// resized section is moved along with it.
+    pub fn write_package<R: Read + Seek>(
+        &mut self,
+        source: &mut UassetParser<R>,
+        export_data: &[Vec<u8>],
+    ) -> Result<()> {
+        let old = source.get_summary().clone();
+        if !old.compressed_chunks.is_empty() {
+            return Err(ParseError::CompressedChunksNotSupported);
+        }
+        if export_data.len() != source.entries.len() {
+            return Err(ParseError::ExportCountMismatch {
+                expected: source.entries.len(),
+                actual: export_data.len(),
+            });
+        }
+
+        // Names of the import and export tables go in the name table too, after
+        // the ones the export data references
+        let export_name_count = self.export_name_count.take().unwrap_or(self.names.len());
+        for import in &source.imports {
+            self.name_index(import.class_package.value());
+            self.name_index(import.class_name.value());
+            self.name_index(import.object_name.value());
+            if let Some(package_name) = &import.package_name {
+                self.name_index(package_name.value());
+            }
+        }
+        for export in &source.entries {
+            self.name_index(export.object_name.value());
+        }
+
+        // The name table follows the summary, directly followed by the soft
+        // object path list when the package has one
+        let old_names_size =
+            Self::serialized_size(old.clone(), |w| w.write_name_table(&source.names))?;
+        let new_names_size =
+            Self::serialized_size(old.clone(), |w| w.write_name_table(&self.names))?;
+        let old_names_end = old.name_offset as i64 + old_names_size as i64;
+        let soft_paths_follow_names = old.soft_object_paths_offset == Some(old_names_end as i32);
+
+        let (old_soft_paths_size, new_soft_paths_size) = if soft_paths_follow_names {
+            (
+                Self::serialized_size(old.clone(), |w| {
+                    w.write_soft_object_path_list(&source.soft_object_paths)
+                })?,
+                Self::serialized_size(old.clone(), |w| {
+                    w.write_soft_object_path_list(&self.soft_object_paths)
+                })?,
+            )
+        } else {
+            (0, 0)
+        };
+
+        let old_front_end = old_names_end + old_soft_paths_size as i64;
+        let header_delta = (new_names_size + new_soft_paths_size) as i64
+            - (old_names_size + old_soft_paths_size) as i64;
+
+        // New locations of the export payloads, keeping any gaps between them
+        let mut order: Vec<usize> = (0..source.entries.len()).collect();
+        order.sort_by_key(|&i| source.entries[i].serial_offset);
+        let mut exports: Vec<ExportEntry> = source.entries.clone();
+        let mut old_end = old.total_header_size as i64;
+        let mut new_end = old_end + header_delta;
+        for &i in &order {
+            let gap = (exports[i].serial_offset - old_end).max(0);
+            exports[i].serial_offset = new_end + gap;
+            exports[i].serial_size = export_data[i].len() as i64;
+            old_end = source.entries[i].serial_offset + source.entries[i].serial_size;
+            new_end = exports[i].serial_offset + exports[i].serial_size;
+        }
+        let old_exports_end = old_end;
+        let exports_delta = new_end - old_exports_end;
+
+        let relocate = |offset: i64| -> i64 {
+            if offset <= old.name_offset as i64 {
+                offset
+            } else if offset < old_front_end {
+                offset + (new_names_size as i64 - old_names_size as i64)
+            } else if offset < old.total_header_size as i64 {
+                offset + header_delta
+            } else if offset < old_exports_end {
+                // Inside an export payload, keep the position within it
+                order
+                    .iter()
+                    .map(|&i| (&source.entries[i], &exports[i]))
+                    .rfind(|(entry, _)| entry.serial_offset <= offset)
+                    .map(|(entry, export)| export.serial_offset + (offset - entry.serial_offset))
+                    .unwrap_or(offset + header_delta)
+            } else {
+                offset + exports_delta
+            }
+        };
+        let relocate_i32 = |offset: i32| relocate(offset as i64) as i32;
+
+        let mut summary = old.clone();
+        summary.total_header_size = old.total_header_size + header_delta as i32;
+        summary.name_count = self.names.len() as i32;
+        summary.names_referenced_from_export_data_count = old
+            .names_referenced_from_export_data_count
+            .map(|_| export_name_count as i32);
+        if soft_paths_follow_names {
+            summary.soft_object_paths_count = Some(self.soft_object_paths.len() as i32);
+            summary.soft_object_paths_offset = Some(old.name_offset + new_names_size as i32);
+        } else {
+            summary.soft_object_paths_offset = old.soft_object_paths_offset.map(relocate_i32);
+        }
+        summary.gatherable_text_data_offset = relocate_i32(old.gatherable_text_data_offset);
+        summary.export_offset = relocate_i32(old.export_offset);
+        summary.import_offset = relocate_i32(old.import_offset);
+        summary.cell_export_offset = old.cell_export_offset.map(relocate_i32);
+        summary.cell_import_offset = old.cell_import_offset.map(relocate_i32);
+        summary.metadata_offset = old.metadata_offset.map(relocate_i32);
+        summary.depends_offset = relocate_i32(old.depends_offset);
+        summary.soft_package_references_offset = relocate_i32(old.soft_package_references_offset);
+        summary.searchable_names_offset = relocate_i32(old.searchable_names_offset);
+        summary.thumbnail_table_offset = relocate_i32(old.thumbnail_table_offset);
+        summary.asset_registry_data_offset = relocate_i32(old.asset_registry_data_offset);
+        summary.bulk_data_start_offset = relocate(old.bulk_data_start_offset);
+        summary.world_tile_info_data_offset = relocate_i32(old.world_tile_info_data_offset);
+        summary.preload_dependency_offset = relocate_i32(old.preload_dependency_offset);
+        summary.payload_toc_offset = old.payload_toc_offset.map(relocate);
+        summary.data_resource_offset = old.data_resource_offset.map(relocate_i32);
+
+        // Sections between the front tables and the export payloads are copied,
+        // with the export table rewritten and the absolute offsets they hold moved
+        let header_end = old.total_header_size as i64;
+        let file_size = source.reader.seek(SeekFrom::End(0))?;
+        if header_end < old_front_end || header_end as u64 > file_size {
+            return Err(ParseError::InvalidFileOffset {
+                offset: header_end,
+                file_size,
+            });
+        }
+        // Position in `header_rest` of a section of `len` bytes at `offset`
+        let header_position = |offset: i64, len: usize| -> Result<usize> {
+            if offset < old_front_end || offset + len as i64 > header_end {
+                return Err(ParseError::InvalidFileOffset {
+                    offset,
+                    file_size: header_end as u64,
+                });
+            }
+            Ok((offset - old_front_end) as usize)
+        };
+        let mut header_rest = vec![0u8; (header_end - old_front_end) as usize];
+        source.reader.seek(SeekFrom::Start(old_front_end as u64))?;
+        source.reader.read_exact(&mut header_rest)?;
+
+        // Import and export entries have a fixed size, they're rewritten in place
+        let mut table_writer = UassetWriter::new(Cursor::new(vec![]), old.clone());
+        table_writer.name_indices = self.name_indices.clone();
+        table_writer.names = self.names.clone();
+        for import in &source.imports {
+            table_writer.write(import)?;
+        }
+        let import_table_size = table_writer.writer.position() as usize;
+        for export in &exports {
+            table_writer.write(export)?;
+        }
+        let tables = table_writer.into_inner().into_inner();
+        let (import_table, export_table) = tables.split_at(import_table_size);
+        for (offset, table) in [
+            (old.import_offset, import_table),
+            (old.export_offset, export_table),
+        ] {
+            let start = header_position(offset as i64, table.len())?;
+            header_rest[start..start + table.len()].copy_from_slice(table);
+        }
+
+        if old.package_flags & (EPackageFlags::FilterEditorOnly as u32) == 0
+            && old.asset_registry_data_offset > 0
+        {
+            let position = header_position(old.asset_registry_data_offset as i64, 8)?;
+            let mut cursor = Cursor::new(&mut header_rest[position..]);
+            let dependency_data_offset = cursor.read_i64::<LittleEndian>()?;
+            cursor.set_position(0);
+            cursor.write_i64::<LittleEndian>(relocate(dependency_data_offset))?;
+        }
+
+        if old.thumbnail_table_offset > 0 {
+            let position = header_position(old.thumbnail_table_offset as i64, 4)? as u64;
+            let mut thumbnails = UassetParser::new(Cursor::new(header_rest));
+            thumbnails.reader.set_position(position);
+            let count = thumbnails.reader.read_i32::<LittleEndian>()?;
+            for _ in 0..count {
+                thumbnails.read_fstring()?; // Class name
+                thumbnails.read_fstring()?; // Object path
+                let offset_position = thumbnails.reader.position();
+                let offset = thumbnails.reader.read_i32::<LittleEndian>()?;
+                thumbnails.reader.set_position(offset_position);
+                thumbnails
+                    .reader
+                    .write_i32::<LittleEndian>(relocate_i32(offset))?;
+            }
+            header_rest = thumbnails.reader.into_inner();
+        }
+
+        self.write(&summary)?;
+        let names = self.names.clone();
+        self.write_name_table(&names)?;
+        if soft_paths_follow_names {
+            let soft_object_paths = self.soft_object_paths.clone();
+            self.write_soft_object_path_list(&soft_object_paths)?;
+        }
+        self.writer.write_all(&header_rest)?;
+
+        let mut old_end = old.total_header_size as i64;
+        for &i in &order {
+            let gap = (source.entries[i].serial_offset - old_end).max(0) as usize;
+            let mut gap_bytes = vec![0u8; gap];
+            source.reader.seek(SeekFrom::Start(old_end as u64))?;
+            source.reader.read_exact(&mut gap_bytes)?;
+            self.writer.write_all(&gap_bytes)?;
+            self.writer.write_all(&export_data[i])?;
+            old_end = source.entries[i].serial_offset + source.entries[i].serial_size;
+        }
+
+        // Anything after the exports, e.g. bulk data and the package trailer
+        source
+            .reader
+            .seek(SeekFrom::Start(old_exports_end as u64))?;
+        let mut tail = vec![];
+        source.reader.read_to_end(&mut tail)?;
+        self.writer.write_all(&tail)?;
+
+        self.summary = summary;
+        Ok(())
+    }
+
+    fn serialized_size<F>(summary: UassetSummary, writer_fn: F) -> Result<u64>
+    where
+        F: FnOnce(&mut UassetWriter<Cursor<Vec<u8>>>) -> Result<()>,
+    {
+        let mut writer = UassetWriter::new(Cursor::new(vec![]), summary);
+        writer_fn(&mut writer)?;
+        Ok(writer.writer.position())
+    }
+}
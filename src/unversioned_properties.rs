@@ -0,0 +1,307 @@
+use std::io::{Read, Seek};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::errors::{ParseError, Result};
+use crate::fname::FName;
+use crate::ftext::{FText, TextHistory};
+use crate::guid::Guid;
+use crate::package_index::PackageIndex;
+use crate::property_data::{FieldPath, PropertyData, PropertyValue, ScriptDelegate};
+use crate::property_tag::{EPropertyTagFlags, PropertyTag};
+use crate::property_type_name::PropertyTypeName;
+use crate::struct_value::{
+    Color, IntPoint, LinearColor, Quat, Rotator, SoftObjectPath, StructValue, Vector, Vector2D,
+    Vector4,
+};
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::usmap::UsmapProperty;
+
+/// Run of the schema in an unversioned header: `skip_num` properties left at
+/// their defaults, followed by `value_num` serialized ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UnversionedFragment {
+    pub skip_num: u8,
+    pub value_num: u8,
+    pub has_any_zeroes: bool,
+    pub is_last: bool,
+}
+
+/// Header written in place of property tags, it lists which properties of the
+/// schema follow and which of them are zero and therefore not in the stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UnversionedHeader {
+    pub fragments: Vec<UnversionedFragment>,
+    /// One entry per value of the fragments that have zeroes
+    pub zero_mask: Vec<bool>,
+}
+
+impl UnversionedFragment {
+    fn unpack(packed: u16) -> Self {
+        UnversionedFragment {
+            skip_num: (packed & 0x7f) as u8,
+            has_any_zeroes: packed & 0x80 != 0,
+            is_last: packed & 0x100 != 0,
+            value_num: (packed >> 9) as u8,
+        }
+    }
+}
+
+impl UnversionedHeader {
+    /// Schema indices of the serialized properties and whether each one is zero.
+    pub fn serialized_properties(&self) -> Vec<(usize, bool)> {
+        let mut properties = vec![];
+        let mut schema_index = 0;
+        let mut zero_mask_index = 0;
+
+        for fragment in &self.fragments {
+            schema_index += fragment.skip_num as usize;
+            for _ in 0..fragment.value_num {
+                let is_zero = fragment.has_any_zeroes
+                    && self
+                        .zero_mask
+                        .get(zero_mask_index)
+                        .copied()
+                        .unwrap_or(false);
+                if fragment.has_any_zeroes {
+                    zero_mask_index += 1;
+                }
+                properties.push((schema_index, is_zero));
+                schema_index += 1;
+            }
+        }
+
+        properties
+    }
+}
+
+impl<R: Read + Seek> Parsable<UnversionedHeader> for UassetParser<R> {
+    fn parse(&mut self) -> Result<UnversionedHeader> {
+        let mut fragments = vec![];
+        let mut zero_mask_num = 0;
+
+        loop {
+            let fragment = UnversionedFragment::unpack(self.reader.read_u16::<LittleEndian>()?);
+            if fragment.has_any_zeroes {
+                zero_mask_num += fragment.value_num as usize;
+            }
+            fragments.push(fragment);
+            if fragment.is_last {
+                break;
+            }
+        }
+
+        // The mask is stored in the smallest of u8, u16 or a run of u32 words
+        let words: Vec<u32> = if zero_mask_num == 0 {
+            vec![]
+        } else if zero_mask_num <= 8 {
+            vec![self.reader.read_u8()? as u32]
+        } else if zero_mask_num <= 16 {
+            vec![self.reader.read_u16::<LittleEndian>()? as u32]
+        } else {
+            (0..zero_mask_num.div_ceil(32))
+                .map(|_| self.reader.read_u32::<LittleEndian>())
+                .collect::<std::io::Result<_>>()?
+        };
+        let zero_mask = (0..zero_mask_num)
+            .map(|i| words[i / 32] & (1 << (i % 32)) != 0)
+            .collect();
+
+        Ok(UnversionedHeader {
+            fragments,
+            zero_mask,
+        })
+    }
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Reads properties saved without tags, their names and types come from
+    /// the mappings schema of `struct_name`.
+    pub fn read_unversioned_properties(&mut self, struct_name: &str) -> Result<Vec<PropertyData>> {
+        let mappings = self.mappings.clone().ok_or(ParseError::MissingMappings)?;
+        let schema = mappings.flattened_properties(struct_name)?;
+
+        let header: UnversionedHeader = self.read()?;
+
+        let mut properties = vec![];
+        for (index, is_zero) in header.serialized_properties() {
+            let (property, array_index) =
+                schema.get(index).copied().flatten().ok_or_else(|| {
+                    ParseError::InvalidUnversionedPropertyIndex {
+                        struct_name: struct_name.to_string(),
+                        index,
+                    }
+                })?;
+
+            let start = self.reader.stream_position()?;
+            let value = if is_zero {
+                self.zero_property_value(&property.type_name)?
+            } else {
+                self.read_property_value(&property.type_name)?
+            };
+
+            let mut tag = Self::unversioned_tag(property, array_index);
+            tag.size = (self.reader.stream_position()? - start) as i32;
+            if value == PropertyValue::BoolProperty(true) {
+                tag.flags |= EPropertyTagFlags::BoolTrue as u8;
+            }
+            properties.push(PropertyData { tag, value });
+        }
+
+        Ok(properties)
+    }
+
+    pub(crate) fn read_unversioned_enum(
+        &mut self,
+        type_name: &PropertyTypeName,
+    ) -> Result<PropertyValue> {
+        let underlying = type_name
+            .parameters
+            .get(1)
+            .cloned()
+            .unwrap_or_else(|| PropertyTypeName::new("ByteProperty"));
+
+        let value = match self.read_property_value(&underlying)? {
+            PropertyValue::ByteProperty(value) => value as i64,
+            PropertyValue::Int8Property(value) => value as i64,
+            PropertyValue::Int16Property(value) => value as i64,
+            PropertyValue::IntProperty(value) => value as i64,
+            PropertyValue::Int64Property(value) => value,
+            PropertyValue::UInt16Property(value) => value as i64,
+            PropertyValue::UInt32Property(value) => value as i64,
+            PropertyValue::UInt64Property(value) => value as i64,
+            _ => return Err(ParseError::UnsupportedPropertyType(type_name.to_string())),
+        };
+
+        Ok(PropertyValue::EnumProperty(
+            self.enum_value_name(type_name, value),
+        ))
+    }
+
+    /// Name of an enum value, or the number itself if the mappings don't know it.
+    fn enum_value_name(&self, type_name: &PropertyTypeName, value: i64) -> FName {
+        let name = type_name.enum_name().and_then(|enum_name| {
+            self.mappings
+                .as_ref()?
+                .enums
+                .get(enum_name)?
+                .value_name(value)
+                .map(str::to_string)
+        });
+        FName::new(name.unwrap_or_else(|| value.to_string()))
+    }
+
+    /// Value of a property flagged in the zero mask.
+    fn zero_property_value(&self, type_name: &PropertyTypeName) -> Result<PropertyValue> {
+        let value = match type_name.name.as_str() {
+            "BoolProperty" => PropertyValue::BoolProperty(false),
+            "Int8Property" => PropertyValue::Int8Property(0),
+            "Int16Property" => PropertyValue::Int16Property(0),
+            "IntProperty" => PropertyValue::IntProperty(0),
+            "Int64Property" => PropertyValue::Int64Property(0),
+            "UInt16Property" => PropertyValue::UInt16Property(0),
+            "UInt32Property" => PropertyValue::UInt32Property(0),
+            "UInt64Property" => PropertyValue::UInt64Property(0),
+            "FloatProperty" => PropertyValue::FloatProperty(0.0),
+            "DoubleProperty" => PropertyValue::DoubleProperty(0.0),
+            "ByteProperty" => PropertyValue::ByteProperty(0),
+            "EnumProperty" => PropertyValue::EnumProperty(self.enum_value_name(type_name, 0)),
+            "NameProperty" => PropertyValue::NameProperty(FName::new("None")),
+            "StrProperty" => PropertyValue::StrProperty(String::new()),
+            "Utf8StrProperty" => PropertyValue::Utf8StrProperty(String::new()),
+            "AnsiStrProperty" => PropertyValue::AnsiStrProperty(String::new()),
+            "TextProperty" => PropertyValue::TextProperty(FText {
+                flags: 0,
+                history: TextHistory::None {
+                    culture_invariant_string: None,
+                },
+            }),
+            "ObjectProperty" | "ClassProperty" | "WeakObjectProperty" | "InterfaceProperty" => {
+                PropertyValue::ObjectProperty(PackageIndex::default())
+            }
+            "LazyObjectProperty" => PropertyValue::LazyObjectProperty(Guid::default()),
+            "SoftObjectProperty" | "SoftClassProperty" => {
+                PropertyValue::SoftObjectProperty(Self::zero_soft_object_path())
+            }
+            "DelegateProperty" => PropertyValue::DelegateProperty(ScriptDelegate::default()),
+            "MulticastDelegateProperty"
+            | "MulticastInlineDelegateProperty"
+            | "MulticastSparseDelegateProperty" => PropertyValue::MulticastDelegateProperty(vec![]),
+            "FieldPathProperty" => PropertyValue::FieldPathProperty(FieldPath::default()),
+            "StructProperty" => {
+                let struct_name = type_name
+                    .struct_name()
+                    .ok_or_else(|| ParseError::UnsupportedPropertyType(type_name.to_string()))?;
+                PropertyValue::StructProperty(self.zero_struct_value(struct_name)?)
+            }
+            "ArrayProperty" => PropertyValue::ArrayProperty(vec![]),
+            "SetProperty" => PropertyValue::SetProperty {
+                elements: vec![],
+                removed: vec![],
+            },
+            "MapProperty" => PropertyValue::MapProperty {
+                entries: vec![],
+                removed: vec![],
+            },
+            other => return Err(ParseError::UnsupportedPropertyType(other.to_string())),
+        };
+
+        Ok(value)
+    }
+
+    fn zero_struct_value(&self, struct_name: &str) -> Result<StructValue> {
+        let value = match struct_name {
+            "Vector" => StructValue::Vector(Vector::default()),
+            "Vector2D" => StructValue::Vector2D(Vector2D::default()),
+            "Vector4" => StructValue::Vector4(Vector4::default()),
+            "Rotator" => StructValue::Rotator(Rotator::default()),
+            "Quat" => StructValue::Quat(Quat::default()),
+            "Guid" => StructValue::Guid(Guid::default()),
+            "Color" => StructValue::Color(Color::default()),
+            "LinearColor" => StructValue::LinearColor(LinearColor::default()),
+            "IntPoint" => StructValue::IntPoint(IntPoint::default()),
+            "DateTime" => StructValue::DateTime(0),
+            "Timespan" => StructValue::Timespan(0),
+            "SoftObjectPath" | "SoftClassPath" => {
+                StructValue::SoftObjectPath(Self::zero_soft_object_path())
+            }
+            "GameplayTagContainer" => StructValue::GameplayTagContainer(vec![]),
+            _ => {
+                // Other structs are zero field by field
+                let mappings = self.mappings.as_ref().ok_or(ParseError::MissingMappings)?;
+                let schema = mappings.flattened_properties(struct_name)?;
+
+                let mut properties = vec![];
+                for (property, array_index) in schema.into_iter().flatten() {
+                    properties.push(PropertyData {
+                        tag: Self::unversioned_tag(property, array_index),
+                        value: self.zero_property_value(&property.type_name)?,
+                    });
+                }
+                StructValue::Properties(properties)
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn zero_soft_object_path() -> SoftObjectPath {
+        SoftObjectPath {
+            package_name: FName::new("None"),
+            asset_name: Some(FName::new("None")),
+            sub_path: String::new(),
+        }
+    }
+
+    fn unversioned_tag(property: &UsmapProperty, array_index: u32) -> PropertyTag {
+        let mut tag = PropertyTag::new(FName::new(property.name.as_str()));
+        tag.type_name = property.type_name.clone();
+        tag.array_index = array_index as i32;
+        if array_index != 0 {
+            tag.flags |= EPropertyTagFlags::HasArrayIndex as u8;
+        }
+        tag
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::custom_version::CustomVersion;
+use crate::errors::{ParseError, Result};
+use crate::guid::Guid;
+use crate::property_type_name::{MAX_TYPE_NAME_DEPTH, PropertyTypeName};
+use crate::versions::PackageFileVersion;
+
+const USMAP_MAGIC: u16 = 0x30C4;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EUsmapVersion {
+    Initial = 0,
+    // Adds the package and custom versions the mappings were generated with
+    PackageVersioning,
+    // Names are prefixed with a u16 length instead of a u8
+    LongFName,
+    // Enum entry counts are u16 instead of u8
+    LargeEnums,
+    // Enum entries store their value next to their name
+    ExplicitEnumValues,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EUsmapCompressionMethod {
+    None = 0,
+    Oodle = 1,
+    Brotli = 2,
+    ZStandard = 3,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EUsmapPropertyType {
+    ByteProperty = 0,
+    BoolProperty,
+    IntProperty,
+    FloatProperty,
+    ObjectProperty,
+    NameProperty,
+    DelegateProperty,
+    DoubleProperty,
+    ArrayProperty,
+    StructProperty,
+    StrProperty,
+    TextProperty,
+    InterfaceProperty,
+    MulticastDelegateProperty,
+    WeakObjectProperty,
+    LazyObjectProperty,
+    AssetObjectProperty,
+    SoftObjectProperty,
+    UInt64Property,
+    UInt32Property,
+    UInt16Property,
+    Int64Property,
+    Int16Property,
+    Int8Property,
+    MapProperty,
+    SetProperty,
+    EnumProperty,
+    FieldPathProperty,
+    OptionalProperty,
+    Utf8StrProperty,
+    AnsiStrProperty,
+}
+
+const USMAP_PROPERTY_TYPES: &[&str] = &[
+    "ByteProperty",
+    "BoolProperty",
+    "IntProperty",
+    "FloatProperty",
+    "ObjectProperty",
+    "NameProperty",
+    "DelegateProperty",
+    "DoubleProperty",
+    "ArrayProperty",
+    "StructProperty",
+    "StrProperty",
+    "TextProperty",
+    "InterfaceProperty",
+    "MulticastDelegateProperty",
+    "WeakObjectProperty",
+    "LazyObjectProperty",
+    "SoftObjectProperty",
+    "SoftObjectProperty",
+    "UInt64Property",
+    "UInt32Property",
+    "UInt16Property",
+    "Int64Property",
+    "Int16Property",
+    "Int8Property",
+    "MapProperty",
+    "SetProperty",
+    "EnumProperty",
+    "FieldPathProperty",
+    "OptionalProperty",
+    "Utf8StrProperty",
+    "AnsiStrProperty",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UsmapEnum {
+    pub name: String,
+    /// Entries as (value, name), older mappings number them in order
+    pub entries: Vec<(i64, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UsmapProperty {
+    /// Index of the first element within the properties of its struct
+    pub schema_index: u16,
+    pub array_size: u8,
+    pub name: String,
+    pub type_name: PropertyTypeName,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UsmapSchema {
+    pub name: String,
+    pub super_type: Option<String>,
+    /// Number of schema indices used by the struct, static arrays take one per element
+    pub property_count: u16,
+    pub properties: Vec<UsmapProperty>,
+}
+
+/// Property layouts dumped from a running game, needed to read packages with
+/// unversioned properties as those don't name or type their values.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Usmap {
+    pub version: u8,
+    pub file_version: Option<PackageFileVersion>,
+    pub custom_versions: Vec<CustomVersion>,
+    pub net_cl: u32,
+    pub enums: HashMap<String, UsmapEnum>,
+    pub schemas: HashMap<String, UsmapSchema>,
+}
+
+impl UsmapEnum {
+    pub fn value_name(&self, value: i64) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry_value, _)| *entry_value == value)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+impl Usmap {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Usmap> {
+        Usmap::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Usmap> {
+        let magic = reader.read_u16::<LittleEndian>()?;
+        if magic != USMAP_MAGIC {
+            return Err(ParseError::InvalidUsmapMagic(magic));
+        }
+
+        let version = reader.read_u8()?;
+        if version > EUsmapVersion::ExplicitEnumValues as u8 {
+            return Err(ParseError::UnsupportedUsmapVersion(version));
+        }
+
+        let mut usmap = Usmap {
+            version,
+            ..Default::default()
+        };
+
+        if version >= EUsmapVersion::PackageVersioning as u8
+            && reader.read_i32::<LittleEndian>()? != 0
+        {
+            usmap.file_version = Some(PackageFileVersion {
+                file_version_ue4: reader.read_i32::<LittleEndian>()?,
+                file_version_ue5: reader.read_i32::<LittleEndian>()?,
+            });
+            let count = reader.read_i32::<LittleEndian>()?;
+            if count < 0 {
+                return Err(ParseError::InvalidArraySize(count));
+            }
+            for _ in 0..count {
+                let key = Guid {
+                    a: reader.read_u32::<LittleEndian>()?,
+                    b: reader.read_u32::<LittleEndian>()?,
+                    c: reader.read_u32::<LittleEndian>()?,
+                    d: reader.read_u32::<LittleEndian>()?,
+                };
+                usmap.custom_versions.push(CustomVersion {
+                    key,
+                    version: reader.read_i32::<LittleEndian>()?,
+                });
+            }
+            usmap.net_cl = reader.read_u32::<LittleEndian>()?;
+        }
+
+        let compression_method = reader.read_u8()?;
+        let compressed_size = reader.read_u32::<LittleEndian>()? as usize;
+        let decompressed_size = reader.read_u32::<LittleEndian>()? as usize;
+
+        // Both sizes come from the file, check them before allocating for them
+        let position = reader.stream_position()?;
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(position))?;
+        if compressed_size as u64 > file_size.saturating_sub(position) {
+            return Err(ParseError::InvalidFileOffset {
+                offset: (position + compressed_size as u64) as i64,
+                file_size,
+            });
+        }
+        if compression_method == EUsmapCompressionMethod::None as u8
+            && decompressed_size != compressed_size
+        {
+            return Err(ParseError::DecompressionFailed(format!(
+                "expected {} bytes, got {}",
+                decompressed_size, compressed_size
+            )));
+        }
+
+        let mut compressed = vec![0u8; compressed_size];
+        reader.read_exact(&mut compressed)?;
+
+        let data = match compression_method {
+            m if m == EUsmapCompressionMethod::None as u8 => compressed,
+            m if m == EUsmapCompressionMethod::Brotli as u8 => decompress(
+                brotli_decompressor::Decompressor::new(compressed.as_slice(), 4096),
+                decompressed_size,
+            )?,
+            m if m == EUsmapCompressionMethod::ZStandard as u8 => decompress(
+                ruzstd::decoding::StreamingDecoder::new(compressed.as_slice())
+                    .map_err(|e| ParseError::DecompressionFailed(e.to_string()))?,
+                decompressed_size,
+            )?,
+            // Oodle is proprietary and has no Rust implementation
+            m => return Err(ParseError::UnsupportedUsmapCompression(m)),
+        };
+
+        if data.len() != decompressed_size {
+            return Err(ParseError::DecompressionFailed(format!(
+                "expected {} bytes, got {}",
+                decompressed_size,
+                data.len()
+            )));
+        }
+
+        usmap.read_mappings(&mut Cursor::new(data))?;
+        Ok(usmap)
+    }
+
+    /// Properties of a struct and its supers by schema index, static array
+    /// elements are listed with their index. Indices of properties the
+    /// mappings don't describe are left empty.
+    #[allow(clippy::type_complexity)]
+    pub fn flattened_properties(
+        &self,
+        struct_name: &str,
+    ) -> Result<Vec<Option<(&UsmapProperty, u32)>>> {
+        let mut chain: Vec<&UsmapSchema> = vec![];
+        let mut current = Some(struct_name);
+        while let Some(name) = current {
+            if chain.iter().any(|schema| schema.name == name) {
+                return Err(ParseError::CyclicUsmapSchema(struct_name.to_string()));
+            }
+            let schema = self
+                .schemas
+                .get(name)
+                .ok_or_else(|| ParseError::MissingSchema(name.to_string()))?;
+            chain.push(schema);
+            current = schema.super_type.as_deref();
+        }
+
+        let mut properties = vec![];
+        for schema in chain.into_iter().rev() {
+            let offset = properties.len();
+            properties.resize(offset + schema.property_count as usize, None);
+            for property in &schema.properties {
+                for array_index in 0..property.array_size as usize {
+                    let index = offset + property.schema_index as usize + array_index;
+                    if index < properties.len() {
+                        properties[index] = Some((property, array_index as u32));
+                    }
+                }
+            }
+        }
+
+        Ok(properties)
+    }
+
+    fn read_mappings(&mut self, reader: &mut Cursor<Vec<u8>>) -> Result<()> {
+        let name_count = reader.read_u32::<LittleEndian>()?;
+        let mut names = Vec::with_capacity(name_count.min(1 << 20) as usize);
+        for _ in 0..name_count {
+            let length = if self.version >= EUsmapVersion::LongFName as u8 {
+                reader.read_u16::<LittleEndian>()? as usize
+            } else {
+                reader.read_u8()? as usize
+            };
+            let mut buffer = vec![0u8; length];
+            reader.read_exact(&mut buffer)?;
+            names.push(String::from_utf8(buffer)?);
+        }
+
+        let enum_count = reader.read_u32::<LittleEndian>()?;
+        for _ in 0..enum_count {
+            let name = read_name(reader, &names)?;
+            let entry_count = if self.version >= EUsmapVersion::LargeEnums as u8 {
+                reader.read_u16::<LittleEndian>()? as usize
+            } else {
+                reader.read_u8()? as usize
+            };
+            let mut entries = Vec::with_capacity(entry_count);
+            for index in 0..entry_count {
+                let value = if self.version >= EUsmapVersion::ExplicitEnumValues as u8 {
+                    reader.read_i64::<LittleEndian>()?
+                } else {
+                    index as i64
+                };
+                entries.push((value, read_name(reader, &names)?));
+            }
+            self.enums.insert(name.clone(), UsmapEnum { name, entries });
+        }
+
+        let schema_count = reader.read_u32::<LittleEndian>()?;
+        for _ in 0..schema_count {
+            let name = read_name(reader, &names)?;
+            let super_type = read_optional_name(reader, &names)?;
+            let property_count = reader.read_u16::<LittleEndian>()?;
+            let serializable_count = reader.read_u16::<LittleEndian>()?;
+
+            let mut properties = Vec::with_capacity(serializable_count as usize);
+            for _ in 0..serializable_count {
+                properties.push(UsmapProperty {
+                    schema_index: reader.read_u16::<LittleEndian>()?,
+                    array_size: reader.read_u8()?,
+                    name: read_name(reader, &names)?,
+                    type_name: read_property_type(reader, &names, 0)?,
+                });
+            }
+
+            self.schemas.insert(
+                name.clone(),
+                UsmapSchema {
+                    name,
+                    super_type,
+                    property_count,
+                    properties,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Decompresses up to one byte past `decompressed_size`, enough to tell a
+/// wrong size without growing the buffer any further than the data does.
+fn decompress(decoder: impl Read, decompressed_size: usize) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    decoder
+        .take(decompressed_size as u64 + 1)
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn read_optional_name(reader: &mut Cursor<Vec<u8>>, names: &[String]) -> Result<Option<String>> {
+    let index = reader.read_i32::<LittleEndian>()?;
+    if index == -1 {
+        return Ok(None);
+    }
+    names
+        .get(index as usize)
+        .cloned()
+        .map(Some)
+        .ok_or(ParseError::InvalidUsmapNameIndex(index))
+}
+
+fn read_name(reader: &mut Cursor<Vec<u8>>, names: &[String]) -> Result<String> {
+    read_optional_name(reader, names)?.ok_or(ParseError::InvalidUsmapNameIndex(-1))
+}
+
+/// Reads a property type in the shape of the complete type names of property tags.
+fn read_property_type(
+    reader: &mut Cursor<Vec<u8>>,
+    names: &[String],
+    depth: usize,
+) -> Result<PropertyTypeName> {
+    if depth > MAX_TYPE_NAME_DEPTH {
+        return Err(ParseError::PropertyTypeTooDeep(MAX_TYPE_NAME_DEPTH));
+    }
+    let property_type = reader.read_u8()?;
+    let name = *USMAP_PROPERTY_TYPES
+        .get(property_type as usize)
+        .ok_or_else(|| ParseError::UnsupportedPropertyType(property_type.to_string()))?;
+
+    let parameters = match property_type {
+        t if t == EUsmapPropertyType::EnumProperty as u8 => {
+            let inner = read_property_type(reader, names, depth + 1)?;
+            let enum_name = read_name(reader, names)?;
+            vec![PropertyTypeName::new(enum_name), inner]
+        }
+        t if t == EUsmapPropertyType::StructProperty as u8 => {
+            vec![PropertyTypeName::new(read_name(reader, names)?)]
+        }
+        t if t == EUsmapPropertyType::ArrayProperty as u8
+            || t == EUsmapPropertyType::SetProperty as u8
+            || t == EUsmapPropertyType::OptionalProperty as u8 =>
+        {
+            vec![read_property_type(reader, names, depth + 1)?]
+        }
+        t if t == EUsmapPropertyType::MapProperty as u8 => vec![
+            read_property_type(reader, names, depth + 1)?,
+            read_property_type(reader, names, depth + 1)?,
+        ],
+        _ => vec![],
+    };
+
+    Ok(PropertyTypeName::with_parameters(name, parameters))
+}
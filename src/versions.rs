@@ -57,3 +57,12 @@ pub enum EUnrealEngineObjectUE5Version {
     // OS shadow serialization of subobjects
     OsSubObjectShadowSerialization,
 }
+
+/// Object versions a package was saved with. Unversioned packages don't store
+/// them and need them supplied from elsewhere, e.g. the mappings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PackageFileVersion {
+    pub file_version_ue4: i32,
+    pub file_version_ue5: i32,
+}
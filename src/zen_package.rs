@@ -0,0 +1,668 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+
+use crate::custom_version::CustomVersion;
+use crate::errors::{ParseError, Result};
+use crate::export_entry::ExportEntry;
+use crate::fname::FName;
+use crate::import_entry::ImportEntry;
+use crate::package_index::PackageIndex;
+use crate::uasset_parser::{Parsable, UassetParser};
+use crate::uasset_summary::UassetSummary;
+use crate::versions::{EUnrealEngineObjectUE5Version, PackageFileVersion};
+
+/// Size of the summary before dependency bundles replaced the graph data in 5.3
+const LEGACY_SUMMARY_SIZE: u64 = 44;
+const SUMMARY_SIZE: u64 = 52;
+const EXPORT_MAP_ENTRY_SIZE: i32 = 72;
+const MAX_NAMES: u32 = 10_000_000;
+
+#[repr(u64)]
+pub enum EPackageObjectIndexType {
+    Export = 0,
+    ScriptImport,
+    PackageImport,
+    Null,
+}
+
+#[repr(u32)]
+pub enum EExportCommandType {
+    Create = 0,
+    Serialize,
+}
+
+#[repr(u8)]
+pub enum EExportFilterFlags {
+    None = 0,
+    NotForClient,
+    NotForServer,
+}
+
+/// Global reference to an object: an export of the package, a script object
+/// or a public export of another package, which replaces the import table
+/// of legacy packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct PackageObjectIndex {
+    pub value: u64,
+}
+
+impl PackageObjectIndex {
+    const INDEX_BITS: u64 = 62;
+    const INDEX_MASK: u64 = (1 << Self::INDEX_BITS) - 1;
+
+    pub fn new(kind: EPackageObjectIndexType, index: u64) -> Self {
+        PackageObjectIndex {
+            value: (kind as u64) << Self::INDEX_BITS | (index & Self::INDEX_MASK),
+        }
+    }
+
+    pub fn null() -> Self {
+        PackageObjectIndex { value: u64::MAX }
+    }
+
+    pub fn kind(&self) -> u64 {
+        self.value >> Self::INDEX_BITS
+    }
+
+    pub fn index(&self) -> u64 {
+        self.value & Self::INDEX_MASK
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.kind() == EPackageObjectIndexType::Null as u64
+    }
+
+    pub fn is_script_import(&self) -> bool {
+        self.kind() == EPackageObjectIndexType::ScriptImport as u64
+    }
+
+    pub fn export_index(&self) -> Option<usize> {
+        (self.kind() == EPackageObjectIndexType::Export as u64).then_some(self.index() as usize)
+    }
+
+    /// Index into the imported packages and into the imported public export
+    /// hashes for imports from other packages.
+    pub fn package_import(&self) -> Option<(usize, usize)> {
+        (self.kind() == EPackageObjectIndexType::PackageImport as u64).then_some((
+            (self.index() >> 32) as usize,
+            (self.index() & 0xffffffff) as usize,
+        ))
+    }
+}
+
+impl<R: Read + Seek> Parsable<PackageObjectIndex> for UassetParser<R> {
+    fn parse(&mut self) -> Result<PackageObjectIndex> {
+        let value = self.reader.read_u64::<LittleEndian>()?;
+        Ok(PackageObjectIndex { value })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ZenPackageSummary {
+    pub has_versioning_info: bool,
+    pub header_size: u32,
+    pub name: FName,
+    pub package_flags: u32,
+    pub cooked_header_size: u32,
+    pub imported_public_export_hashes_offset: i32,
+    pub import_map_offset: i32,
+    pub export_map_offset: i32,
+    pub export_bundle_entries_offset: i32,
+    /// Before 5.3
+    pub graph_data_offset: Option<i32>,
+    /// Since 5.3
+    pub dependency_bundle_headers_offset: Option<i32>,
+    pub dependency_bundle_entries_offset: Option<i32>,
+    pub imported_package_names_offset: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ZenPackageVersioningInfo {
+    pub zen_version: u32,
+    pub file_version: PackageFileVersion,
+    pub licensee_version: i32,
+    pub custom_versions: Vec<CustomVersion>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ZenExportMapEntry {
+    /// Offset of the export in the package as cooked, header included
+    pub cooked_serial_offset: u64,
+    pub cooked_serial_size: u64,
+    pub object_name: FName,
+    pub outer_index: PackageObjectIndex,
+    pub class_index: PackageObjectIndex,
+    pub super_index: PackageObjectIndex,
+    pub template_index: PackageObjectIndex,
+    pub public_export_hash: u64,
+    pub object_flags: u32,
+    pub filter_flags: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ZenExportBundleEntry {
+    pub local_export_index: u32,
+    pub command_type: u32,
+}
+
+/// Header of a package stored in an IoStore container. Parsing it also fills
+/// the summary, names, imports and exports of the parser the way a legacy
+/// package would.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ZenPackageHeader {
+    pub summary: ZenPackageSummary,
+    pub versioning_info: Option<ZenPackageVersioningInfo>,
+    pub imported_public_export_hashes: Vec<u64>,
+    pub import_map: Vec<PackageObjectIndex>,
+    pub export_map: Vec<ZenExportMapEntry>,
+    pub export_bundle_entries: Vec<ZenExportBundleEntry>,
+    /// Packages the imports come from, only stored since 5.3
+    pub imported_package_names: Vec<FName>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptObjectEntry {
+    pub object_name: FName,
+    pub global_index: PackageObjectIndex,
+    pub outer_index: PackageObjectIndex,
+    /// Class of the object for class default objects
+    pub cdo_class_index: PackageObjectIndex,
+}
+
+/// Objects of the engine and game modules that packages import, stored once
+/// in the global container instead of in every package.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptObjects {
+    pub entries: HashMap<PackageObjectIndex, ScriptObjectEntry>,
+}
+
+impl ScriptObjects {
+    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<ScriptObjects> {
+        let names = read_name_batch(&mut reader)?;
+        let count = reader.read_i32::<LittleEndian>()?;
+        if count < 0 {
+            return Err(ParseError::InvalidArraySize(count));
+        }
+
+        let mut entries = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let object_name = read_mapped_name(&mut reader, &names)?;
+            let mut read_index = || -> Result<PackageObjectIndex> {
+                Ok(PackageObjectIndex {
+                    value: reader.read_u64::<LittleEndian>()?,
+                })
+            };
+            let entry = ScriptObjectEntry {
+                object_name,
+                global_index: read_index()?,
+                outer_index: read_index()?,
+                cdo_class_index: read_index()?,
+            };
+            entries.insert(entry.global_index, entry);
+        }
+        Ok(ScriptObjects { entries })
+    }
+
+    pub fn get(&self, index: PackageObjectIndex) -> Option<&ScriptObjectEntry> {
+        self.entries.get(&index)
+    }
+
+    /// Path of the object, e.g. `/Script/Engine.DataTable`.
+    pub fn object_path(&self, index: PackageObjectIndex) -> Option<String> {
+        // Every object appears at most once in a chain of outers, a longer one
+        // is a cycle
+        let mut object = self.get(index)?;
+        let mut names = vec![object.object_name.as_string()];
+        while !object.outer_index.is_null() {
+            if names.len() == self.entries.len() {
+                return None;
+            }
+            object = self.get(object.outer_index)?;
+            names.push(object.object_name.as_string());
+        }
+        names.reverse();
+        Some(names.join("."))
+    }
+}
+
+/// Reads a batch of names as saved by the engine: the lengths of all names
+/// first, then their characters.
+pub(crate) fn read_name_batch<R: Read + Seek>(reader: &mut R) -> Result<Vec<String>> {
+    let count = reader.read_u32::<LittleEndian>()?;
+    if count == 0 {
+        return Ok(vec![]);
+    }
+    if count > MAX_NAMES {
+        return Err(ParseError::InvalidArraySize(count as i32));
+    }
+
+    let _string_bytes = reader.read_u32::<LittleEndian>()?;
+    let _hash_version = reader.read_u64::<LittleEndian>()?;
+    // Hashes of the lowercase names, only used for lookups by the engine
+    reader.seek(SeekFrom::Current(count as i64 * 8))?;
+
+    let mut headers = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        headers.push(reader.read_u16::<BigEndian>()?);
+    }
+
+    let mut names = Vec::with_capacity(count as usize);
+    for header in headers {
+        let len = (header & 0x7fff) as usize;
+        if header & 0x8000 != 0 {
+            // Wide names are aligned to their character size
+            if reader.stream_position()? % 2 == 1 {
+                reader.seek(SeekFrom::Current(1))?;
+            }
+            let mut units = vec![0u16; len];
+            reader.read_u16_into::<LittleEndian>(&mut units)?;
+            names.push(String::from_utf16(&units).map_err(|_| ParseError::InvalidUtf16)?);
+        } else {
+            let mut buffer = vec![0u8; len];
+            reader.read_exact(&mut buffer)?;
+            names.push(String::from_utf8(buffer)?);
+        }
+    }
+    Ok(names)
+}
+
+/// Name stored as an index into a name batch, the top bits of the index
+/// tell which batch.
+fn read_mapped_name<R: Read>(reader: &mut R, names: &[String]) -> Result<FName> {
+    const INDEX_MASK: u32 = (1 << 30) - 1;
+    let index = reader.read_u32::<LittleEndian>()? & INDEX_MASK;
+    let number = reader.read_i32::<LittleEndian>()?;
+    Ok(FName::from_table(names, index as i32, number))
+}
+
+impl<R: Read + Seek> Parsable<ZenExportMapEntry> for UassetParser<R> {
+    fn parse(&mut self) -> Result<ZenExportMapEntry> {
+        let cooked_serial_offset = self.reader.read_u64::<LittleEndian>()?;
+        let cooked_serial_size = self.reader.read_u64::<LittleEndian>()?;
+        let object_name = read_mapped_name(&mut self.reader, &self.names)?;
+        let outer_index = self.read()?;
+        let class_index = self.read()?;
+        let super_index = self.read()?;
+        let template_index = self.read()?;
+        let public_export_hash = self.reader.read_u64::<LittleEndian>()?;
+        let object_flags = self.reader.read_u32::<LittleEndian>()?;
+        let filter_flags = self.reader.read_u8()?;
+        self.skip_bytes(3)?;
+
+        Ok(ZenExportMapEntry {
+            cooked_serial_offset,
+            cooked_serial_size,
+            object_name,
+            outer_index,
+            class_index,
+            super_index,
+            template_index,
+            public_export_hash,
+            object_flags,
+            filter_flags,
+        })
+    }
+}
+
+impl<R: Read + Seek> UassetParser<R> {
+    /// Parses the header of a package read from an IoStore container, the
+    /// counterpart of [`parse_asset`](Self::parse_asset). Script imports are
+    /// only named when [`script_objects`](Self::script_objects) are set.
+    pub fn parse_zen_package(&mut self) -> Result<()> {
+        self.reader.seek(SeekFrom::Start(0))?;
+        let has_versioning_info = self.reader.read_u32::<LittleEndian>()? != 0;
+        let header_size = self.reader.read_u32::<LittleEndian>()?;
+        let name_index = self.reader.read_u32::<LittleEndian>()?;
+        let name_number = self.reader.read_i32::<LittleEndian>()?;
+        let package_flags = self.reader.read_u32::<LittleEndian>()?;
+        let cooked_header_size = self.reader.read_u32::<LittleEndian>()?;
+        let mut offsets = [0i32; 7];
+        self.reader.read_i32_into::<LittleEndian>(&mut offsets)?;
+
+        // The summary doesn't say which layout it has, the object version does
+        let has_dependency_bundles = |version: &PackageFileVersion| {
+            version.file_version_ue5 >= EUnrealEngineObjectUE5Version::DataResources as i32
+        };
+        let (versioning_info, dependency_bundles) = if has_versioning_info {
+            self.reader.seek(SeekFrom::Start(SUMMARY_SIZE))?;
+            let info = self.read_zen_versioning_info()?;
+            if has_dependency_bundles(&info.file_version) {
+                (Some(info), true)
+            } else {
+                self.reader.seek(SeekFrom::Start(LEGACY_SUMMARY_SIZE))?;
+                (Some(self.read_zen_versioning_info()?), false)
+            }
+        } else {
+            let version = self
+                .unversioned_file_version
+                .or_else(|| self.mappings.as_ref().and_then(|m| m.file_version))
+                .ok_or(ParseError::MissingUnversionedFileVersion)?;
+            let dependency_bundles = has_dependency_bundles(&version);
+            let summary_size = if dependency_bundles {
+                SUMMARY_SIZE
+            } else {
+                LEGACY_SUMMARY_SIZE
+            };
+            self.reader.seek(SeekFrom::Start(summary_size))?;
+            (None, dependency_bundles)
+        };
+
+        let name_offset = self.reader.stream_position()?;
+        self.names = read_name_batch(&mut self.reader)?;
+
+        let [
+            imported_public_export_hashes_offset,
+            import_map_offset,
+            export_map_offset,
+            export_bundle_entries_offset,
+            ..,
+        ] = offsets;
+        let summary = ZenPackageSummary {
+            has_versioning_info,
+            header_size,
+            name: FName::from_table(&self.names, name_index as i32, name_number),
+            package_flags,
+            cooked_header_size,
+            imported_public_export_hashes_offset,
+            import_map_offset,
+            export_map_offset,
+            export_bundle_entries_offset,
+            graph_data_offset: (!dependency_bundles).then_some(offsets[4]),
+            dependency_bundle_headers_offset: dependency_bundles.then_some(offsets[4]),
+            dependency_bundle_entries_offset: dependency_bundles.then_some(offsets[5]),
+            imported_package_names_offset: dependency_bundles.then_some(offsets[6]),
+        };
+
+        let mut header = ZenPackageHeader {
+            versioning_info,
+            ..Default::default()
+        };
+
+        self.reader
+            .seek(SeekFrom::Start(imported_public_export_hashes_offset as u64))?;
+        for _ in 0..(import_map_offset - imported_public_export_hashes_offset).max(0) / 8 {
+            header
+                .imported_public_export_hashes
+                .push(self.reader.read_u64::<LittleEndian>()?);
+        }
+
+        for _ in 0..(export_map_offset - import_map_offset).max(0) / 8 {
+            header.import_map.push(self.read()?);
+        }
+
+        let export_count =
+            (export_bundle_entries_offset - export_map_offset).max(0) / EXPORT_MAP_ENTRY_SIZE;
+        self.reader
+            .seek(SeekFrom::Start(export_map_offset as u64))?;
+        for _ in 0..export_count {
+            header.export_map.push(self.read()?);
+        }
+
+        let bundle_entries_end = offsets[4];
+        self.reader
+            .seek(SeekFrom::Start(export_bundle_entries_offset as u64))?;
+        for _ in 0..(bundle_entries_end - export_bundle_entries_offset).max(0) / 8 {
+            header.export_bundle_entries.push(ZenExportBundleEntry {
+                local_export_index: self.reader.read_u32::<LittleEndian>()?,
+                command_type: self.reader.read_u32::<LittleEndian>()?,
+            });
+        }
+
+        if let Some(offset) = summary.imported_package_names_offset
+            && (offset as u32) < header_size
+        {
+            self.reader.seek(SeekFrom::Start(offset as u64))?;
+            for name in read_name_batch(&mut self.reader)? {
+                let number = self.reader.read_i32::<LittleEndian>()?;
+                header
+                    .imported_package_names
+                    .push(FName::from_table(&[name], 0, number));
+            }
+        }
+        header.summary = summary;
+
+        let (imports, import_positions) = self.zen_imports(&header);
+        let entries = self.zen_exports(&header, &import_positions);
+        let file_version = header
+            .versioning_info
+            .as_ref()
+            .map(|info| info.file_version)
+            .or(self.unversioned_file_version)
+            .or_else(|| self.mappings.as_ref().and_then(|m| m.file_version))
+            .unwrap_or_default();
+
+        self.set_summary(UassetSummary {
+            file_version_ue4: file_version.file_version_ue4,
+            file_version_ue5: file_version.file_version_ue5,
+            file_version_licensee_ue4: header
+                .versioning_info
+                .as_ref()
+                .map_or(0, |info| info.licensee_version as u32),
+            unversioned: header.versioning_info.is_none(),
+            total_header_size: header_size as i32,
+            custom_versions: header
+                .versioning_info
+                .as_ref()
+                .map(|info| info.custom_versions.clone())
+                .unwrap_or_default(),
+            package_name: header.summary.name.as_string(),
+            package_flags,
+            name_count: self.names.len() as i32,
+            name_offset: name_offset as i32,
+            export_count: entries.len() as i32,
+            export_offset: export_map_offset,
+            import_count: header.import_map.len() as i32,
+            import_offset: import_map_offset,
+            ..Default::default()
+        });
+        self.imports = imports;
+        self.entries = entries;
+        self.soft_object_paths = vec![];
+        self.zen_header = Some(header);
+        Ok(())
+    }
+
+    fn read_zen_versioning_info(&mut self) -> Result<ZenPackageVersioningInfo> {
+        Ok(ZenPackageVersioningInfo {
+            zen_version: self.reader.read_u32::<LittleEndian>()?,
+            file_version: PackageFileVersion {
+                file_version_ue4: self.reader.read_i32::<LittleEndian>()?,
+                file_version_ue5: self.reader.read_i32::<LittleEndian>()?,
+            },
+            licensee_version: self.reader.read_i32::<LittleEndian>()?,
+            custom_versions: self.read_tarray(|parser| parser.read(), 100000)?,
+        })
+    }
+
+    /// Import table standing in for the import map, one import per entry of
+    /// the map followed by the outers the map doesn't list. Objects of other
+    /// packages are only known by their public export hash.
+    fn zen_imports(
+        &self,
+        header: &ZenPackageHeader,
+    ) -> (Vec<ImportEntry>, HashMap<PackageObjectIndex, usize>) {
+        let mut imports = vec![
+            unresolved_import(FName::new("None"), PackageIndex::new(0));
+            header.import_map.len()
+        ];
+        let mut positions = HashMap::new();
+        for (i, index) in header.import_map.iter().enumerate() {
+            if !index.is_null() {
+                positions.entry(*index).or_insert(i);
+            }
+        }
+
+        let mut packages = HashMap::new();
+        for (i, &index) in header.import_map.iter().enumerate() {
+            if index.is_script_import() {
+                imports[i] = self.script_import(index, &mut imports, &mut positions);
+            } else if let Some((package, hash)) = index.package_import() {
+                let outer_index = match header.imported_package_names.get(package) {
+                    Some(package_name) => {
+                        let position =
+                            *packages.entry(package_name.as_string()).or_insert_with(|| {
+                                imports.push(package_import(package_name.clone()));
+                                imports.len() - 1
+                            });
+                        PackageIndex::from_import(position)
+                    }
+                    None => PackageIndex::new(0),
+                };
+                let name = header.imported_public_export_hashes.get(hash).map_or_else(
+                    || FName::new("None"),
+                    |hash| FName::new(format!("{:016X}", hash)),
+                );
+                imports[i] = unresolved_import(name, outer_index);
+            }
+        }
+        (imports, positions)
+    }
+
+    fn script_import(
+        &self,
+        index: PackageObjectIndex,
+        imports: &mut Vec<ImportEntry>,
+        positions: &mut HashMap<PackageObjectIndex, usize>,
+    ) -> ImportEntry {
+        let Some(object) = self.script_objects.as_ref().and_then(|s| s.get(index)) else {
+            return unresolved_import(FName::new("None"), PackageIndex::new(0));
+        };
+        if object.outer_index.is_null() {
+            return package_import(object.object_name.clone());
+        }
+
+        let outer_index = match positions.get(&object.outer_index) {
+            Some(&position) => PackageIndex::from_import(position),
+            None => {
+                let position = imports.len();
+                imports.push(unresolved_import(FName::new("None"), PackageIndex::new(0)));
+                positions.insert(object.outer_index, position);
+                imports[position] = self.script_import(object.outer_index, imports, positions);
+                PackageIndex::from_import(position)
+            }
+        };
+
+        // Script objects don't record their class, only class default objects
+        // point at theirs
+        let script_objects = self.script_objects.as_ref().unwrap();
+        let (class_package, class_name) = match script_objects.get(object.cdo_class_index) {
+            Some(class) => (
+                script_objects
+                    .object_path(class.outer_index)
+                    .unwrap_or_else(|| "None".to_string()),
+                class.object_name.clone(),
+            ),
+            None => ("/Script/CoreUObject".to_string(), FName::new("Class")),
+        };
+
+        ImportEntry {
+            class_package: FName::new(class_package),
+            class_name,
+            outer_index,
+            object_name: object.object_name.clone(),
+            package_name: None,
+            import_optional: false,
+        }
+    }
+
+    /// Export table with the offsets of the exports in the package data.
+    fn zen_exports(
+        &self,
+        header: &ZenPackageHeader,
+        import_positions: &HashMap<PackageObjectIndex, usize>,
+    ) -> Vec<ExportEntry> {
+        let package_index = |index: PackageObjectIndex| {
+            if let Some(export_index) = index.export_index() {
+                PackageIndex::from_export(export_index)
+            } else {
+                import_positions
+                    .get(&index)
+                    .map(|&position| PackageIndex::from_import(position))
+                    .unwrap_or_default()
+            }
+        };
+
+        // Since 5.3 exports keep their cooked layout, before that they follow
+        // the order of the export bundle
+        let header_size = header.summary.header_size as u64;
+        let mut serial_offsets = vec![0u64; header.export_map.len()];
+        if header.summary.graph_data_offset.is_none() {
+            for (offset, export) in serial_offsets.iter_mut().zip(&header.export_map) {
+                *offset = (header_size + export.cooked_serial_offset)
+                    .saturating_sub(header.summary.cooked_header_size as u64);
+            }
+        } else {
+            let mut offset = header_size;
+            for entry in &header.export_bundle_entries {
+                let index = entry.local_export_index as usize;
+                if entry.command_type == EExportCommandType::Serialize as u32
+                    && let Some(export) = header.export_map.get(index)
+                {
+                    serial_offsets[index] = offset;
+                    offset += export.cooked_serial_size;
+                }
+            }
+        }
+
+        header
+            .export_map
+            .iter()
+            .zip(serial_offsets)
+            .map(|(export, serial_offset)| ExportEntry {
+                class_index: package_index(export.class_index),
+                super_index: package_index(export.super_index),
+                template_index: package_index(export.template_index),
+                outer_index: package_index(export.outer_index),
+                object_name: export.object_name.clone(),
+                object_flags: export.object_flags as i32,
+                serial_size: export.cooked_serial_size as i64,
+                serial_offset: serial_offset as i64,
+                force_export: false,
+                not_for_client: export.filter_flags == EExportFilterFlags::NotForClient as u8,
+                not_for_server: export.filter_flags == EExportFilterFlags::NotForServer as u8,
+                is_inherited_instance: false,
+                package_flags: 0,
+                not_always_loaded_for_editor_game: false,
+                is_asset: false,
+                generate_public_hash: export.public_export_hash != 0,
+                first_export_dependency: -1,
+                serialization_before_serialization_dependencies: 0,
+                create_before_serialization_dependencies: 0,
+                serialization_before_create_dependencies: 0,
+                create_before_create_dependencies: 0,
+                script_serialization_start_offset: 0,
+                script_serialization_end_offset: 0,
+            })
+            .collect()
+    }
+}
+
+fn package_import(package_name: FName) -> ImportEntry {
+    ImportEntry {
+        class_package: FName::new("/Script/CoreUObject"),
+        class_name: FName::new("Package"),
+        outer_index: PackageIndex::new(0),
+        object_name: package_name,
+        package_name: None,
+        import_optional: false,
+    }
+}
+
+fn unresolved_import(object_name: FName, outer_index: PackageIndex) -> ImportEntry {
+    ImportEntry {
+        class_package: FName::new("None"),
+        class_name: FName::new("None"),
+        outer_index,
+        object_name,
+        package_name: None,
+        import_optional: false,
+    }
+}
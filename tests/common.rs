@@ -1,8 +1,135 @@
+#![allow(dead_code)]
+
+use std::io::Cursor;
 use std::path::PathBuf;
 
+use aes::Aes256;
+use aes::cipher::{BlockEncrypt, KeyInit, generic_array::GenericArray};
+use rust_uasset::property_data::PropertyData;
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::UassetSummary;
+
 pub fn test_data_path(filename: &str) -> PathBuf {
     std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("tests")
         .join("data")
         .join(filename)
 }
+
+/// Appends `value` as a null terminated FString.
+pub fn fstring(data: &mut Vec<u8>, value: &str) {
+    data.extend((value.len() as i32 + 1).to_le_bytes());
+    data.extend(value.as_bytes());
+    data.push(0);
+}
+
+/// Encrypts `data` with AES-256 as pak and IoStore files are, padded to
+/// whole blocks.
+pub fn encrypt(data: &mut Vec<u8>, key: &[u8; 32]) {
+    data.resize(data.len().next_multiple_of(16), 0);
+    let cipher = Aes256::new(&GenericArray::from(*key));
+    for block in data.chunks_exact_mut(16) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    }
+}
+
+/// Builds tagged property streams in the UE 5.4 layout for tests.
+#[derive(Default)]
+pub struct TaggedPropertyBuilder {
+    pub names: Vec<String>,
+    pub data: Vec<u8>,
+}
+
+impl TaggedPropertyBuilder {
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        let index = match self.names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        };
+        self.data.extend_from_slice(&(index as i32).to_le_bytes());
+        self.data.extend_from_slice(&0i32.to_le_bytes());
+        self
+    }
+
+    /// `type_name` is the pre-order list of (name, parameter count) nodes.
+    pub fn tag(
+        &mut self,
+        name: &str,
+        type_name: &[(&str, i32)],
+        size: i32,
+        flags: u8,
+    ) -> &mut Self {
+        self.name(name);
+        for (node, inner_count) in type_name {
+            self.name(node);
+            self.data.extend_from_slice(&inner_count.to_le_bytes());
+        }
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.push(flags);
+        self
+    }
+
+    /// Writes a tag followed by its value, the tag size is patched in once
+    /// `value` has written the payload.
+    pub fn property(
+        &mut self,
+        name: &str,
+        type_name: &[(&str, i32)],
+        flags: u8,
+        value: impl FnOnce(&mut Self),
+    ) -> &mut Self {
+        self.tag(name, type_name, 0, flags);
+        let size_offset = self.data.len() - 5;
+
+        let value_start = self.data.len();
+        value(self);
+        let size = (self.data.len() - value_start) as i32;
+        self.data[size_offset..size_offset + 4].copy_from_slice(&size.to_le_bytes());
+        self
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn fstring(&mut self, value: &str) -> &mut Self {
+        fstring(&mut self.data, value);
+        self
+    }
+
+    pub fn none(&mut self) -> &mut Self {
+        self.name("None")
+    }
+}
+
+/// Reads the properties of `builder` up to the terminating `None`, as a UE 5.4
+/// package would.
+pub fn read_properties(builder: &TaggedPropertyBuilder) -> Vec<PropertyData> {
+    read_versioned_properties(builder, 1012)
+}
+
+pub fn read_versioned_properties(
+    builder: &TaggedPropertyBuilder,
+    file_version_ue5: i32,
+) -> Vec<PropertyData> {
+    let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+    parser.names = builder.names.clone();
+    parser.set_summary(UassetSummary {
+        file_version_ue5,
+        ..Default::default()
+    });
+
+    let mut properties = vec![];
+    loop {
+        let property: PropertyData = parser.read().expect("managed to read property");
+        if property.tag.name.is_none() {
+            break;
+        }
+        properties.push(property);
+    }
+    properties
+}
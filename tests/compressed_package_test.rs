@@ -0,0 +1,211 @@
+use std::io::{Cursor, Read, Write};
+use std::sync::Arc;
+
+use common::test_data_path;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use rust_uasset::compressed_package::{
+    COMPRESSED_CHUNK_TAG, CompressedPackageReader, Decompressor, ECompressionFlags,
+};
+use rust_uasset::errors::{ParseError, Result};
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::CompressedChunk;
+use rust_uasset::uasset_writer::UassetWriter;
+
+mod common;
+
+const BLOCK_SIZE: usize = 256;
+
+/// Stores the blocks reversed, for the packages of the custom format.
+struct ReverseDecompressor;
+
+impl Decompressor for ReverseDecompressor {
+    fn decompress(&self, compressed: &[u8], _uncompressed_size: usize) -> Result<Vec<u8>> {
+        Ok(compressed.iter().rev().copied().collect())
+    }
+}
+
+fn zlib(block: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(block).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn reverse(block: &[u8]) -> Vec<u8> {
+    block.iter().rev().copied().collect()
+}
+
+/// Saves the package again with everything after the summary in one
+/// compressed chunk, the summary grows by the chunk so every offset moves.
+fn compressed_package(
+    data: &[u8],
+    flags: ECompressionFlags,
+    compress: fn(&[u8]) -> Vec<u8>,
+) -> Vec<u8> {
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+    let old = parser.get_summary().clone();
+    let shift = 16;
+
+    let mut summary = old.clone();
+    for offset in [
+        &mut summary.total_header_size,
+        &mut summary.name_offset,
+        &mut summary.export_offset,
+        &mut summary.import_offset,
+        &mut summary.depends_offset,
+        &mut summary.soft_package_references_offset,
+        &mut summary.asset_registry_data_offset,
+        &mut summary.preload_dependency_offset,
+    ] {
+        if *offset > 0 {
+            *offset += shift;
+        }
+    }
+    for offset in [
+        &mut summary.soft_object_paths_offset,
+        &mut summary.metadata_offset,
+        &mut summary.data_resource_offset,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if *offset > 0 {
+            *offset += shift;
+        }
+    }
+    summary.bulk_data_start_offset += shift as i64;
+
+    let mut body = data[old.name_offset as usize..].to_vec();
+    let mut exports = UassetWriter::new(Cursor::new(vec![]), old.clone());
+    for export in &parser.entries {
+        let mut export = export.clone();
+        export.serial_offset += shift as i64;
+        exports.write(&export).unwrap();
+    }
+    let exports = exports.writer.into_inner();
+    let exports_start = (old.export_offset - old.name_offset) as usize;
+    body[exports_start..exports_start + exports.len()].copy_from_slice(&exports);
+
+    let blocks: Vec<(Vec<u8>, usize)> = body
+        .chunks(BLOCK_SIZE)
+        .map(|block| (compress(block), block.len()))
+        .collect();
+    let compressed_size: usize = blocks.iter().map(|(block, _)| block.len()).sum();
+    let mut chunk = vec![];
+    chunk.extend(COMPRESSED_CHUNK_TAG.to_le_bytes());
+    chunk.extend((BLOCK_SIZE as i64).to_le_bytes());
+    chunk.extend((compressed_size as i64).to_le_bytes());
+    chunk.extend((body.len() as i64).to_le_bytes());
+    for (block, size) in &blocks {
+        chunk.extend((block.len() as i64).to_le_bytes());
+        chunk.extend((*size as i64).to_le_bytes());
+    }
+    for (block, _) in &blocks {
+        chunk.extend(block);
+    }
+
+    summary.compression_flags = flags as u32;
+    summary.compressed_chunks = vec![CompressedChunk {
+        uncompressed_offset: summary.name_offset,
+        uncompressed_size: body.len() as i32,
+        compressed_offset: summary.name_offset,
+        compressed_size: chunk.len() as i32,
+    }];
+    let mut writer = UassetWriter::new(Cursor::new(vec![]), summary.clone());
+    writer.write(&summary).unwrap();
+    let mut package = writer.writer.into_inner();
+    assert_eq!(package.len(), summary.name_offset as usize);
+    package.extend(chunk);
+    package
+}
+
+fn assert_data_table<R: std::io::Read + std::io::Seek>(parser: &mut UassetParser<R>) {
+    parser.parse_asset().expect("managed to parse the asset");
+    assert_eq!(parser.entries.len(), 3);
+    let data_table = parser
+        .read_export(1)
+        .expect("managed to read the data table")
+        .into_data_table()
+        .expect("export is a data table");
+    assert_eq!(data_table.rows.len(), 2);
+    assert_eq!(
+        data_table.rows[0]
+            .get_name("Tag")
+            .map(|tag| tag.as_string()),
+        Some("Tag.Foo.Bar".to_string())
+    );
+}
+
+#[test]
+fn test_read_compressed_package() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let package = compressed_package(&data, ECompressionFlags::Zlib, zlib);
+
+    let mut parser = UassetParser::new(Cursor::new(&package));
+    assert!(matches!(
+        parser.parse_asset(),
+        Err(ParseError::CompressedPackage)
+    ));
+    let chunk = parser.get_summary().compressed_chunks[0];
+    assert_eq!(chunk.uncompressed_offset, chunk.compressed_offset);
+    assert_eq!(
+        chunk.uncompressed_size as usize,
+        data.len() - chunk.uncompressed_offset as usize + 16
+    );
+
+    let reader = CompressedPackageReader::new(Cursor::new(&package))
+        .expect("managed to read the compressed chunks");
+    let mut parser = UassetParser::new(reader);
+    assert_data_table(&mut parser);
+    assert_eq!(
+        parser.get_summary().package_name,
+        "/Game/P1/Dialogs/DT_MyTable"
+    );
+
+    let mut written = Cursor::new(vec![]);
+    let mut writer = UassetWriter::new(&mut written, parser.get_summary().clone());
+    assert!(matches!(
+        writer.write_package(&mut parser, &[vec![], vec![], vec![]]),
+        Err(ParseError::CompressedChunksNotSupported)
+    ));
+}
+
+#[test]
+fn test_read_custom_compressed_package() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let package = compressed_package(&data, ECompressionFlags::Custom, reverse);
+
+    assert!(matches!(
+        CompressedPackageReader::new(Cursor::new(&package)),
+        Err(ParseError::UnsupportedCompressionMethod(_))
+    ));
+
+    let reader = CompressedPackageReader::with_decompressor(
+        Cursor::new(&package),
+        Arc::new(ReverseDecompressor),
+    )
+    .expect("managed to read the compressed chunks");
+    assert_data_table(&mut UassetParser::new(reader));
+}
+
+#[test]
+fn test_read_compressed_chunk_out_of_bounds() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let package = compressed_package(&data, ECompressionFlags::Zlib, zlib);
+    let chunk = package
+        .windows(8)
+        .position(|bytes| bytes == COMPRESSED_CHUNK_TAG.to_le_bytes())
+        .expect("found the chunk");
+
+    // One byte blocks need more sizes than the chunk holds, and a block
+    // can't go past the end of the file
+    for (offset, value) in [(chunk + 8, 1), (chunk + 32, i64::MAX)] {
+        let mut package = package.clone();
+        package[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        let mut reader = CompressedPackageReader::new(Cursor::new(&package))
+            .expect("managed to read the compressed chunks");
+        let error = reader.read_to_end(&mut vec![]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,142 @@
+use std::io::Cursor;
+
+use common::TaggedPropertyBuilder;
+use rust_uasset::custom_version::{
+    CustomVersion, CustomVersionKey, FUE5ReleaseStreamObjectVersion,
+};
+use rust_uasset::ftext::{FText, FormatArgumentValue, TextHistory};
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::UassetSummary;
+
+mod common;
+
+fn read_text(builder: &TaggedPropertyBuilder) -> FText {
+    read_text_with_versions(builder, vec![])
+}
+
+fn read_text_with_versions(
+    builder: &TaggedPropertyBuilder,
+    custom_versions: Vec<CustomVersion>,
+) -> FText {
+    let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+    parser.names = builder.names.clone();
+    parser.set_summary(UassetSummary {
+        file_version_ue5: 1012,
+        custom_versions,
+        ..Default::default()
+    });
+    parser.read().expect("managed to read FText")
+}
+
+fn base_text<'a>(
+    builder: &'a mut TaggedPropertyBuilder,
+    key: &str,
+    source: &str,
+) -> &'a mut TaggedPropertyBuilder {
+    builder
+        .bytes(&0u32.to_le_bytes())
+        .bytes(&[0])
+        .fstring("Items")
+        .fstring(key)
+        .fstring(source)
+}
+
+#[test]
+fn test_named_format_text() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder.bytes(&0u32.to_le_bytes()).bytes(&[1]);
+    base_text(&mut builder, "Damage_Fmt", "Deals {Amount} damage");
+    builder
+        .bytes(&1i32.to_le_bytes())
+        .fstring("Amount")
+        .bytes(&[0])
+        .bytes(&25i64.to_le_bytes());
+
+    let text = read_text(&builder);
+
+    match &text.history {
+        TextHistory::NamedFormat { arguments, .. } => {
+            assert_eq!(
+                arguments[0],
+                ("Amount".to_string(), FormatArgumentValue::Int(25))
+            );
+        }
+        other => panic!("unexpected history {:?}", other),
+    }
+    assert_eq!(text.as_string(), "Deals 25 damage");
+}
+
+#[test]
+fn test_argument_format_integer_width() {
+    let argument_format = |value: &[u8]| {
+        let mut builder = TaggedPropertyBuilder::default();
+        builder.bytes(&0u32.to_le_bytes()).bytes(&[3]);
+        base_text(&mut builder, "Damage_Fmt", "Deals {Amount} damage");
+        builder
+            .bytes(&1i32.to_le_bytes())
+            .fstring("Amount")
+            .bytes(&[0])
+            .bytes(value);
+        builder
+    };
+
+    // Saved before the custom version widened the integers
+    let text = read_text(&argument_format(&25i32.to_le_bytes()));
+    assert_eq!(text.as_string(), "Deals 25 damage");
+
+    let text = read_text_with_versions(
+        &argument_format(&(1i64 << 40).to_le_bytes()),
+        vec![CustomVersion {
+            key: FUE5ReleaseStreamObjectVersion::KEY,
+            version: FUE5ReleaseStreamObjectVersion::TextFormatArgumentData64bitSupport as i32,
+        }],
+    );
+    match &text.history {
+        TextHistory::ArgumentFormat { arguments, .. } => {
+            assert_eq!(arguments[0].1, FormatArgumentValue::Int(1 << 40));
+        }
+        other => panic!("unexpected history {:?}", other),
+    }
+}
+
+#[test]
+fn test_string_table_and_number_texts() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .bytes(&0u32.to_le_bytes())
+        .bytes(&[11])
+        .name("/Game/Localization/ST_Items.ST_Items")
+        .fstring("Sword_Name");
+
+    let text = read_text(&builder);
+    match &text.history {
+        TextHistory::StringTableEntry { table_id, key } => {
+            assert_eq!(table_id.as_string(), "/Game/Localization/ST_Items.ST_Items");
+            assert_eq!(key, "Sword_Name");
+        }
+        other => panic!("unexpected history {:?}", other),
+    }
+
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .bytes(&0u32.to_le_bytes())
+        .bytes(&[5])
+        .bytes(&[3])
+        .bytes(&0.5f64.to_le_bytes())
+        .bytes(&0u32.to_le_bytes())
+        .fstring("en");
+
+    let text = read_text(&builder);
+    match &text.history {
+        TextHistory::AsPercent {
+            source_value,
+            format_options,
+            target_culture,
+        } => {
+            assert_eq!(**source_value, FormatArgumentValue::Double(0.5));
+            assert!(format_options.is_none());
+            assert_eq!(target_culture, "en");
+        }
+        other => panic!("unexpected history {:?}", other),
+    }
+}
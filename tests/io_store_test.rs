@@ -0,0 +1,444 @@
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+
+use common::{encrypt, fstring, test_data_path};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use rust_uasset::errors::ParseError;
+use rust_uasset::fname::FName;
+use rust_uasset::io_store::IoStoreReader;
+use rust_uasset::package_index::PackageIndex;
+use rust_uasset::pak::parse_key;
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::versions::EUnrealEngineObjectUE5Version;
+use rust_uasset::zen_package::{
+    EPackageObjectIndexType, PackageObjectIndex, ScriptObjectEntry, ScriptObjects,
+};
+
+mod common;
+
+const KEY: &str = "0x000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F";
+const BLOCK_SIZE: usize = 1024;
+const PACKAGE_ID: u64 = 0x1234_5678_9ABC_DEF0;
+const NULL_INDEX: u64 = u64::MAX;
+/// Files of the directory index, the name, next file and chunk of each.
+const FILES: [[u32; 3]; 2] = [[2, 1, 0], [3, u32::MAX, 1]];
+
+fn name_batch(data: &mut Vec<u8>, names: &[String]) {
+    data.extend((names.len() as u32).to_le_bytes());
+    data.extend((names.iter().map(String::len).sum::<usize>() as u32).to_le_bytes());
+    data.extend(0u64.to_le_bytes());
+    data.extend(vec![0u8; names.len() * 8]);
+    for name in names {
+        data.extend((name.len() as u16).to_be_bytes());
+    }
+    for name in names {
+        data.extend(name.as_bytes());
+    }
+}
+
+/// Global ids of the imports, all of them are script objects.
+fn script_import_id(import_index: usize) -> u64 {
+    1 << 62 | (0x100 + import_index as u64)
+}
+
+fn global_index(index: PackageIndex) -> u64 {
+    if let Some(import_index) = index.import_index() {
+        script_import_id(import_index)
+    } else if let Some(export_index) = index.export_index() {
+        export_index as u64
+    } else {
+        NULL_INDEX
+    }
+}
+
+/// Converts a legacy package to the layout of a package in a container,
+/// saved with the object version `file_version_ue5`.
+fn zen_package<R: std::io::Read + std::io::Seek>(
+    parser: &UassetParser<R>,
+    data: &[u8],
+    file_version_ue5: i32,
+) -> Vec<u8> {
+    let summary = parser.get_summary();
+    let mut names = parser.names.clone();
+    names.push(summary.package_name.clone());
+
+    let mut header = vec![0u8; 52];
+    header.extend(1u32.to_le_bytes());
+    header.extend(summary.file_version_ue4.to_le_bytes());
+    header.extend(file_version_ue5.to_le_bytes());
+    header.extend(0i32.to_le_bytes());
+    header.extend(0i32.to_le_bytes());
+    name_batch(&mut header, &names);
+
+    let hashes_offset = header.len() as i32;
+    let import_map_offset = header.len() as i32;
+    for import_index in 0..parser.imports.len() {
+        header.extend(script_import_id(import_index).to_le_bytes());
+    }
+
+    let export_map_offset = header.len() as i32;
+    for export in &parser.entries {
+        header.extend((export.serial_offset as u64).to_le_bytes());
+        header.extend((export.serial_size as u64).to_le_bytes());
+        header.extend((export.object_name.index as u32).to_le_bytes());
+        header.extend(export.object_name.number.to_le_bytes());
+        for index in [
+            export.outer_index,
+            export.class_index,
+            export.super_index,
+            export.template_index,
+        ] {
+            header.extend(global_index(index).to_le_bytes());
+        }
+        header.extend(0u64.to_le_bytes());
+        header.extend((export.object_flags as u32).to_le_bytes());
+        header.extend([0u8; 4]);
+    }
+
+    let bundle_entries_offset = header.len() as i32;
+    for command in 0..2u32 {
+        for export_index in 0..parser.entries.len() as u32 {
+            header.extend(export_index.to_le_bytes());
+            header.extend(command.to_le_bytes());
+        }
+    }
+
+    let header_size = header.len() as i32;
+    let mut summary_data = vec![];
+    summary_data.extend(1u32.to_le_bytes());
+    summary_data.extend(header_size.to_le_bytes());
+    summary_data.extend((names.len() as u32 - 1).to_le_bytes());
+    summary_data.extend(0u32.to_le_bytes());
+    summary_data.extend(summary.package_flags.to_le_bytes());
+    summary_data.extend(summary.total_header_size.to_le_bytes());
+    for offset in [
+        hashes_offset,
+        import_map_offset,
+        export_map_offset,
+        bundle_entries_offset,
+        header_size,
+        header_size,
+        header_size,
+    ] {
+        summary_data.extend(offset.to_le_bytes());
+    }
+    header[..52].copy_from_slice(&summary_data);
+
+    header.extend(&data[summary.total_header_size as usize..]);
+    header
+}
+
+/// Script objects for the imports of the package, as in the global container.
+fn script_objects<R: std::io::Read + std::io::Seek>(parser: &UassetParser<R>) -> Vec<u8> {
+    let names: Vec<String> = parser
+        .imports
+        .iter()
+        .map(|import| import.object_name.as_string())
+        .collect();
+    let mut data = vec![];
+    name_batch(&mut data, &names);
+    data.extend((names.len() as i32).to_le_bytes());
+    for (import_index, import) in parser.imports.iter().enumerate() {
+        data.extend((import_index as u32).to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(script_import_id(import_index).to_le_bytes());
+        data.extend(global_index(import.outer_index).to_le_bytes());
+        data.extend(NULL_INDEX.to_le_bytes());
+    }
+    data
+}
+
+/// Builds an encrypted container of zlib compressed blocks, returns the
+/// `.utoc` and `.ucas` data.
+fn build_container(
+    chunks: &[(u64, u8, Vec<u8>)],
+    files: &[[u32; 3]],
+    key: &[u8; 32],
+) -> (Vec<u8>, Vec<u8>) {
+    let mut cas = vec![];
+    let mut blocks = vec![];
+    let mut offsets = vec![];
+    for (_, _, data) in chunks {
+        offsets.push((blocks.len() * BLOCK_SIZE, data.len()));
+        for block in data.chunks(BLOCK_SIZE) {
+            let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+            encoder.write_all(block).unwrap();
+            let mut compressed = encoder.finish().unwrap();
+            blocks.push((cas.len(), compressed.len(), block.len()));
+            encrypt(&mut compressed, key);
+            cas.extend(compressed);
+        }
+    }
+
+    let mut directory_index = vec![];
+    fstring(&mut directory_index, "../../../");
+    let directories: [[u32; 4]; 3] = [
+        [u32::MAX, 1, u32::MAX, u32::MAX],
+        [0, 2, u32::MAX, u32::MAX],
+        [1, u32::MAX, u32::MAX, 0],
+    ];
+    directory_index.extend(3i32.to_le_bytes());
+    directory_index.extend(directories.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    directory_index.extend((files.len() as i32).to_le_bytes());
+    directory_index.extend(files.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    let strings = ["MyGame", "Content", "DT_MyTable.uasset", "DT_MyTable.ubulk"];
+    directory_index.extend((strings.len() as i32).to_le_bytes());
+    for string in strings {
+        fstring(&mut directory_index, string);
+    }
+    encrypt(&mut directory_index, key);
+
+    let mut toc = vec![];
+    toc.extend(b"-==--==--==--==-");
+    toc.extend([8, 0, 0, 0]);
+    for value in [
+        144,
+        chunks.len() as u32,
+        blocks.len() as u32,
+        12,
+        1,
+        32,
+        BLOCK_SIZE as u32,
+        directory_index.len() as u32,
+        1,
+    ] {
+        toc.extend(value.to_le_bytes());
+    }
+    toc.extend(0xC0FFEEu64.to_le_bytes());
+    toc.extend([0u8; 16]);
+    // Compressed, encrypted and indexed
+    toc.extend([1 | 2 | 8, 0, 0, 0]);
+    toc.extend(0u32.to_le_bytes());
+    toc.extend(u64::MAX.to_le_bytes());
+    toc.extend([0u8; 4 + 4 + 40]);
+
+    for (id, chunk_type, _) in chunks {
+        toc.extend(id.to_le_bytes());
+        toc.extend([0, 0, 0, *chunk_type]);
+    }
+    for (offset, length) in offsets {
+        toc.extend(&(offset as u64).to_be_bytes()[3..]);
+        toc.extend(&(length as u64).to_be_bytes()[3..]);
+    }
+    for (offset, compressed_size, size) in blocks {
+        toc.extend(&(offset as u64).to_le_bytes()[..5]);
+        toc.extend(&(compressed_size as u32).to_le_bytes()[..3]);
+        toc.extend(&(size as u32).to_le_bytes()[..3]);
+        toc.push(1);
+    }
+    let mut method = [0u8; 32];
+    method[..4].copy_from_slice(b"Zlib");
+    toc.extend(method);
+    toc.extend(directory_index);
+
+    (toc, cas)
+}
+
+#[test]
+fn test_read_io_store_package() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut legacy = UassetParser::new(Cursor::new(&data));
+    legacy.parse_asset().expect("managed to parse the asset");
+
+    let key = parse_key(KEY).unwrap();
+    let (toc, cas) = build_container(
+        &[
+            (
+                PACKAGE_ID,
+                1,
+                zen_package(&legacy, &data, legacy.get_summary().file_version_ue5),
+            ),
+            (PACKAGE_ID, 2, vec![1, 2, 3, 4]),
+            (0, 5, script_objects(&legacy)),
+        ],
+        &FILES,
+        &key,
+    );
+
+    assert!(matches!(
+        IoStoreReader::from_readers(Cursor::new(&toc), vec![Cursor::new(cas.clone())], None),
+        Err(ParseError::MissingAesKey)
+    ));
+
+    let mut container =
+        IoStoreReader::from_readers(Cursor::new(&toc), vec![Cursor::new(cas)], Some(key))
+            .expect("managed to read the table of contents");
+    assert_eq!(container.mount_point, "../../../");
+    assert_eq!(
+        container.files().collect::<Vec<_>>(),
+        vec![
+            "MyGame/Content/DT_MyTable.uasset",
+            "MyGame/Content/DT_MyTable.ubulk"
+        ]
+    );
+    assert_eq!(
+        container
+            .read_file("MyGame/Content/DT_MyTable.ubulk")
+            .unwrap(),
+        vec![1, 2, 3, 4]
+    );
+
+    let script_objects = container
+        .read_script_objects()
+        .expect("managed to read the script objects");
+    let mut parser = container
+        .open_package("../../../MyGame/Content/DT_MyTable.uasset")
+        .expect("managed to open the package");
+    parser.script_objects = Some(Arc::new(script_objects));
+    parser
+        .parse_zen_package()
+        .expect("managed to parse the package header");
+
+    let header = parser.zen_header.as_ref().unwrap();
+    assert_eq!(header.import_map.len(), legacy.imports.len());
+    assert_eq!(
+        parser.get_summary().package_name,
+        "/Game/P1/Dialogs/DT_MyTable"
+    );
+    assert_eq!(parser.get_summary().file_version_ue5, 1012);
+    assert_eq!(parser.names[..legacy.names.len()], legacy.names);
+    assert_eq!(parser.entries.len(), 3);
+    assert_eq!(
+        parser.object_path(PackageIndex::from_export(0)).as_deref(),
+        Some("/Game/P1/Dialogs/DT_MyTable.DT_MyTable:AssetImportData")
+    );
+    assert_eq!(parser.export_class_name(1).as_deref(), Some("DataTable"));
+    assert_eq!(
+        parser.object_path(parser.entries[1].class_index).as_deref(),
+        Some("/Script/Engine.DataTable")
+    );
+
+    let data_table = parser
+        .read_export(1)
+        .expect("managed to read the data table")
+        .into_data_table()
+        .expect("export is a data table");
+    assert_eq!(
+        data_table.row_struct.as_deref(),
+        Some("/Script/GameplayTags.GameplayTagTableRow")
+    );
+    assert_eq!(data_table.rows.len(), 2);
+    assert_eq!(
+        data_table.rows[0]
+            .get_name("Tag")
+            .map(|tag| tag.as_string()),
+        Some("Tag.Foo.Bar".to_string())
+    );
+    assert_eq!(parser.reader.read_bulk_data(2, 2).unwrap(), vec![3, 4]);
+}
+
+#[test]
+fn test_read_io_store_package_ue53() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut legacy = UassetParser::new(Cursor::new(&data));
+    legacy.parse_asset().expect("managed to parse the asset");
+
+    // 5.3 already has the summary with the dependency bundles
+    let file_version_ue5 = EUnrealEngineObjectUE5Version::DataResources as i32;
+    let key = parse_key(KEY).unwrap();
+    let (toc, cas) = build_container(
+        &[
+            (PACKAGE_ID, 1, zen_package(&legacy, &data, file_version_ue5)),
+            (PACKAGE_ID, 2, vec![1, 2, 3, 4]),
+            (0, 5, script_objects(&legacy)),
+        ],
+        &FILES,
+        &key,
+    );
+    let mut container =
+        IoStoreReader::from_readers(Cursor::new(&toc), vec![Cursor::new(cas)], Some(key))
+            .expect("managed to read the table of contents");
+    let script_objects = container
+        .read_script_objects()
+        .expect("managed to read the script objects");
+    let mut parser = container
+        .open_package("../../../MyGame/Content/DT_MyTable.uasset")
+        .expect("managed to open the package");
+    parser.script_objects = Some(Arc::new(script_objects));
+    parser
+        .parse_zen_package()
+        .expect("managed to parse the package header");
+
+    let header = parser.zen_header.as_ref().unwrap();
+    assert_eq!(header.summary.graph_data_offset, None);
+    assert!(header.summary.dependency_bundle_headers_offset.is_some());
+    assert_eq!(header.import_map.len(), legacy.imports.len());
+    assert_eq!(parser.get_summary().file_version_ue5, 1009);
+    assert_eq!(parser.names[..legacy.names.len()], legacy.names);
+    assert_eq!(parser.entries.len(), 3);
+    assert_eq!(
+        parser.object_path(PackageIndex::from_export(0)).as_deref(),
+        Some("/Game/P1/Dialogs/DT_MyTable.DT_MyTable:AssetImportData")
+    );
+    assert_eq!(parser.export_class_name(1).as_deref(), Some("DataTable"));
+}
+
+#[test]
+fn test_read_looping_directory_index() {
+    let key = parse_key(KEY).unwrap();
+    // The second file links back to the first one
+    let (toc, cas) = build_container(
+        &[(PACKAGE_ID, 2, vec![1, 2, 3, 4])],
+        &[[2, 1, 0], [3, 0, 0]],
+        &key,
+    );
+    assert!(matches!(
+        IoStoreReader::from_readers(Cursor::new(&toc), vec![Cursor::new(cas)], Some(key)),
+        Err(ParseError::InvalidIoStoreToc)
+    ));
+}
+
+#[test]
+fn test_script_object_paths() {
+    let index = |i| PackageObjectIndex::new(EPackageObjectIndexType::ScriptImport, i);
+    let mut script_objects = ScriptObjects::default();
+    for (i, name, outer) in [
+        (0, "/Script/Engine", PackageObjectIndex::null()),
+        (1, "DataTable", index(0)),
+    ] {
+        script_objects.entries.insert(
+            index(i),
+            ScriptObjectEntry {
+                object_name: FName::new(name),
+                global_index: index(i),
+                outer_index: outer,
+                cdo_class_index: PackageObjectIndex::null(),
+            },
+        );
+    }
+    assert_eq!(
+        script_objects.object_path(index(1)).as_deref(),
+        Some("/Script/Engine.DataTable")
+    );
+    assert_eq!(script_objects.object_path(index(2)), None);
+
+    // Outers pointing back at each other don't resolve
+    script_objects
+        .entries
+        .get_mut(&index(0))
+        .unwrap()
+        .outer_index = index(1);
+    assert_eq!(script_objects.object_path(index(1)), None);
+}
+
+#[test]
+fn test_read_toc_sizes_out_of_bounds() {
+    let key = parse_key(KEY).unwrap();
+    let (toc, cas) = build_container(&[(PACKAGE_ID, 2, vec![1, 2, 3, 4])], &FILES, &key);
+
+    // Chunk count, block count, compression method length and count, and
+    // directory index size
+    for offset in [24, 28, 36, 40, 48] {
+        let mut toc = toc.clone();
+        toc[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            IoStoreReader::from_readers(
+                Cursor::new(&toc),
+                vec![Cursor::new(cas.clone())],
+                Some(key)
+            ),
+            Err(ParseError::InvalidIoStoreToc)
+        ));
+    }
+}
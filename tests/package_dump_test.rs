@@ -0,0 +1,32 @@
+#![cfg(feature = "serde")]
+
+use std::fs::File;
+
+use common::test_data_path;
+use rust_uasset::uasset_parser::UassetParser;
+
+mod common;
+
+#[test]
+fn test_package_dump() {
+    let file =
+        File::open(test_data_path("test_table_ue54.uasset")).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    let dump = parser.dump_package().expect("managed to dump the package");
+    let json = serde_json::to_value(&dump).expect("managed to serialize the dump");
+
+    assert_eq!(json["summary"]["name_count"], 30);
+    assert_eq!(json["names"].as_array().unwrap().len(), 30);
+
+    let data_table = &json["exports"][1];
+    assert_eq!(data_table["object_name"], "DT_MyTable");
+    assert_eq!(data_table["class_path"], "/Script/Engine.DataTable");
+    assert_eq!(data_table["properties"][0]["tag"]["name"], "RowStruct");
+    assert_eq!(
+        data_table["properties"][0]["tag"]["type_name"],
+        "ObjectProperty"
+    );
+    assert!(data_table.get("error").is_none());
+}
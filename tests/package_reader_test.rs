@@ -0,0 +1,85 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use common::test_data_path;
+use rust_uasset::errors::ParseError;
+use rust_uasset::package_reader::PackageReader;
+use rust_uasset::uasset_parser::UassetParser;
+
+mod common;
+
+/// Splits a package the way the cooker does, the header in the `.uasset` and
+/// the exports in the `.uexp`.
+fn split_package(data: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+    let header_size = parser.get_summary().total_header_size as usize;
+    (data[..header_size].to_vec(), data[header_size..].to_vec())
+}
+
+#[test]
+fn test_split_package_reader() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let (header, exports) = split_package(&data);
+
+    let mut reader = PackageReader::split(Cursor::new(header), Cursor::new(exports)).unwrap();
+    let mut combined = vec![];
+    reader.read_to_end(&mut combined).unwrap();
+    assert!(combined == data);
+
+    // Reads spanning the two files
+    let mut buffer = [0u8; 8];
+    reader.seek(SeekFrom::Start(1852)).unwrap();
+    reader.read_exact(&mut buffer).unwrap();
+    assert_eq!(buffer, data[1852..1860]);
+    reader.seek(SeekFrom::End(-4)).unwrap();
+    reader.read_exact(&mut buffer[..4]).unwrap();
+    assert_eq!(buffer[..4], data[data.len() - 4..]);
+}
+
+#[test]
+fn test_open_cooked_package() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let (header, exports) = split_package(&data);
+
+    let directory = std::env::temp_dir().join(format!("rust_uasset_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("DT_MyTable.uasset");
+    std::fs::write(&path, header).unwrap();
+    std::fs::write(path.with_extension("uexp"), exports).unwrap();
+    std::fs::write(path.with_extension("ubulk"), [1, 2, 3, 4]).unwrap();
+
+    let mut parser = UassetParser::open(&path).expect("managed to open the package");
+    parser.parse_asset().expect("managed to parse the asset");
+    assert!(parser.reader.is_split());
+
+    let data_table = parser
+        .read_export(1)
+        .expect("managed to read the data table")
+        .into_data_table()
+        .expect("export is a data table");
+    assert_eq!(data_table.rows.len(), 2);
+    assert_eq!(parser.reader.read_bulk_data(1, 2).unwrap(), vec![2, 3]);
+    assert!(parser.reader.read_optional_bulk_data(0, 1).is_err());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_unreadable_companion() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+
+    let directory =
+        std::env::temp_dir().join(format!("rust_uasset_companion_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("DT_MyTable.uasset");
+    std::fs::write(&path, data).unwrap();
+    assert!(UassetParser::open(&path).is_ok());
+
+    // A .uexp that exists but can't be opened isn't taken for a missing one
+    let uexp = path.with_extension("uexp");
+    std::os::unix::fs::symlink(&uexp, &uexp).unwrap();
+    assert!(matches!(UassetParser::open(&path), Err(ParseError::Io(_))));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,279 @@
+use std::io::{Cursor, Write};
+
+use common::{encrypt, fstring, test_data_path};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use rust_uasset::errors::ParseError;
+use rust_uasset::pak::{PakCompressedBlock, PakEntry, PakFile, parse_key};
+use rust_uasset::uasset_parser::UassetParser;
+
+mod common;
+
+const KEY: &str = "0x000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F";
+
+/// Full entry as written before the data of every file.
+fn entry(
+    offset: i64,
+    size: i64,
+    uncompressed_size: i64,
+    blocks: &[(i64, i64)],
+    encrypted: bool,
+) -> Vec<u8> {
+    let mut data = vec![];
+    data.extend(offset.to_le_bytes());
+    data.extend(size.to_le_bytes());
+    data.extend(uncompressed_size.to_le_bytes());
+    data.extend((!blocks.is_empty() as u32).to_le_bytes());
+    data.extend([0u8; 20]);
+    if !blocks.is_empty() {
+        data.extend((blocks.len() as i32).to_le_bytes());
+        for (start, end) in blocks {
+            data.extend(start.to_le_bytes());
+            data.extend(end.to_le_bytes());
+        }
+    }
+    data.push(encrypted as u8);
+    data.extend(65536u32.to_le_bytes());
+    data
+}
+
+/// Builds a version 11 pak with an encrypted index: the package header stored
+/// as is, its exports compressed with zlib and an encrypted text file.
+fn build_pak(header: &[u8], exports: &[u8], notes: &[u8], key: &[u8; 32]) -> Vec<u8> {
+    let mut pak = vec![];
+    let mut encoded = vec![];
+
+    // Uncompressed, offset and size in 32 bits
+    let header_offset = pak.len() as u32;
+    pak.extend(entry(
+        0,
+        header.len() as i64,
+        header.len() as i64,
+        &[],
+        false,
+    ));
+    pak.extend(header);
+    encoded.extend((3u32 << 30).to_le_bytes());
+    encoded.extend(header_offset.to_le_bytes());
+    encoded.extend((header.len() as u32).to_le_bytes());
+
+    // Zlib in a single block, stored after the full entry
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(exports).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let exports_location = encoded.len() as i32;
+    let exports_offset = pak.len() as u32;
+    let block_start = 53 + 4 + 16;
+    pak.extend(entry(
+        0,
+        compressed.len() as i64,
+        exports.len() as i64,
+        &[(block_start, block_start + compressed.len() as i64)],
+        false,
+    ));
+    pak.extend(&compressed);
+    encoded.extend((7u32 << 29 | 1 << 23 | 1 << 6 | 0x3f).to_le_bytes());
+    encoded.extend(65536u32.to_le_bytes());
+    encoded.extend(exports_offset.to_le_bytes());
+    encoded.extend((exports.len() as u32).to_le_bytes());
+    encoded.extend((compressed.len() as u32).to_le_bytes());
+
+    // Encrypted, listed as a full entry in the index
+    let notes_entry = entry(
+        pak.len() as i64,
+        notes.len() as i64,
+        notes.len() as i64,
+        &[],
+        true,
+    );
+    pak.extend(entry(0, notes.len() as i64, notes.len() as i64, &[], true));
+    let mut encrypted_notes = notes.to_vec();
+    encrypt(&mut encrypted_notes, key);
+    pak.extend(encrypted_notes);
+
+    let mut directories = vec![];
+    directories.extend(2i32.to_le_bytes());
+    fstring(&mut directories, "/");
+    directories.extend(1i32.to_le_bytes());
+    fstring(&mut directories, "Notes.txt");
+    directories.extend((-1i32).to_le_bytes());
+    fstring(&mut directories, "Content/");
+    directories.extend(2i32.to_le_bytes());
+    fstring(&mut directories, "DT_MyTable.uasset");
+    directories.extend(0i32.to_le_bytes());
+    fstring(&mut directories, "DT_MyTable.uexp");
+    directories.extend(exports_location.to_le_bytes());
+    encrypt(&mut directories, key);
+    let directories_offset = pak.len() as i64;
+    pak.extend(&directories);
+
+    let mut index = vec![];
+    fstring(&mut index, "../../../MyGame/");
+    index.extend(3i32.to_le_bytes());
+    index.extend(0u64.to_le_bytes());
+    index.extend(0u32.to_le_bytes());
+    index.extend(1u32.to_le_bytes());
+    index.extend(directories_offset.to_le_bytes());
+    index.extend((directories.len() as i64).to_le_bytes());
+    index.extend([0u8; 20]);
+    index.extend((encoded.len() as i32).to_le_bytes());
+    index.extend(&encoded);
+    index.extend(1i32.to_le_bytes());
+    index.extend(notes_entry);
+    encrypt(&mut index, key);
+    let index_offset = pak.len() as i64;
+    pak.extend(&index);
+
+    pak.extend([0u8; 16]);
+    pak.push(1);
+    pak.extend(0x5A6F12E1u32.to_le_bytes());
+    pak.extend(11i32.to_le_bytes());
+    pak.extend(index_offset.to_le_bytes());
+    pak.extend((index.len() as i64).to_le_bytes());
+    pak.extend([0u8; 20]);
+    let mut method = [0u8; 32];
+    method[..4].copy_from_slice(b"Zlib");
+    pak.extend(method);
+    pak.extend([0u8; 32 * 4]);
+    pak
+}
+
+/// Builds a version 11 pak holding `data` followed by an unencrypted index.
+fn index_pak(data: &[u8], index: &[u8]) -> Vec<u8> {
+    let mut pak = data.to_vec();
+    pak.extend(index);
+    pak.extend([0u8; 16]);
+    pak.push(0);
+    pak.extend(0x5A6F12E1u32.to_le_bytes());
+    pak.extend(11i32.to_le_bytes());
+    pak.extend((data.len() as i64).to_le_bytes());
+    pak.extend((index.len() as i64).to_le_bytes());
+    pak.extend([0u8; 20]);
+    pak.extend([0u8; 32 * 5]);
+    pak
+}
+
+#[test]
+fn test_read_pak() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(Cursor::new(&data));
+    parser.parse_asset().expect("managed to parse the asset");
+    let (header, exports) = data.split_at(parser.get_summary().total_header_size as usize);
+
+    let key = parse_key(KEY).expect("valid key");
+    let pak = build_pak(header, exports, b"Shipped build", &key);
+
+    assert!(matches!(
+        PakFile::from_reader(Cursor::new(pak.clone()), None),
+        Err(ParseError::MissingAesKey)
+    ));
+
+    let mut pak =
+        PakFile::from_reader(Cursor::new(pak), Some(key)).expect("managed to read the pak");
+    assert_eq!(pak.mount_point, "../../../MyGame/");
+    assert_eq!(pak.compression_methods, vec!["Zlib"]);
+    assert_eq!(
+        pak.files().collect::<Vec<_>>(),
+        vec![
+            "Content/DT_MyTable.uasset",
+            "Content/DT_MyTable.uexp",
+            "Notes.txt"
+        ]
+    );
+
+    assert_eq!(
+        pak.read_file("../../../MyGame/Notes.txt").unwrap(),
+        b"Shipped build"
+    );
+    assert_eq!(pak.read_file("Content/DT_MyTable.uexp").unwrap(), exports);
+    assert!(matches!(
+        pak.read_file("Missing.txt"),
+        Err(ParseError::PakEntryNotFound(_))
+    ));
+
+    let mut parser = pak
+        .open_package("Content/DT_MyTable.uasset")
+        .expect("managed to open the package");
+    parser.parse_asset().expect("managed to parse the asset");
+    assert!(parser.reader.is_split());
+    let data_table = parser
+        .read_export(1)
+        .expect("managed to read the data table")
+        .into_data_table()
+        .expect("export is a data table");
+    assert_eq!(data_table.rows.len(), 2);
+}
+
+#[test]
+fn test_parse_pak_key() {
+    let hex = parse_key(KEY).unwrap();
+    let base64 = parse_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").unwrap();
+    assert_eq!(hex, base64);
+    assert_eq!(hex[31], 0x1f);
+    assert!(parse_key("0x1234").is_err());
+}
+
+#[test]
+fn test_pak_sizes_out_of_bounds() {
+    // Index without secondary blocks, then the encoded entries and file count
+    let index = |encoded_size: i32, file_count: i32| {
+        let mut index = vec![];
+        fstring(&mut index, "../../../MyGame/");
+        index.extend(0i32.to_le_bytes());
+        index.extend(0u64.to_le_bytes());
+        index.extend(0u32.to_le_bytes());
+        index.extend(0u32.to_le_bytes());
+        index.extend(encoded_size.to_le_bytes());
+        index.extend(file_count.to_le_bytes());
+        index
+    };
+    assert!(PakFile::from_reader(Cursor::new(index_pak(&[], &index(0, 0))), None).is_ok());
+    for (encoded_size, file_count) in [(10_000_000, 0), (0, 10_000_000)] {
+        assert!(matches!(
+            PakFile::from_reader(
+                Cursor::new(index_pak(&[], &index(encoded_size, file_count))),
+                None
+            ),
+            Err(ParseError::InvalidPakFooter)
+        ));
+    }
+
+    let mut pak = PakFile::from_reader(Cursor::new(index_pak(&[], &index(0, 0))), None).unwrap();
+    let stored = PakEntry {
+        size: i64::MAX,
+        uncompressed_size: i64::MAX,
+        ..Default::default()
+    };
+    assert!(matches!(
+        pak.read_entry(&stored),
+        Err(ParseError::InvalidPakFooter)
+    ));
+
+    // A valid block doesn't make up for a size larger than the pak
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(b"Shipped build").unwrap();
+    let compressed = encoder.finish().unwrap();
+    let data = index_pak(&compressed, &index(0, 0));
+    let mut pak = PakFile::from_reader(Cursor::new(data), None).unwrap();
+    pak.compression_methods = vec!["Zlib".to_string()];
+    let compressed_entry = |uncompressed_size: i64| PakEntry {
+        size: compressed.len() as i64,
+        uncompressed_size,
+        compression_method: 1,
+        compression_blocks: vec![PakCompressedBlock {
+            start: 0,
+            end: compressed.len() as i64,
+        }],
+        ..Default::default()
+    };
+    assert_eq!(
+        pak.read_entry(&compressed_entry(13)).unwrap(),
+        b"Shipped build"
+    );
+    for uncompressed_size in [i64::MAX, -1] {
+        assert!(matches!(
+            pak.read_entry(&compressed_entry(uncompressed_size)),
+            Err(ParseError::DecompressionFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::sync::Arc;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use common::{TaggedPropertyBuilder, test_data_path};
+use rust_uasset::data::export_object::ExportObject;
+use rust_uasset::fname::FName;
+use rust_uasset::parser_registry::{CustomValue, ParserRegistry};
+use rust_uasset::property_data::PropertyValue;
+use rust_uasset::struct_value::StructValue;
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::UassetSummary;
+
+mod common;
+
+#[derive(Debug)]
+struct Stat {
+    name: FName,
+    value: i32,
+}
+
+impl CustomValue for Stat {
+    fn as_string(&self) -> String {
+        format!("{}={}", self.name.as_string(), self.value)
+    }
+}
+
+fn read_stat<R: Read + Seek>(
+    parser: &mut UassetParser<R>,
+) -> rust_uasset::errors::Result<Arc<dyn CustomValue>> {
+    let name: FName = parser.read()?;
+    let value = parser.reader.read_i32::<LittleEndian>()?;
+    Ok(Arc::new(Stat { name, value }))
+}
+
+#[test]
+fn test_registered_struct_parser() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property(
+            "Stat",
+            &[("StructProperty", 1), ("Stat", 1), ("/Script/Game", 0)],
+            0x08,
+            |b| {
+                b.name("Strength").bytes(&12i32.to_le_bytes());
+            },
+        )
+        .none();
+
+    let mut registry = ParserRegistry::new();
+    registry.register_struct("Stat", read_stat);
+
+    let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+    parser.names = builder.names.clone();
+    parser.registry = Some(Arc::new(registry));
+    parser.set_summary(UassetSummary {
+        file_version_ue5: 1012,
+        ..Default::default()
+    });
+
+    let properties = parser
+        .read_tagged_properties()
+        .expect("managed to read properties");
+    let PropertyValue::StructProperty(StructValue::Custom(custom)) = &properties[0].value else {
+        panic!("unexpected value {:?}", properties[0].value);
+    };
+    let stat = custom
+        .value
+        .downcast_ref::<Stat>()
+        .expect("value is a Stat");
+    assert_eq!(stat.name.as_string(), "Strength");
+    assert_eq!(stat.value, 12);
+    assert_eq!(properties[0].value.as_string(), "Strength=12");
+    assert_eq!(custom.data.len(), 12);
+}
+
+#[test]
+fn test_registered_export_parser() {
+    let file =
+        File::open(test_data_path("test_table_ue54.uasset")).expect("Failed to open test file");
+
+    let mut registry = ParserRegistry::new();
+    registry.register_export(
+        "/Script/Engine.DataTable",
+        |parser: &mut UassetParser<File>| {
+            let value = parser.reader.read_u8()?;
+            Ok(Arc::new(Stat {
+                name: FName::new("FirstByte"),
+                value: value as i32,
+            }) as Arc<dyn CustomValue>)
+        },
+    );
+
+    let mut parser = UassetParser::new(file);
+    parser.registry = Some(Arc::new(registry));
+    parser.parse_asset().expect("managed to parse the asset");
+
+    match parser.read_export(1).expect("managed to read the export") {
+        ExportObject::Custom(custom) => assert_eq!(custom.data.len(), 1),
+        other => panic!("unexpected export {:?}", other),
+    }
+    assert!(matches!(parser.read_export(0), Ok(ExportObject::Object(_))));
+}
@@ -0,0 +1,542 @@
+use std::io::Cursor;
+
+use common::{TaggedPropertyBuilder, read_properties, read_versioned_properties};
+use rust_uasset::errors::ParseError;
+use rust_uasset::ftext::TextHistory;
+use rust_uasset::guid::Guid;
+use rust_uasset::property_data::{PropertyData, PropertyValue};
+use rust_uasset::property_type_name::PropertyTypeName;
+use rust_uasset::struct_value::{IntPoint, StructValue, Vector};
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::UassetSummary;
+
+mod common;
+
+#[test]
+fn test_scalar_property_values() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property("Health", &[("IntProperty", 0)], 0, |b| {
+            b.bytes(&(-25i32).to_le_bytes());
+        })
+        .property("Speed", &[("FloatProperty", 0)], 0, |b| {
+            b.bytes(&1.5f32.to_le_bytes());
+        })
+        .property("Weight", &[("DoubleProperty", 0)], 0, |b| {
+            b.bytes(&0.25f64.to_le_bytes());
+        })
+        .property("bEnabled", &[("BoolProperty", 0)], 0x10, |_| {})
+        .property("Count", &[("UInt64Property", 0)], 0, |b| {
+            b.bytes(&7u64.to_le_bytes());
+        })
+        .property("Tier", &[("ByteProperty", 0)], 0, |b| {
+            b.bytes(&[3]);
+        })
+        .property(
+            "Rarity",
+            &[
+                ("EnumProperty", 2),
+                ("ERarity", 1),
+                ("/Script/Game", 0),
+                ("ByteProperty", 0),
+            ],
+            0,
+            |b| {
+                b.name("ERarity::Epic");
+            },
+        )
+        .property("DisplayName", &[("TextProperty", 0)], 0, |b| {
+            b.bytes(&0u32.to_le_bytes())
+                .bytes(&[0])
+                .fstring("Items")
+                .fstring("Sword_Name")
+                .fstring("Sword");
+        })
+        .none();
+
+    let properties = read_properties(&builder);
+    let values: Vec<&PropertyValue> = properties.iter().map(|p| &p.value).collect();
+
+    assert_eq!(values[0], &PropertyValue::IntProperty(-25));
+    assert_eq!(values[1], &PropertyValue::FloatProperty(1.5));
+    assert_eq!(values[2], &PropertyValue::DoubleProperty(0.25));
+    assert_eq!(values[3], &PropertyValue::BoolProperty(true));
+    assert_eq!(values[4], &PropertyValue::UInt64Property(7));
+    assert_eq!(values[5], &PropertyValue::ByteProperty(3));
+    assert_eq!(values[6].as_string(), "ERarity::Epic");
+
+    match values[7] {
+        PropertyValue::TextProperty(text) => {
+            assert_eq!(
+                text.history,
+                TextHistory::Base {
+                    namespace: "Items".into(),
+                    key: "Sword_Name".into(),
+                    source_string: "Sword".into(),
+                }
+            );
+        }
+        other => panic!("unexpected value {:?}", other),
+    }
+}
+
+#[test]
+fn test_negative_property_size() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .tag("Health", &[("IntProperty", 0)], -4, 0)
+        .bytes(&12i32.to_le_bytes())
+        .none();
+
+    let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+    parser.names = builder.names.clone();
+    parser.set_summary(UassetSummary {
+        file_version_ue5: 1012,
+        ..Default::default()
+    });
+    let result: Result<PropertyData, _> = parser.read();
+    assert!(matches!(
+        result,
+        Err(ParseError::InvalidPropertySize { size: -4, .. })
+    ));
+}
+
+#[test]
+fn test_container_property_values() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property(
+            "Tags",
+            &[("ArrayProperty", 1), ("NameProperty", 0)],
+            0,
+            |b| {
+                b.bytes(&2i32.to_le_bytes()).name("Fire").name("Ice");
+            },
+        )
+        .property(
+            "Resistances",
+            &[
+                ("MapProperty", 2),
+                ("EnumProperty", 2),
+                ("EElement", 1),
+                ("/Script/Game", 0),
+                ("ByteProperty", 0),
+                ("IntProperty", 0),
+            ],
+            0,
+            |b| {
+                b.bytes(&0i32.to_le_bytes())
+                    .bytes(&1i32.to_le_bytes())
+                    .name("EElement::Fire")
+                    .bytes(&40i32.to_le_bytes());
+            },
+        )
+        .property("Ids", &[("SetProperty", 1), ("IntProperty", 0)], 0, |b| {
+            b.bytes(&1i32.to_le_bytes())
+                .bytes(&9i32.to_le_bytes())
+                .bytes(&2i32.to_le_bytes())
+                .bytes(&1i32.to_le_bytes())
+                .bytes(&2i32.to_le_bytes());
+        })
+        .none();
+
+    let properties = read_properties(&builder);
+
+    match &properties[0].value {
+        PropertyValue::ArrayProperty(elements) => {
+            let names: Vec<String> = elements.iter().map(|e| e.as_string()).collect();
+            assert_eq!(names, ["Fire", "Ice"]);
+        }
+        other => panic!("unexpected value {:?}", other),
+    }
+
+    match &properties[1].value {
+        PropertyValue::MapProperty { entries, removed } => {
+            assert!(removed.is_empty());
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].0.as_string(), "EElement::Fire");
+            assert_eq!(entries[0].1, PropertyValue::IntProperty(40));
+        }
+        other => panic!("unexpected value {:?}", other),
+    }
+
+    assert_eq!(
+        properties[2].value,
+        PropertyValue::SetProperty {
+            elements: vec![PropertyValue::IntProperty(1), PropertyValue::IntProperty(2)],
+            removed: vec![PropertyValue::IntProperty(9)],
+        }
+    );
+}
+
+#[test]
+fn test_struct_property_values() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property(
+            "Location",
+            &[
+                ("StructProperty", 1),
+                ("Vector", 1),
+                ("/Script/CoreUObject", 0),
+            ],
+            0x08,
+            |b| {
+                b.bytes(&1.0f64.to_le_bytes())
+                    .bytes(&2.0f64.to_le_bytes())
+                    .bytes(&3.0f64.to_le_bytes());
+            },
+        )
+        .property(
+            "Cells",
+            &[
+                ("ArrayProperty", 1),
+                ("StructProperty", 1),
+                ("IntPoint", 1),
+                ("/Script/CoreUObject", 0),
+            ],
+            0,
+            |b| {
+                b.bytes(&1i32.to_le_bytes())
+                    .bytes(&4i32.to_le_bytes())
+                    .bytes(&(-2i32).to_le_bytes());
+            },
+        )
+        .property(
+            "Reward",
+            &[
+                ("StructProperty", 1),
+                ("ItemReward", 1),
+                ("/Script/Game", 0),
+            ],
+            0,
+            |b| {
+                b.property("Amount", &[("IntProperty", 0)], 0, |b| {
+                    b.bytes(&10i32.to_le_bytes());
+                })
+                .none();
+            },
+        )
+        .property(
+            "Tags",
+            &[
+                ("StructProperty", 1),
+                ("GameplayTagContainer", 1),
+                ("/Script/GameplayTags", 0),
+            ],
+            0x08,
+            |b| {
+                b.bytes(&1i32.to_le_bytes()).name("Status.Burning");
+            },
+        )
+        .none();
+
+    let properties = read_properties(&builder);
+
+    assert_eq!(
+        properties[0].value,
+        PropertyValue::StructProperty(StructValue::Vector(Vector {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0
+        }))
+    );
+    assert_eq!(
+        properties[1].value,
+        PropertyValue::ArrayProperty(vec![PropertyValue::StructProperty(StructValue::IntPoint(
+            IntPoint { x: 4, y: -2 }
+        ))])
+    );
+
+    match &properties[2].value {
+        PropertyValue::StructProperty(StructValue::Properties(fields)) => {
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].tag.name.as_string(), "Amount");
+            assert_eq!(fields[0].value, PropertyValue::IntProperty(10));
+        }
+        other => panic!("unexpected value {:?}", other),
+    }
+
+    assert_eq!(properties[3].value.as_string(), "(Status.Burning)");
+}
+
+#[test]
+fn test_native_struct_without_summary() {
+    let mut parser = UassetParser::new(Cursor::new(vec![0u8; 24]));
+    let result: Result<Vector, _> = parser.read();
+    assert!(matches!(result, Err(ParseError::MissingSummary)));
+}
+
+#[test]
+fn test_property_value_without_summary() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder.name("ERarity::Epic");
+
+    let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+    parser.names = builder.names.clone();
+    let value = parser
+        .read_property_value(&PropertyTypeName::new("EnumProperty"))
+        .expect("managed to read the value");
+    assert_eq!(value.as_string(), "ERarity::Epic");
+}
+
+#[test]
+fn test_property_tag_without_summary() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property("Health", &[("IntProperty", 0)], 0, |b| {
+            b.bytes(&12i32.to_le_bytes());
+        })
+        .none();
+
+    let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+    parser.names = builder.names.clone();
+    let result: Result<PropertyData, _> = parser.read();
+    assert!(matches!(result, Err(ParseError::MissingSummary)));
+}
+
+#[test]
+fn test_malformed_property_type_names() {
+    let read_tag = |builder: &TaggedPropertyBuilder| {
+        let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+        parser.names = builder.names.clone();
+        parser.set_summary(UassetSummary {
+            file_version_ue5: 1012,
+            ..Default::default()
+        });
+        parser.read::<PropertyData>()
+    };
+
+    let mut builder = TaggedPropertyBuilder::default();
+    builder.tag("Nested", &[("ArrayProperty", 1); 64], 0, 0);
+    assert!(matches!(
+        read_tag(&builder),
+        Err(ParseError::PropertyTypeTooDeep(_))
+    ));
+
+    // A count no file could hold runs out of data
+    let mut builder = TaggedPropertyBuilder::default();
+    builder.tag("Huge", &[("ArrayProperty", i32::MAX)], 0, 0);
+    assert!(matches!(read_tag(&builder), Err(ParseError::Io(_))));
+}
+
+#[test]
+fn test_property_tag_extensions() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .tag("Health", &[("IntProperty", 0)], 4, 0x04)
+        // Overridable information: Modified, no experimental overridable logic
+        .bytes(&[0x02, 0x01])
+        .bytes(&0u32.to_le_bytes())
+        .bytes(&12i32.to_le_bytes())
+        .none();
+
+    let properties = read_properties(&builder);
+
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].tag.extensions, 0x02);
+    assert_eq!(properties[0].tag.overridable_operation, 1);
+    assert!(!properties[0].tag.experimental_overridable_logic);
+    assert_eq!(properties[0].value, PropertyValue::IntProperty(12));
+}
+
+#[test]
+fn test_legacy_property_tags() {
+    let no_guid = [0u8; 16];
+    let mut builder = TaggedPropertyBuilder::default();
+
+    // Name, Type, Size, ArrayIndex, type specific fields, HasPropertyGuid
+    builder
+        .name("Health")
+        .name("IntProperty")
+        .bytes(&4i32.to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .bytes(&[0])
+        .bytes(&30i32.to_le_bytes());
+    builder
+        .name("bEnabled")
+        .name("BoolProperty")
+        .bytes(&0i32.to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .bytes(&[1, 0]);
+    builder
+        .name("Rarity")
+        .name("ByteProperty")
+        .bytes(&8i32.to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("ERarity")
+        .bytes(&[0])
+        .name("ERarity::Rare");
+    builder
+        .name("Location")
+        .name("StructProperty")
+        .bytes(&12i32.to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("Vector")
+        .bytes(&no_guid)
+        .bytes(&[0])
+        .bytes(&1.0f32.to_le_bytes())
+        .bytes(&2.0f32.to_le_bytes())
+        .bytes(&3.0f32.to_le_bytes());
+    builder
+        .name("Cells")
+        .name("ArrayProperty")
+        .bytes(&(4 + 8 + 8 + 4 + 4 + 8 + 16 + 1 + 8i32).to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("StructProperty")
+        .bytes(&[0])
+        .bytes(&1i32.to_le_bytes())
+        .name("Cells")
+        .name("StructProperty")
+        .bytes(&8i32.to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("IntPoint")
+        .bytes(&no_guid)
+        .bytes(&[0])
+        .bytes(&5i32.to_le_bytes())
+        .bytes(&6i32.to_le_bytes());
+    builder.none();
+
+    // UE 5.0, before large world coordinates
+    let properties = read_versioned_properties(&builder, 1002);
+
+    assert_eq!(properties.len(), 5);
+    assert_eq!(properties[0].value, PropertyValue::IntProperty(30));
+    assert_eq!(properties[1].value, PropertyValue::BoolProperty(true));
+    assert_eq!(properties[2].tag.type_name.parameters[0].name, "ERarity");
+    assert_eq!(properties[2].value.as_string(), "ERarity::Rare");
+    assert_eq!(
+        properties[3].value,
+        PropertyValue::StructProperty(StructValue::Vector(Vector {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0
+        }))
+    );
+    assert_eq!(
+        properties[4].value,
+        PropertyValue::ArrayProperty(vec![PropertyValue::StructProperty(StructValue::IntPoint(
+            IntPoint { x: 5, y: 6 }
+        ))])
+    );
+}
+
+#[test]
+fn test_legacy_struct_fallbacks() {
+    let struct_guid = Guid::new(0x11223344, 0x55667788, 0x99AABBCC, 0xDDEEFF00);
+    let mut guid_bytes = vec![];
+    for part in [0x11223344u32, 0x55667788, 0x99AABBCC, 0xDDEEFF00] {
+        guid_bytes.extend(part.to_le_bytes());
+    }
+    let box_bytes: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .chain([1])
+        .collect();
+    let mut builder = TaggedPropertyBuilder::default();
+
+    // A user defined struct keeps its GUID
+    builder
+        .name("Stats")
+        .name("StructProperty")
+        .bytes(&(8 + 8 + 4 + 4 + 1 + 4 + 8i32).to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("PlayerStats")
+        .bytes(&guid_bytes)
+        .bytes(&[0])
+        .name("Level")
+        .name("IntProperty")
+        .bytes(&4i32.to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .bytes(&[0])
+        .bytes(&7i32.to_le_bytes())
+        .name("None");
+    // A native struct the crate doesn't know doesn't read as tagged properties
+    builder
+        .name("Bounds")
+        .name("StructProperty")
+        .bytes(&(box_bytes.len() as i32).to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("Box2D")
+        .bytes(&[0; 16])
+        .bytes(&[0])
+        .bytes(&box_bytes);
+    // Legacy map tags don't name the struct of their keys
+    builder
+        .name("Offsets")
+        .name("MapProperty")
+        .bytes(&(4 + 4 + 12 + 4i32).to_le_bytes())
+        .bytes(&0i32.to_le_bytes())
+        .name("StructProperty")
+        .name("IntProperty")
+        .bytes(&[0])
+        .bytes(&0i32.to_le_bytes())
+        .bytes(&1i32.to_le_bytes())
+        .bytes(&1.0f32.to_le_bytes())
+        .bytes(&2.0f32.to_le_bytes())
+        .bytes(&3.0f32.to_le_bytes())
+        .bytes(&9i32.to_le_bytes());
+    builder.none();
+
+    let properties = read_versioned_properties(&builder, 1002);
+
+    assert_eq!(properties.len(), 3);
+    assert_eq!(
+        properties[0].tag.type_name.to_string(),
+        format!("StructProperty(PlayerStats,{struct_guid})")
+    );
+    assert_eq!(properties[0].tag.type_name.struct_guid(), Some(struct_guid));
+    assert!(matches!(
+        properties[0].value,
+        PropertyValue::StructProperty(StructValue::Properties(_))
+    ));
+    assert_eq!(
+        properties[1].value,
+        PropertyValue::StructProperty(StructValue::Raw(box_bytes))
+    );
+    assert!(matches!(
+        properties[2].value,
+        PropertyValue::StructProperty(StructValue::Raw(_))
+    ));
+}
+
+#[test]
+fn test_property_type_names() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property(
+            "Loot",
+            &[
+                ("MapProperty", 2),
+                ("NameProperty", 0),
+                ("ArrayProperty", 1),
+                ("StructProperty", 1),
+                ("Guid", 1),
+                ("/Script/CoreUObject", 0),
+            ],
+            0,
+            |b| {
+                b.bytes(&0i32.to_le_bytes()).bytes(&0i32.to_le_bytes());
+            },
+        )
+        .none();
+
+    let properties = read_properties(&builder);
+    let type_name = &properties[0].tag.type_name;
+
+    assert_eq!(
+        type_name.to_string(),
+        "MapProperty(NameProperty,ArrayProperty(StructProperty(Guid(/Script/CoreUObject))))"
+    );
+
+    let (key, value) = type_name.key_value().expect("map has key and value types");
+    assert_eq!(key.name, "NameProperty");
+    let element = value.inner().expect("array has an inner type");
+    assert_eq!(element.struct_name(), Some("Guid"));
+    assert_eq!(
+        element.struct_path().as_deref(),
+        Some("/Script/CoreUObject.Guid")
+    );
+    assert_eq!(type_name.inner(), None);
+    assert_eq!(key.enum_name(), None);
+}
@@ -0,0 +1,212 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use common::{TaggedPropertyBuilder, read_properties, test_data_path};
+use rust_uasset::package_index::PackageIndex;
+use rust_uasset::property_deserializer::{from_properties, from_properties_with};
+use rust_uasset::uasset_parser::UassetParser;
+use serde::Deserialize;
+
+mod common;
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+enum Element {
+    Fire,
+    Ice,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Location {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct ItemRow {
+    health: i32,
+    display_name: String,
+    element: Element,
+    tags: Vec<String>,
+    resistances: HashMap<Element, i32>,
+    location: Location,
+    #[serde(default)]
+    weight: Option<f64>,
+}
+
+#[test]
+fn test_deserialize_properties() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property("Health", &[("IntProperty", 0)], 0, |b| {
+            b.bytes(&100i32.to_le_bytes());
+        })
+        .property("DisplayName", &[("TextProperty", 0)], 0, |b| {
+            b.bytes(&0u32.to_le_bytes())
+                .bytes(&[0])
+                .fstring("Items")
+                .fstring("Sword_Name")
+                .fstring("Sword");
+        })
+        .property(
+            "Element",
+            &[
+                ("EnumProperty", 2),
+                ("EElement", 1),
+                ("/Script/Game", 0),
+                ("ByteProperty", 0),
+            ],
+            0,
+            |b| {
+                b.name("EElement::Ice");
+            },
+        )
+        .property(
+            "Tags",
+            &[("ArrayProperty", 1), ("NameProperty", 0)],
+            0,
+            |b| {
+                b.bytes(&2i32.to_le_bytes()).name("Sharp").name("Heavy");
+            },
+        )
+        .property(
+            "Resistances",
+            &[
+                ("MapProperty", 2),
+                ("EnumProperty", 2),
+                ("EElement", 1),
+                ("/Script/Game", 0),
+                ("ByteProperty", 0),
+                ("IntProperty", 0),
+            ],
+            0,
+            |b| {
+                b.bytes(&0i32.to_le_bytes())
+                    .bytes(&1i32.to_le_bytes())
+                    .name("EElement::Fire")
+                    .bytes(&40i32.to_le_bytes());
+            },
+        )
+        .property(
+            "Location",
+            &[
+                ("StructProperty", 1),
+                ("Vector", 1),
+                ("/Script/CoreUObject", 0),
+            ],
+            0x08,
+            |b| {
+                b.bytes(&1.0f64.to_le_bytes())
+                    .bytes(&2.0f64.to_le_bytes())
+                    .bytes(&3.0f64.to_le_bytes());
+            },
+        )
+        .none();
+
+    let properties = read_properties(&builder);
+    let row: ItemRow = from_properties(&properties).expect("managed to deserialize the row");
+    assert_eq!(
+        row,
+        ItemRow {
+            health: 100,
+            display_name: "Sword".into(),
+            element: Element::Ice,
+            tags: vec!["Sharp".into(), "Heavy".into()],
+            resistances: HashMap::from([(Element::Fire, 40)]),
+            location: Location {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0
+            },
+            weight: None,
+        }
+    );
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct WrongType {
+        #[serde(rename = "Health")]
+        health: String,
+    }
+    assert!(from_properties::<WrongType>(&properties).is_err());
+}
+
+#[test]
+fn test_deserialize_static_arrays_and_objects() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property("Slots", &[("IntProperty", 0)], 0, |b| {
+            b.bytes(&4i32.to_le_bytes());
+        })
+        .property("Slots", &[("IntProperty", 0)], 0x01, |b| {
+            b.bytes(&5i32.to_le_bytes());
+        });
+    // The array index goes before the payload, patch it into the last tag
+    let index_offset = builder.data.len() - 4;
+    builder
+        .data
+        .splice(index_offset..index_offset, 1i32.to_le_bytes());
+    builder
+        .property("Icon", &[("ObjectProperty", 0)], 0, |b| {
+            b.bytes(&(-2i32).to_le_bytes());
+        })
+        .property("Mesh", &[("ObjectProperty", 0)], 0, |b| {
+            b.bytes(&0i32.to_le_bytes());
+        })
+        .none();
+    let properties = read_properties(&builder);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct SlotRow {
+        slots: Vec<i32>,
+        icon: String,
+        mesh: String,
+    }
+    let object_text = |index: PackageIndex| match index.index {
+        -2 => "/Game/UI/T_Icon.T_Icon".to_string(),
+        _ => "None".to_string(),
+    };
+    let row: SlotRow =
+        from_properties_with(&properties, &object_text).expect("managed to deserialize the row");
+    assert_eq!(
+        row,
+        SlotRow {
+            slots: vec![4, 5],
+            icon: "/Game/UI/T_Icon.T_Icon".into(),
+            mesh: "None".into(),
+        }
+    );
+
+    let row: SlotRow = from_properties(&properties).expect("managed to deserialize the row");
+    assert_eq!(row.icon, "-2");
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TagRow {
+    tag: String,
+    dev_comment: String,
+}
+
+#[test]
+fn test_deserialize_data_table_rows() {
+    let file = File::open(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+    let data_table = parser
+        .read_export(1)
+        .expect("managed to read the export")
+        .into_data_table()
+        .expect("export is a data table");
+
+    let rows: Vec<TagRow> = data_table
+        .deserialize_rows()
+        .expect("managed to deserialize the rows");
+    assert_eq!(rows[0].tag, "Tag.Foo.Bar");
+    assert_eq!(rows[1].dev_comment, "Comment1");
+}
@@ -1,9 +1,19 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
 use std::{fs::File, io::Seek};
 
-use byteorder::{LittleEndian, ReadBytesExt};
-use common::test_data_path;
-use rust_uasset::{data::uclass::UClassData, uasset_parser::UassetParser};
-use rust_uasset::data::data_table::DataTable;
+use common::{TaggedPropertyBuilder, read_properties, test_data_path};
+use rust_uasset::custom_version::{FEditorObjectVersion, FUE5ReleaseStreamObjectVersion};
+use rust_uasset::fname::FName;
+use rust_uasset::import_entry::ImportEntry;
+use rust_uasset::package_index::PackageIndex;
+use rust_uasset::property_type_name::PropertyTypeName;
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::UassetSummary;
+use rust_uasset::usmap::{Usmap, UsmapProperty, UsmapSchema};
+use rust_uasset::data::data_table::{DataTable, DataTableRow};
+use rust_uasset::data::export_object::ExportObject;
 
 mod common;
 
@@ -23,6 +33,80 @@ fn test_uasset_summary_parser() {
     assert_eq!(parser.entries.len(), summary.export_count as usize);
 }
 
+#[test]
+fn test_custom_versions() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    assert_eq!(parser.custom_version::<FEditorObjectVersion>(), None);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    let names: Vec<_> = parser
+        .get_summary()
+        .custom_versions
+        .iter()
+        .map(|version| version.name())
+        .collect();
+    assert_eq!(
+        names,
+        vec![Some("FAnimPhysObjectVersion"), Some("FEditorObjectVersion")]
+    );
+    assert_eq!(parser.custom_version::<FEditorObjectVersion>(), Some(40));
+    assert_eq!(
+        parser.custom_version::<FUE5ReleaseStreamObjectVersion>(),
+        None
+    );
+}
+
+#[test]
+fn test_import_table_parser() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    assert_eq!(
+        parser.imports.len(),
+        parser.get_summary().import_count as usize
+    );
+
+    let row_struct = &parser.imports[6];
+    assert_eq!(row_struct.class_package.as_string(), "/Script/CoreUObject");
+    assert_eq!(row_struct.class_name.as_string(), "ScriptStruct");
+    assert_eq!(row_struct.object_name.as_string(), "GameplayTagTableRow");
+    assert_eq!(row_struct.outer_index, PackageIndex::new(-6));
+    assert!(!row_struct.import_optional);
+}
+
+#[test]
+fn test_object_path_resolution() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    let data_table = PackageIndex::from_export(1);
+    assert_eq!(parser.entries[1].class_index, PackageIndex::new(-3));
+    assert_eq!(
+        parser.object_path(data_table).as_deref(),
+        Some("/Game/P1/Dialogs/DT_MyTable.DT_MyTable")
+    );
+    assert_eq!(
+        parser.object_path(PackageIndex::from_export(0)).as_deref(),
+        Some("/Game/P1/Dialogs/DT_MyTable.DT_MyTable:AssetImportData")
+    );
+    assert_eq!(
+        parser.object_path(parser.entries[1].class_index).as_deref(),
+        Some("/Script/Engine.DataTable")
+    );
+    assert_eq!(parser.object_path(PackageIndex::new(0)), None);
+
+    // Outers pointing back at each other don't resolve
+    parser.entries[0].outer_index = PackageIndex::from_export(1);
+    parser.entries[1].outer_index = PackageIndex::from_export(0);
+    assert_eq!(parser.object_path(data_table), None);
+}
+
 #[test]
 fn test_export_data_parser() {
     let file_path = test_data_path("test_table_ue54.uasset");
@@ -40,3 +124,375 @@ fn test_export_data_parser() {
     let data_table: DataTable = parser.read().expect("Failed to parse data table");
     assert_eq!(data_table.rows.len(), 2);
 }
+
+#[test]
+fn test_export_dispatch() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    assert_eq!(parser.find_export("DataTable"), Some(1));
+    assert_eq!(parser.find_data_table(), Some(1));
+    assert_eq!(parser.export_class_name(2).as_deref(), Some("MetaData"));
+    assert!(matches!(parser.read_export(0), Ok(ExportObject::Object(_))));
+
+    let data_table = parser
+        .read_export(1)
+        .expect("Failed to parse data table")
+        .into_data_table()
+        .expect("export is a data table");
+    assert_eq!(data_table.rows.len(), 2);
+}
+
+#[test]
+fn test_data_table_row_values() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    parser
+        .reader
+        .seek(std::io::SeekFrom::Start(
+            parser.entries[1].serial_offset as u64,
+        ))
+        .expect("failed to seek");
+    let data_table: DataTable = parser.read().expect("Failed to parse data table");
+
+    let row = &data_table.rows[1];
+    assert_eq!(row.name.as_string(), "NewRow2");
+    assert_eq!(row.get_str("DevComment"), Some("Comment1"));
+    assert_eq!(row.get_i32("DevComment"), None);
+    assert_eq!(
+        row.string_values(),
+        vec![
+            ("Tag".to_string(), "Boo.Moo".to_string()),
+            ("DevComment".to_string(), "Comment1".to_string()),
+        ]
+    );
+    assert_eq!(data_table.string_rows()[1]["Name"], "NewRow2");
+}
+
+#[test]
+fn test_data_table_class_paths() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+    let class = parser.entries[1]
+        .class_index
+        .import_index()
+        .expect("class is an import");
+
+    parser.imports[class].object_name = FName::new("CompositeDataTable");
+    assert_eq!(parser.find_export("DataTable"), None);
+    assert_eq!(parser.find_data_table(), Some(1));
+    let export = parser.read_export(1).expect("managed to read the export");
+    assert_eq!(
+        export.as_data_table().map(|table| table.rows.len()),
+        Some(2)
+    );
+
+    // A class of the same name elsewhere isn't a data table
+    parser.imports[class].object_name = FName::new("DataTable");
+    parser.imports[class].outer_index = PackageIndex::new(-2);
+    assert_ne!(
+        parser.object_path(parser.entries[1].class_index).as_deref(),
+        Some("/Script/Engine.DataTable")
+    );
+    let export = parser.read_export(1).expect("managed to read the export");
+    assert!(export.as_data_table().is_none());
+    assert_eq!(parser.find_data_table(), None);
+}
+
+#[test]
+fn test_data_table_export() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+
+    parser
+        .reader
+        .seek(std::io::SeekFrom::Start(
+            parser.entries[1].serial_offset as u64,
+        ))
+        .expect("failed to seek");
+
+    let data_table: DataTable = parser.read().expect("Failed to parse data table");
+    assert_eq!(data_table.columns, vec!["Tag", "DevComment"]);
+    assert_eq!(
+        data_table.row_struct.as_deref(),
+        Some("/Script/GameplayTags.GameplayTagTableRow")
+    );
+    let schema: Vec<String> = data_table
+        .schema()
+        .iter()
+        .map(|field| format!("{}: {}", field.name, field.type_name))
+        .collect();
+    assert_eq!(schema, vec!["Tag: NameProperty", "DevComment: StrProperty"]);
+
+    let mut csv = vec![];
+    data_table.write_csv(&mut csv).expect("managed to write csv");
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "---,Tag,DevComment\n\
+         NewRow1,\"Tag.Foo.Bar\",\"Comment2\"\n\
+         NewRow2,\"Boo.Moo\",\"Comment1\"\n"
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        let mut json = vec![];
+        data_table.write_json(&mut json).expect("managed to write json");
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("[\n\t{\n\t\t\"Name\": \"NewRow1\",\n\t\t\"Tag\": \"Tag.Foo.Bar\","));
+    }
+}
+
+#[test]
+fn test_data_table_typed_export() {
+    let file_path = test_data_path("test_table_ue54.uasset");
+    let file = File::open(&file_path).expect("Failed to open test file");
+    let mut parser = UassetParser::new(file);
+    parser.parse_asset().expect("managed to parse the asset");
+    let mut data_table = parser
+        .read_export(1)
+        .expect("Failed to parse data table")
+        .into_data_table()
+        .expect("export is a data table");
+
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .property("Count", &[("IntProperty", 0)], 0, |b| {
+            b.bytes(&3i32.to_le_bytes());
+        })
+        .property("Scale", &[("FloatProperty", 0)], 0, |b| {
+            b.bytes(&1.5f32.to_le_bytes());
+        })
+        .property("bEnabled", &[("BoolProperty", 0)], 0x10, |_| {})
+        .property("Owner", &[("ObjectProperty", 0)], 0, |b| {
+            b.bytes(&(-3i32).to_le_bytes());
+        })
+        .property(
+            "Sizes",
+            &[("ArrayProperty", 1), ("IntProperty", 0)],
+            0,
+            |b| {
+                b.bytes(&2i32.to_le_bytes())
+                    .bytes(&1i32.to_le_bytes())
+                    .bytes(&2i32.to_le_bytes());
+            },
+        )
+        .property(
+            "Offset",
+            &[
+                ("StructProperty", 1),
+                ("IntPoint", 1),
+                ("/Script/CoreUObject", 0),
+            ],
+            0x08,
+            |b| {
+                b.bytes(&5i32.to_le_bytes()).bytes(&6i32.to_le_bytes());
+            },
+        )
+        .property(
+            "Weights",
+            &[
+                ("MapProperty", 2),
+                ("NameProperty", 0),
+                ("FloatProperty", 0),
+            ],
+            0,
+            |b| {
+                b.bytes(&0i32.to_le_bytes())
+                    .bytes(&1i32.to_le_bytes())
+                    .name("Heavy")
+                    .bytes(&2.5f32.to_le_bytes());
+            },
+        )
+        .none();
+    let properties = read_properties(&builder);
+    data_table.columns = properties
+        .iter()
+        .map(|property| property.tag.name.as_string())
+        .collect();
+    data_table.rows = vec![DataTableRow {
+        name: FName::new("Row1"),
+        properties,
+    }];
+
+    let mut csv = vec![];
+    data_table
+        .write_csv(&mut csv)
+        .expect("managed to write csv");
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "---,Count,Scale,bEnabled,Owner,Sizes,Offset,Weights\n\
+         Row1,\"3\",\"1.5\",\"True\",\"/Script/Engine.DataTable\",\"(1,2)\",\"(X=5,Y=6)\",\"((Heavy, 2.5))\"\n"
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        let mut json = vec![];
+        data_table
+            .write_json(&mut json)
+            .expect("managed to write json");
+        let values = |data_table: &DataTable| {
+            data_table.rows[0]
+                .properties
+                .iter()
+                .map(|property| property.value.clone())
+                .collect::<Vec<_>>()
+        };
+        let exported = values(&data_table);
+        data_table
+            .import_json(json.as_slice())
+            .expect("managed to import the json export");
+        assert_eq!(values(&data_table), exported);
+
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "Name": "Row1",
+                "Count": 3,
+                "Scale": 1.5,
+                "bEnabled": true,
+                "Owner": "/Script/Engine.DataTable",
+                "Sizes": [1, 2],
+                "Offset": { "X": 5, "Y": 6 },
+                "Weights": { "Heavy": 2.5 },
+            }])
+        );
+    }
+
+    data_table
+        .import_csv(
+            "---,Owner\n\
+             Row1,\"/Script/Engine.Default__DataTable\"\n"
+                .as_bytes(),
+        )
+        .expect_err("objects outside the package don't import");
+    data_table
+        .import_csv(
+            "---,Owner\n\
+             Row1,\"/Game/P1/Dialogs/DT_MyTable.DT_MyTable\"\n"
+                .as_bytes(),
+        )
+        .expect("managed to import an object path");
+    assert_eq!(
+        data_table.rows[0].get_object("Owner"),
+        Some(PackageIndex::from_export(1))
+    );
+}
+
+#[test]
+fn test_data_table_columns() {
+    let mut builder = TaggedPropertyBuilder::default();
+    builder
+        .bytes(&[0])
+        .property("RowStruct", &[("ObjectProperty", 0)], 0, |b| {
+            b.bytes(&(-1i32).to_le_bytes());
+        })
+        .none()
+        .bytes(&0u32.to_le_bytes())
+        .bytes(&2i32.to_le_bytes());
+    // Rows skip the properties left at their defaults
+    let rarity = [("EnumProperty", 2), ("ERarity", 0), ("ByteProperty", 0)];
+    builder
+        .name("Sword")
+        .property("Damage", &[("IntProperty", 0)], 0, |b| {
+            b.bytes(&5i32.to_le_bytes());
+        })
+        .property("Rarity", &rarity, 0, |b| {
+            b.name("ERarity::Epic");
+        })
+        .none();
+    builder
+        .name("Shield")
+        .property("Weight", &[("FloatProperty", 0)], 0, |b| {
+            b.bytes(&2.5f32.to_le_bytes());
+        })
+        .property("bTwoHanded", &[("BoolProperty", 0)], 0x10, |_| {})
+        .property("Rarity", &rarity, 0, |b| {
+            b.name("ERarity::Common");
+        })
+        .none();
+
+    let read_table = |mappings: Option<Usmap>| -> DataTable {
+        let mut parser = UassetParser::new(Cursor::new(builder.data.clone()));
+        parser.names = builder.names.clone();
+        parser.set_summary(UassetSummary {
+            file_version_ue5: 1012,
+            ..Default::default()
+        });
+        parser.imports = vec![ImportEntry {
+            class_package: FName::new("/Script/CoreUObject"),
+            class_name: FName::new("ScriptStruct"),
+            outer_index: PackageIndex::new(0),
+            object_name: FName::new("ItemRow"),
+            package_name: None,
+            import_optional: false,
+        }];
+        parser.mappings = mappings.map(Arc::new);
+        parser.read().expect("managed to read the data table")
+    };
+
+    let mut data_table = read_table(None);
+    assert_eq!(
+        data_table.columns,
+        vec!["Damage", "Weight", "bTwoHanded", "Rarity"]
+    );
+
+    // The mappings know the columns no row serializes
+    let properties = ["Damage", "Weight", "bTwoHanded", "Icon", "Rarity"]
+        .iter()
+        .enumerate()
+        .map(|(index, name)| UsmapProperty {
+            schema_index: index as u16,
+            array_size: 1,
+            name: name.to_string(),
+            type_name: PropertyTypeName::new("IntProperty"),
+        })
+        .collect();
+    let schema = UsmapSchema {
+        name: "ItemRow".to_string(),
+        super_type: None,
+        property_count: 5,
+        properties,
+    };
+    let mappings = Usmap {
+        schemas: HashMap::from([("ItemRow".to_string(), schema)]),
+        ..Default::default()
+    };
+    assert_eq!(
+        read_table(Some(mappings)).columns,
+        vec!["Damage", "Weight", "bTwoHanded", "Icon", "Rarity"]
+    );
+
+    let mut csv = vec![];
+    data_table
+        .write_csv(&mut csv)
+        .expect("managed to write csv");
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(
+        csv,
+        "---,Damage,Weight,bTwoHanded,Rarity\n\
+         Sword,\"5\",\"\",\"\",\"Epic\"\n\
+         Shield,\"\",\"2.5\",\"True\",\"Common\"\n"
+    );
+
+    // Enum values get their prefix back
+    data_table
+        .import_csv(csv.replace("Epic", "Rare").as_bytes())
+        .expect("managed to import the csv");
+    assert_eq!(
+        data_table.rows[0]
+            .get_name("Rarity")
+            .map(|rarity| rarity.as_string()),
+        Some("ERarity::Rare".to_string())
+    );
+    assert_eq!(data_table.rows[1].get_bool("bTwoHanded"), Some(true));
+}
@@ -0,0 +1,286 @@
+mod common;
+
+use common::test_data_path;
+use rust_uasset::{
+    data::data_table::DataTable, data::export_object::ExportObject, errors::ParseError,
+    uasset_parser::UassetParser, uasset_writer::UassetWriter,
+};
+use std::io::{Cursor, Seek};
+
+fn read_data_table<R: std::io::Read + Seek>(parser: &mut UassetParser<R>) -> DataTable {
+    let index = parser
+        .find_export("DataTable")
+        .expect("found the data table");
+    parser
+        .read_export(index)
+        .expect("Failed to parse data table")
+        .into_data_table()
+        .expect("export is a data table")
+}
+
+#[test]
+fn test_data_table_import_and_write() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+
+    let mut data_table = read_data_table(&mut parser);
+    data_table
+        .import_csv(
+            "---,Tag,DevComment\n\
+             NewRow1,\"Tag.Foo.Bar\",\"Changed, with \"\"quotes\"\"\"\n\
+             NewRow3,\"Boo.Moo\",\"Added\"\n"
+                .as_bytes(),
+        )
+        .expect("managed to import csv");
+
+    let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+    writer
+        .write(&data_table)
+        .expect("managed to write data table");
+    let mut export_data: Vec<Vec<u8>> = (0..parser.entries.len())
+        .map(|i| parser.read_export_data(i).unwrap())
+        .collect();
+    export_data[1] = writer.writer.get_ref().clone();
+
+    writer.writer = Cursor::new(vec![]);
+    writer
+        .write_package(&mut parser, &export_data)
+        .expect("managed to write the package");
+    let package = writer.into_inner().into_inner();
+
+    let mut parser = UassetParser::new(Cursor::new(package));
+    parser
+        .parse_asset()
+        .expect("managed to parse the written asset");
+    assert!(parser.names.contains(&"NewRow3".to_string()));
+    let data_table_export = &parser.entries[1];
+    assert_eq!(
+        parser.entries[2].serial_offset,
+        data_table_export.serial_offset + data_table_export.serial_size
+    );
+    assert_eq!(
+        parser.object_path(parser.entries[0].class_index).as_deref(),
+        Some("/Script/Engine.AssetImportData")
+    );
+
+    let data_table = read_data_table(&mut parser);
+    let mut csv = vec![];
+    data_table
+        .write_csv(&mut csv)
+        .expect("managed to write csv");
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "---,Tag,DevComment\n\
+         NewRow1,\"Tag.Foo.Bar\",\"Changed, with \"\"quotes\"\"\"\n\
+         NewRow3,\"Boo.Moo\",\"Added\"\n"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_data_table_json_import() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+
+    let mut data_table = read_data_table(&mut parser);
+    data_table
+        .import_json(r#"[{"Name": "NewRow2", "Tag": "Tag.Foo.Bar"}]"#.as_bytes())
+        .expect("managed to import json");
+    assert_eq!(data_table.rows.len(), 1);
+    let row = &data_table.rows[0];
+    assert_eq!(
+        row.get_value("Tag").map(|value| value.as_string()),
+        Some("Tag.Foo.Bar".to_string())
+    );
+    assert_eq!(row.get_str("DevComment"), Some("Comment1"));
+
+    let error = data_table.import_json(r#"[{"Name": "NewRow2", "Missing": 1}]"#.as_bytes());
+    assert!(error.is_err());
+}
+
+#[test]
+fn test_package_round_trip() {
+    for file_name in ["test_table_ue54.uasset", "DT_MyTable1.uasset"] {
+        let data = std::fs::read(test_data_path(file_name)).unwrap();
+        let mut parser = UassetParser::new(Cursor::new(data.clone()));
+        parser.parse_asset().expect("managed to parse the asset");
+
+        let export_data: Vec<Vec<u8>> = (0..parser.entries.len())
+            .map(|i| parser.read_export_data(i).unwrap())
+            .collect();
+        // Re-serializing the rows gives back the same payload
+        let data_table = read_data_table(&mut parser);
+        let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+        writer
+            .write(&data_table)
+            .expect("managed to write data table");
+        assert!(writer.writer.get_ref() == &export_data[1]);
+
+        // The metadata comes back from its properties and native tail
+        let ExportObject::Object(metadata) = parser.read_export(2).unwrap() else {
+            panic!("metadata isn't a plain object");
+        };
+        assert_eq!(metadata.class_data.object_guid, None);
+        assert_eq!(metadata.tail.len(), 53);
+        writer.writer = Cursor::new(vec![]);
+        writer
+            .write(&metadata)
+            .expect("managed to write the metadata");
+        assert!(writer.writer.get_ref() == &export_data[2]);
+
+        writer.writer = Cursor::new(vec![]);
+        writer
+            .write_package(&mut parser, &export_data)
+            .expect("managed to write the package");
+
+        assert!(
+            writer.into_inner().into_inner() == data,
+            "{} differs",
+            file_name
+        );
+    }
+}
+
+#[test]
+fn test_names_referenced_from_export_data() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+    let count = parser
+        .get_summary()
+        .names_referenced_from_export_data_count
+        .unwrap() as usize;
+    assert!(count < parser.names.len());
+
+    // A new name and one only the import table referenced so far
+    let mut data_table = read_data_table(&mut parser);
+    data_table
+        .import_csv("---,Tag\nNewRow3,\"Boo.Moo\"\nDataTable,\"Tag.Foo.Bar\"\n".as_bytes())
+        .expect("managed to import csv");
+
+    let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+    writer
+        .write(&data_table)
+        .expect("managed to write data table");
+    let mut export_data: Vec<Vec<u8>> = (0..parser.entries.len())
+        .map(|i| parser.read_export_data(i).unwrap())
+        .collect();
+    export_data[1] = writer.writer.get_ref().clone();
+    writer.writer = Cursor::new(vec![]);
+    writer
+        .write_package(&mut parser, &export_data)
+        .expect("managed to write the package");
+
+    let mut parser = UassetParser::new(Cursor::new(writer.into_inner().into_inner()));
+    parser
+        .parse_asset()
+        .expect("managed to parse the written asset");
+    let summary = parser.get_summary();
+    assert_eq!(
+        summary.names_referenced_from_export_data_count,
+        Some(count as i32 + 2)
+    );
+    let (export_names, header_names) = parser.names.split_at(count + 2);
+    assert!(export_names.contains(&"NewRow3".to_string()));
+    assert!(export_names.contains(&"DataTable".to_string()));
+    assert!(header_names.contains(&"/Script/Engine".to_string()));
+    assert!(!header_names.contains(&"Tag".to_string()));
+
+    assert_eq!(
+        parser.object_path(parser.entries[1].class_index).as_deref(),
+        Some("/Script/Engine.DataTable")
+    );
+    let data_table = read_data_table(&mut parser);
+    let rows: Vec<String> = data_table
+        .rows
+        .iter()
+        .map(|row| row.name.as_string())
+        .collect();
+    assert_eq!(rows, vec!["NewRow3", "DataTable"]);
+    assert_eq!(
+        data_table.rows[1]
+            .get_name("Tag")
+            .map(|tag| tag.as_string()),
+        Some("Tag.Foo.Bar".to_string())
+    );
+}
+
+#[test]
+fn test_write_package_export_count() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+
+    let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+    assert!(matches!(
+        writer.write_package(&mut parser, &[vec![]]),
+        Err(ParseError::ExportCountMismatch {
+            expected: 3,
+            actual: 1
+        })
+    ));
+}
+
+#[test]
+fn test_write_package_invalid_header_offsets() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+    let export_data: Vec<Vec<u8>> = (0..parser.entries.len())
+        .map(|i| parser.read_export_data(i).unwrap())
+        .collect();
+    let summary = parser.get_summary().clone();
+
+    // Tables in front of the name table, past the header and past the file
+    let mut import_before_names = summary.clone();
+    import_before_names.import_offset = 0;
+    let mut export_past_header = summary.clone();
+    export_past_header.export_offset = summary.total_header_size - 4;
+    let mut registry_past_header = summary.clone();
+    registry_past_header.asset_registry_data_offset = summary.total_header_size;
+    let mut header_past_file = summary.clone();
+    header_past_file.total_header_size = i32::MAX;
+    for summary in [
+        import_before_names,
+        export_past_header,
+        registry_past_header,
+        header_past_file,
+    ] {
+        parser.set_summary(summary);
+        let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+        assert!(matches!(
+            writer.write_package(&mut parser, &export_data),
+            Err(ParseError::InvalidFileOffset { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_read_export_data_out_of_file() {
+    let data = std::fs::read(test_data_path("test_table_ue54.uasset")).unwrap();
+    let file_size = data.len() as i64;
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.parse_asset().expect("managed to parse the asset");
+
+    parser.entries[1].serial_size = -1;
+    assert!(matches!(
+        parser.read_export_data(1),
+        Err(ParseError::InvalidArraySize(-1))
+    ));
+    parser.entries[1].serial_size = file_size;
+    assert!(matches!(
+        parser.read_export_data(1),
+        Err(ParseError::InvalidArraySize(_))
+    ));
+    parser.entries[1].serial_size = i64::MAX;
+    assert!(matches!(
+        parser.read_export_data(1),
+        Err(ParseError::InvalidArraySize(i32::MAX))
+    ));
+    assert!(matches!(
+        parser.read_export_data(3),
+        Err(ParseError::InvalidExportIndex(3))
+    ));
+}
@@ -0,0 +1,442 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use rust_uasset::errors::ParseError;
+use rust_uasset::fname::FName;
+use rust_uasset::package_index::PackageIndex;
+use rust_uasset::property_data::{FieldPath, PropertyData, PropertyValue, ScriptDelegate};
+use rust_uasset::property_type_name::PropertyTypeName;
+use rust_uasset::struct_value::{StructValue, Vector};
+use rust_uasset::uasset_parser::UassetParser;
+use rust_uasset::uasset_summary::{EPackageFlags, UassetSummary};
+use rust_uasset::uasset_writer::UassetWriter;
+use rust_uasset::usmap::{Usmap, UsmapProperty, UsmapSchema};
+use rust_uasset::versions::PackageFileVersion;
+
+mod common;
+
+/// Builds the uncompressed mappings payload, types are written pre-order
+/// as raw usmap property type bytes and name indices.
+#[derive(Default)]
+struct UsmapPayloadBuilder {
+    names: Vec<String>,
+    data: Vec<u8>,
+}
+
+impl UsmapPayloadBuilder {
+    fn name_index(&mut self, name: &str) -> u32 {
+        match self.names.iter().position(|n| n == name) {
+            Some(index) => index as u32,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() as u32 - 1
+            }
+        }
+    }
+
+    fn name(&mut self, name: &str) -> &mut Self {
+        let index = self.name_index(name);
+        self.data.extend_from_slice(&index.to_le_bytes());
+        self
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    fn finish(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        payload.extend_from_slice(&(self.names.len() as u32).to_le_bytes());
+        for name in &self.names {
+            payload.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            payload.extend_from_slice(name.as_bytes());
+        }
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+}
+
+fn test_mappings_payload() -> Vec<u8> {
+    // The name table is put in front once everything is written
+    let mut builder = UsmapPayloadBuilder::default();
+
+    builder.bytes(&1u32.to_le_bytes());
+    builder.name("EMode").bytes(&2u16.to_le_bytes());
+    builder.name("EMode::First").name("EMode::Second");
+
+    builder.bytes(&3u32.to_le_bytes());
+
+    builder.name("TestBase").bytes(&(-1i32).to_le_bytes());
+    builder
+        .bytes(&1u16.to_le_bytes())
+        .bytes(&1u16.to_le_bytes());
+    builder
+        .bytes(&0u16.to_le_bytes())
+        .bytes(&[1])
+        .name("BaseValue")
+        .bytes(&[2]);
+
+    builder.name("TestStruct").name("TestBase");
+    builder
+        .bytes(&9u16.to_le_bytes())
+        .bytes(&8u16.to_le_bytes());
+    builder
+        .bytes(&0u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Flag")
+        .bytes(&[1]);
+    builder
+        .bytes(&1u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Count")
+        .bytes(&[2]);
+    builder
+        .bytes(&2u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Scores")
+        .bytes(&[8, 3]);
+    builder
+        .bytes(&3u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Mode")
+        .bytes(&[26, 0]);
+    builder.name("EMode");
+    builder
+        .bytes(&4u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Location")
+        .bytes(&[9]);
+    builder.name("Vector");
+    builder
+        .bytes(&5u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Label")
+        .bytes(&[10]);
+    builder
+        .bytes(&6u16.to_le_bytes())
+        .bytes(&[2])
+        .name("Slots")
+        .bytes(&[2]);
+    builder
+        .bytes(&8u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Inner")
+        .bytes(&[9]);
+    builder.name("InnerStruct");
+
+    builder.name("InnerStruct").bytes(&(-1i32).to_le_bytes());
+    builder
+        .bytes(&1u16.to_le_bytes())
+        .bytes(&1u16.to_le_bytes());
+    builder
+        .bytes(&0u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Amount")
+        .bytes(&[2]);
+
+    builder.finish()
+}
+
+fn usmap_file(compression_method: u8, payload: &[u8], decompressed_size: usize) -> Vec<u8> {
+    let mut data = vec![];
+    data.extend_from_slice(&0x30C4u16.to_le_bytes());
+    // LargeEnums, with versioning
+    data.push(3);
+    data.extend_from_slice(&1i32.to_le_bytes());
+    data.extend_from_slice(&522i32.to_le_bytes());
+    data.extend_from_slice(&1012i32.to_le_bytes());
+    data.extend_from_slice(&0i32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+
+    data.push(compression_method);
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(&(decompressed_size as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+fn test_mappings() -> Usmap {
+    let payload = test_mappings_payload();
+    Usmap::from_reader(Cursor::new(usmap_file(0, &payload, payload.len())))
+        .expect("managed to read mappings")
+}
+
+#[test]
+fn test_usmap_loading() {
+    let usmap = test_mappings();
+
+    assert_eq!(
+        usmap.file_version,
+        Some(PackageFileVersion {
+            file_version_ue4: 522,
+            file_version_ue5: 1012,
+        })
+    );
+    assert_eq!(usmap.enums["EMode"].value_name(1), Some("EMode::Second"));
+
+    let test_struct = &usmap.schemas["TestStruct"];
+    assert_eq!(test_struct.super_type.as_deref(), Some("TestBase"));
+    assert_eq!(test_struct.properties.len(), 8);
+    assert_eq!(
+        test_struct.properties[3].type_name.to_string(),
+        "EnumProperty(EMode,ByteProperty)"
+    );
+    assert_eq!(
+        test_struct.properties[2].type_name.to_string(),
+        "ArrayProperty(FloatProperty)"
+    );
+
+    let properties = usmap.flattened_properties("TestStruct").unwrap();
+    assert_eq!(properties.len(), 10);
+    let (slot, array_index) = properties[8].unwrap();
+    assert_eq!((slot.name.as_str(), array_index), ("Slots", 1));
+
+    // Same payload compressed with zstd
+    let payload = test_mappings_payload();
+    let compressed = ruzstd::encoding::compress_to_vec(
+        payload.as_slice(),
+        ruzstd::encoding::CompressionLevel::Fastest,
+    );
+    let compressed_usmap =
+        Usmap::from_reader(Cursor::new(usmap_file(3, &compressed, payload.len())))
+            .expect("managed to read compressed mappings");
+    assert_eq!(compressed_usmap, usmap);
+
+    // Oodle can't be decompressed
+    assert!(matches!(
+        Usmap::from_reader(Cursor::new(usmap_file(1, &payload, payload.len()))),
+        Err(ParseError::UnsupportedUsmapCompression(1))
+    ));
+}
+
+#[test]
+fn test_malformed_mappings() {
+    let payload = test_mappings_payload();
+
+    // Sizes past the end of the file are rejected before reading
+    let mut truncated = usmap_file(0, &payload, payload.len());
+    truncated.truncate(truncated.len() - 1);
+    assert!(matches!(
+        Usmap::from_reader(Cursor::new(truncated)),
+        Err(ParseError::InvalidFileOffset { .. })
+    ));
+    assert!(matches!(
+        Usmap::from_reader(Cursor::new(usmap_file(0, &payload, u32::MAX as usize))),
+        Err(ParseError::DecompressionFailed(_))
+    ));
+    let compressed = ruzstd::encoding::compress_to_vec(
+        payload.as_slice(),
+        ruzstd::encoding::CompressionLevel::Fastest,
+    );
+    assert!(matches!(
+        Usmap::from_reader(Cursor::new(usmap_file(3, &compressed, u32::MAX as usize))),
+        Err(ParseError::DecompressionFailed(_))
+    ));
+
+    // An array of arrays nested past any real property
+    let mut builder = UsmapPayloadBuilder::default();
+    builder
+        .bytes(&0u32.to_le_bytes())
+        .bytes(&1u32.to_le_bytes());
+    builder.name("Deep").bytes(&(-1i32).to_le_bytes());
+    builder
+        .bytes(&1u16.to_le_bytes())
+        .bytes(&1u16.to_le_bytes());
+    builder
+        .bytes(&0u16.to_le_bytes())
+        .bytes(&[1])
+        .name("Value")
+        .bytes(&[8; 64])
+        .bytes(&[2]);
+    let payload = builder.finish();
+    assert!(matches!(
+        Usmap::from_reader(Cursor::new(usmap_file(0, &payload, payload.len()))),
+        Err(ParseError::PropertyTypeTooDeep(_))
+    ));
+}
+
+#[test]
+fn test_cyclic_super_structs() {
+    let mut usmap = test_mappings();
+    usmap.schemas.get_mut("TestBase").unwrap().super_type = Some("TestStruct".to_string());
+    assert!(matches!(
+        usmap.flattened_properties("TestStruct"),
+        Err(ParseError::CyclicUsmapSchema(_))
+    ));
+    assert!(matches!(
+        usmap.flattened_properties("Missing"),
+        Err(ParseError::MissingSchema(_))
+    ));
+}
+
+#[test]
+fn test_unversioned_properties() {
+    let mut data = vec![];
+    // Skip BaseValue, then Flag..Label with Count and Location zeroed
+    data.extend_from_slice(&(1u16 | 0x80 | (6 << 9)).to_le_bytes());
+    // Skip Slots[0], then Slots[1] and Inner
+    data.extend_from_slice(&(1u16 | 0x100 | (2 << 9)).to_le_bytes());
+    data.push(0b010010);
+
+    data.push(1);
+    data.extend_from_slice(&1i32.to_le_bytes());
+    data.extend_from_slice(&1.5f32.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(&3i32.to_le_bytes());
+    data.extend_from_slice(b"Hi\0");
+    data.extend_from_slice(&7i32.to_le_bytes());
+    data.extend_from_slice(&(0x100u16 | (1 << 9)).to_le_bytes());
+    data.extend_from_slice(&3i32.to_le_bytes());
+
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.set_summary(UassetSummary {
+        file_version_ue5: 1012,
+        package_flags: EPackageFlags::UnversionedProperties as u32,
+        ..Default::default()
+    });
+    parser.mappings = Some(Arc::new(test_mappings()));
+
+    let properties = parser
+        .read_properties("TestStruct")
+        .expect("managed to read unversioned properties");
+
+    let summary: Vec<(String, i32, PropertyValue)> = properties
+        .iter()
+        .map(|p: &PropertyData| (p.tag.name.as_string(), p.tag.array_index, p.value.clone()))
+        .collect();
+
+    assert_eq!(summary.len(), 8);
+    assert_eq!(summary[0].2, PropertyValue::BoolProperty(true));
+    assert_eq!(
+        summary[1],
+        ("Count".into(), 0, PropertyValue::IntProperty(0))
+    );
+    assert_eq!(
+        summary[2].2,
+        PropertyValue::ArrayProperty(vec![PropertyValue::FloatProperty(1.5)])
+    );
+    assert_eq!(summary[3].2.as_string(), "EMode::Second");
+    assert_eq!(
+        summary[4].2,
+        PropertyValue::StructProperty(StructValue::Vector(Vector::default()))
+    );
+    assert_eq!(summary[5].2, PropertyValue::StrProperty("Hi".into()));
+    assert_eq!(
+        summary[6],
+        ("Slots".into(), 1, PropertyValue::IntProperty(7))
+    );
+    assert_eq!(summary[7].2.as_string(), "(Amount=3)");
+}
+
+#[test]
+fn test_unversioned_string_delegate_and_field_path_values() {
+    let mut usmap = test_mappings();
+    let properties = [
+        ("Title", "Utf8StrProperty"),
+        ("Code", "AnsiStrProperty"),
+        ("OnUse", "DelegateProperty"),
+        ("OnChanged", "MulticastDelegateProperty"),
+        ("Watched", "FieldPathProperty"),
+    ];
+    usmap.schemas.insert(
+        "Callbacks".to_string(),
+        UsmapSchema {
+            name: "Callbacks".to_string(),
+            super_type: None,
+            property_count: properties.len() as u16,
+            properties: properties
+                .iter()
+                .enumerate()
+                .map(|(index, (name, type_name))| UsmapProperty {
+                    schema_index: index as u16,
+                    array_size: 1,
+                    name: name.to_string(),
+                    type_name: PropertyTypeName::new(*type_name),
+                })
+                .collect(),
+        },
+    );
+
+    let mut data = vec![];
+    data.extend_from_slice(&(0x100u16 | (5 << 9)).to_le_bytes());
+    data.extend_from_slice(&7i32.to_le_bytes());
+    data.extend_from_slice("Héllo\0".as_bytes());
+    data.extend_from_slice(&3i32.to_le_bytes());
+    data.extend_from_slice(&[b'C', 0xE9, 0]);
+    data.extend_from_slice(&(-1i32).to_le_bytes());
+    data.extend_from_slice(&[0; 8]);
+    // The multicast delegate has no bindings
+    data.extend_from_slice(&0i32.to_le_bytes());
+    data.extend_from_slice(&1i32.to_le_bytes());
+    data.extend_from_slice(&1i32.to_le_bytes());
+    data.extend_from_slice(&0i32.to_le_bytes());
+    data.extend_from_slice(&1i32.to_le_bytes());
+
+    let mut parser = UassetParser::new(Cursor::new(data.clone()));
+    parser.names = vec!["Use".to_string(), "Health".to_string()];
+    parser.set_summary(UassetSummary {
+        file_version_ue5: 1012,
+        package_flags: EPackageFlags::UnversionedProperties as u32,
+        ..Default::default()
+    });
+    parser.mappings = Some(Arc::new(usmap));
+
+    let values: Vec<PropertyValue> = parser
+        .read_properties("Callbacks")
+        .expect("managed to read unversioned properties")
+        .into_iter()
+        .map(|property| property.value)
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            PropertyValue::Utf8StrProperty("Héllo".into()),
+            PropertyValue::AnsiStrProperty("Cé".into()),
+            PropertyValue::DelegateProperty(ScriptDelegate {
+                object: PackageIndex::new(-1),
+                function_name: FName::new("Use"),
+            }),
+            PropertyValue::MulticastDelegateProperty(vec![]),
+            PropertyValue::FieldPathProperty(FieldPath {
+                path: vec![FName::new("Health")],
+                owner: PackageIndex::new(1),
+            }),
+        ]
+    );
+    assert_eq!(values[2].as_string(), "-1.Use");
+    assert_eq!(values[4].as_string(), "1:Health");
+
+    // Values are written back the way they were read
+    let mut writer = UassetWriter::for_package(Cursor::new(vec![]), &parser);
+    for (value, (_, type_name)) in values.iter().zip(properties) {
+        writer
+            .write_property_value(&PropertyTypeName::new(type_name), value)
+            .expect("managed to write the value");
+    }
+    assert_eq!(writer.into_inner().into_inner(), data[2..]);
+}
+
+#[test]
+fn test_unversioned_package_summary() {
+    let mut data = std::fs::read(common::test_data_path("test_table_ue54.uasset")).unwrap();
+    // Zero the UE4, UE5 and licensee versions
+    data[12..24].fill(0);
+
+    let mut parser = UassetParser::new(Cursor::new(data.clone()));
+    assert!(matches!(
+        parser.parse_asset(),
+        Err(ParseError::MissingUnversionedFileVersion)
+    ));
+
+    let mut parser = UassetParser::new(Cursor::new(data));
+    parser.mappings = Some(Arc::new(test_mappings()));
+    parser
+        .parse_asset()
+        .expect("managed to parse unversioned asset");
+
+    let summary = parser.get_summary();
+    assert!(summary.unversioned);
+    assert_eq!(summary.file_version_ue5, 1012);
+    assert_eq!(parser.imports.len(), 7);
+}